@@ -0,0 +1,167 @@
+use std::error::Error;
+use std::fmt;
+use std::future::Future;
+use std::pin::pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Weak};
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::{self, Thread};
+use std::time::{Duration, Instant};
+
+use super::Actor;
+use crate::sync::async_channel::{self, Receiver, Sender, TrySendError};
+
+pub use crate::sync::async_channel::SendError;
+
+/// actor 에게 message 를 보내는 handle
+///
+/// mailbox 의 Sender 는 Addr 들이 공유하는 Inner 하나만 가지고 있다.
+/// 모든 Addr 가 drop 되면 mailbox 가 disconnect 되어 actor 가 종료된다.
+/// (Context 는 Weak 만 가지고 있으므로 actor 가 자기 자신을 살려두지 않는다)
+pub struct Addr<A: Actor> {
+    inner: Arc<Inner<A>>,
+}
+
+pub(super) struct Inner<A: Actor> {
+    pub(super) tx: Sender<A::Msg>,
+    pub(super) alive: Arc<AtomicBool>,
+}
+
+impl<A: Actor> Addr<A> {
+    pub(super) fn from_inner(inner: Arc<Inner<A>>) -> Self {
+        Addr { inner }
+    }
+
+    pub(super) fn downgrade(&self) -> Weak<Inner<A>> {
+        Arc::downgrade(&self.inner)
+    }
+
+    /// mailbox 가 가득 차 있으면 빈 자리가 생길 때까지 thread 를 재우고 대기 (back pressure)
+    /// actor 가 이미 종료되었으면 보내려던 message 를 SendError 로 돌려준다.
+    ///
+    /// runtime 의 task (다른 actor 의 handle 포함) 안에서는 worker thread 를 막으므로 send_async 나 try_send 를 사용한다.
+    pub fn send(&self, msg: A::Msg) -> Result<(), SendError<A::Msg>> {
+        futures::executor::block_on(self.send_async(msg))
+    }
+
+    /// mailbox 가 가득 차 있으면 task 만 대기
+    pub fn send_async(&self, msg: A::Msg) -> async_channel::Send<'_, A::Msg> {
+        self.inner.tx.send(msg)
+    }
+
+    /// 대기하지 않고 바로 시도
+    pub fn try_send(&self, msg: A::Msg) -> Result<(), TrySendError<A::Msg>> {
+        self.inner.tx.try_send(msg)
+    }
+
+    /// 응답을 받을 Reply 를 담은 message 를 보내고 응답이 올 때까지 대기
+    ///
+    /// ```ignore
+    /// let count = addr.ask(|reply| Msg::Get(reply))?;
+    /// ```
+    pub fn ask<R, F>(&self, f: F) -> Result<R, AskError>
+    where
+        F: FnOnce(Reply<R>) -> A::Msg,
+    {
+        futures::executor::block_on(self.ask_async(f))
+    }
+
+    /// ask 의 async 버전 (응답을 기다리는 동안 worker thread 를 막지 않는다)
+    pub async fn ask_async<R, F>(&self, f: F) -> Result<R, AskError>
+    where
+        F: FnOnce(Reply<R>) -> A::Msg,
+    {
+        let (reply, rx) = Reply::new();
+        self.send_async(f(reply))
+            .await
+            .map_err(|_| AskError::Stopped)?;
+        rx.recv().await.map_err(|_| AskError::NoReply)
+    }
+
+    /// ask 와 같지만 timeout 만큼만 기다린다. (mailbox 가 가득 차서 보내지 못한 시간 포함)
+    pub fn ask_timeout<R, F>(&self, timeout: Duration, f: F) -> Result<R, AskError>
+    where
+        F: FnOnce(Reply<R>) -> A::Msg,
+    {
+        block_on_timeout(self.ask_async(f), timeout).unwrap_or(Err(AskError::Timeout))
+    }
+
+    /// actor 가 아직 message 를 처리하고 있는지
+    pub fn is_alive(&self) -> bool {
+        self.inner.alive.load(Ordering::Acquire)
+    }
+}
+
+impl<A: Actor> Clone for Addr<A> {
+    fn clone(&self) -> Self {
+        Addr {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+/// ask 의 응답을 보내는 oneshot sender (한번만 보낼 수 있도록 self 를 소비)
+pub struct Reply<R> {
+    tx: Sender<R>,
+}
+
+impl<R> Reply<R> {
+    fn new() -> (Reply<R>, Receiver<R>) {
+        let (tx, rx) = async_channel::channel(1);
+        (Reply { tx }, rx)
+    }
+
+    /// 요청한 쪽이 이미 기다리지 않으면 (timeout) 응답은 버려진다.
+    pub fn send(self, value: R) {
+        let _ = self.tx.try_send(value);
+    }
+}
+
+/// ask 가 실패한 이유
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AskError {
+    /// actor 가 이미 종료됨
+    Stopped,
+    /// actor 가 응답하지 않고 Reply 를 drop 함 (처리 중 panic 포함)
+    NoReply,
+    /// 시간 안에 응답이 오지 않음
+    Timeout,
+}
+
+impl fmt::Display for AskError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AskError::Stopped => write!(f, "actor has stopped"),
+            AskError::NoReply => write!(f, "actor dropped the reply without responding"),
+            AskError::Timeout => write!(f, "timed out waiting for a reply"),
+        }
+    }
+}
+
+impl Error for AskError {}
+
+// 현재 thread 를 재우면서 future 를 poll 한다. timeout 이 지나면 None
+fn block_on_timeout<F: Future>(future: F, timeout: Duration) -> Option<F::Output> {
+    struct ThreadWaker(Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    let deadline = Instant::now() + timeout;
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    let mut future = pin!(future);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return Some(output);
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            return None;
+        }
+        thread::park_timeout(deadline - now);
+    }
+}
@@ -0,0 +1,330 @@
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock, Weak};
+use std::time::{Duration, Instant};
+
+use crate::runtime::multi;
+use crate::runtime::{Runtime, Spawner};
+use crate::scope;
+use crate::sync::async_channel::{self, Receiver};
+
+/// actor 에게 message 를 보내는 Addr 와 ask 의 응답 (Reply)
+pub mod addr;
+/// 이름으로 actor 를 찾는 registry
+pub mod registry;
+
+pub use addr::{Addr, AskError, Reply, SendError};
+pub use registry::{registry, RegisterError, Registry};
+
+/// ch06 의 actor model
+///
+/// actor 는 자신의 상태를 다른 thread 와 공유하지 않고, mailbox 로 받은 message 를 하나씩 처리하면서만 상태를 바꾼다.
+/// 따라서 상태에 lock 이 필요 없고, 다른 actor 와는 message 로만 통신한다.
+///
+/// 각 actor 는 runtime::multi 의 task 하나로 실행되고, mailbox 는 길이가 제한된 sync::async_channel 이다.
+/// - message 를 기다리는 동안 worker thread 를 차지하지 않으므로 actor 를 수천개 만들어도 thread 는 늘지 않는다.
+/// - mailbox 가 가득 차면 보내는 쪽이 대기한다. (send 는 thread 를, send_async 는 task 를)
+/// - handle 은 동기 함수라서 실행하는 동안 worker 하나를 쓴다. 오래 block 하는 작업은 spawn_blocking 으로 넘긴다.
+pub trait Actor: Send + Sized + 'static {
+    type Msg: Send + 'static;
+
+    fn handle(&mut self, msg: Self::Msg, ctx: &mut Context<Self>);
+
+    /// 첫 message 를 처리하기 전 (restart 된 경우 새 actor 에서도) 호출
+    fn started(&mut self, _ctx: &mut Context<Self>) {}
+
+    /// actor 가 종료될 때 호출 (panic 으로 종료된 경우는 제외)
+    fn stopped(&mut self) {}
+}
+
+/// handle 안에서 사용하는 actor 자신의 정보
+pub struct Context<A: Actor> {
+    addr: Weak<addr::Inner<A>>,
+    stopped: bool,
+    restarts: usize,
+}
+
+impl<A: Actor> Context<A> {
+    /// 자기 자신의 Addr. 외부의 Addr 가 모두 사라져 종료 중이면 None
+    pub fn addr(&self) -> Option<Addr<A>> {
+        self.addr.upgrade().map(Addr::from_inner)
+    }
+
+    /// 지금 처리 중인 message 이후로는 처리하지 않고 종료
+    pub fn stop(&mut self) {
+        self.stopped = true;
+    }
+
+    /// 지금까지 restart 된 횟수
+    pub fn restarts(&self) -> usize {
+        self.restarts
+    }
+}
+
+/// handle 에서 panic 이 발생했을 때의 처리
+///
+/// restart 하면 factory 로 새 actor 를 만들어 mailbox 의 다음 message 부터 처리한다.
+/// (panic 이 발생한 message 는 버려지고, 그 message 의 Reply 는 NoReply 가 된다)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RestartStrategy {
+    /// 종료
+    Never,
+    /// 항상 restart
+    Always,
+    /// within 시간 안에 max_restarts 번을 넘게 panic 하면 종료
+    Limited { max_restarts: usize, within: Duration },
+}
+
+// restart 여부를 판단하기 위해 최근 restart 시각을 기록
+struct Supervisor {
+    strategy: RestartStrategy,
+    history: Vec<Instant>,
+}
+
+impl Supervisor {
+    fn should_restart(&mut self) -> bool {
+        match self.strategy {
+            RestartStrategy::Never => false,
+            RestartStrategy::Always => true,
+            RestartStrategy::Limited { max_restarts, within } => {
+                let now = Instant::now();
+                self.history.retain(|t| now.duration_since(*t) < within);
+                if self.history.len() >= max_restarts {
+                    return false;
+                }
+                self.history.push(now);
+                true
+            }
+        }
+    }
+}
+
+// runtime 의 task 나 block_on 안이면 그 runtime, 아니면 actor 용 공용 runtime (process 가 끝날 때까지 유지)
+fn current_spawner() -> Spawner {
+    static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+    multi::try_current().unwrap_or_else(|| RUNTIME.get_or_init(Runtime::default).get_spawner())
+}
+
+/// actor 를 실행하고 Addr 를 반환 (panic 하면 종료)
+///
+/// runtime 안에서 호출하면 그 runtime 에서, 밖에서 호출하면 cpu 수 만큼의 worker 를 가진 공용 runtime 에서 실행한다.
+pub fn spawn<A: Actor>(actor: A, mailbox: usize) -> Addr<A> {
+    let mut actor = Some(actor);
+    spawn_supervised(move || actor.take().unwrap(), RestartStrategy::Never, mailbox)
+}
+
+/// factory 로 만든 actor 를 실행하고, handle 에서 panic 하면 strategy 에 따라 factory 로 다시 만든다.
+/// (factory 나 started 에서 panic 하면 restart 하지 않고 종료)
+pub fn spawn_supervised<A, F>(factory: F, strategy: RestartStrategy, mailbox: usize) -> Addr<A>
+where
+    A: Actor,
+    F: FnMut() -> A + Send + 'static,
+{
+    spawn_supervised_on(&current_spawner(), factory, strategy, mailbox)
+}
+
+/// spawn_supervised 와 같지만 spawner 의 runtime 에서 실행
+pub fn spawn_supervised_on<A, F>(
+    spawner: &Spawner,
+    factory: F,
+    strategy: RestartStrategy,
+    mailbox: usize,
+) -> Addr<A>
+where
+    A: Actor,
+    F: FnMut() -> A + Send + 'static,
+{
+    let (tx, rx) = async_channel::channel(mailbox);
+    let alive = Arc::new(AtomicBool::new(true));
+    let addr = Addr::from_inner(Arc::new(addr::Inner {
+        tx,
+        alive: alive.clone(),
+    }));
+
+    let ctx = Context {
+        addr: addr.downgrade(),
+        stopped: false,
+        restarts: 0,
+    };
+    let supervisor = Supervisor {
+        strategy,
+        history: Vec::new(),
+    };
+    // 끝난 actor 의 결과는 alive 로 알 수 있으므로 JoinHandle 은 필요 없다
+    drop(spawner.spawn(run(factory, supervisor, rx, ctx, alive)));
+
+    addr
+}
+
+async fn run<A, F>(
+    mut factory: F,
+    mut supervisor: Supervisor,
+    rx: Receiver<A::Msg>,
+    mut ctx: Context<A>,
+    alive: Arc<AtomicBool>,
+) where
+    A: Actor,
+    F: FnMut() -> A,
+{
+    // mailbox 를 닫기 전에 표시해야 ask 가 실패한 뒤 is_alive 가 false 가 된다.
+    // factory / started 의 panic 으로 끝나는 경우에도 표시하도록 guard 로 둔다. (rx 보다 먼저 drop)
+    let _alive = scope::guard(alive, |alive| alive.store(false, Ordering::Release));
+
+    let mut actor = factory();
+    actor.started(&mut ctx);
+
+    // 모든 Addr 가 사라지면 (mailbox 를 비운 뒤) Disconnected
+    while let Ok(msg) = rx.recv().await {
+        let result = panic::catch_unwind(AssertUnwindSafe(|| actor.handle(msg, &mut ctx)));
+        match result {
+            Ok(()) if ctx.stopped => break,
+            Ok(()) => {}
+            Err(_) if supervisor.should_restart() => {
+                // 상태가 깨졌을 수 있는 actor 는 버리고 새로 만든다
+                actor = factory();
+                ctx.restarts += 1;
+                actor.started(&mut ctx);
+            }
+            Err(_) => return,
+        }
+    }
+    actor.stopped();
+}
+
+#[cfg(test)]
+mod counter {
+    use super::*;
+
+    pub struct Counter {
+        pub count: i64,
+    }
+
+    pub enum Msg {
+        Add(i64),
+        Get(Reply<i64>),
+        Panic,
+        Stop,
+    }
+
+    impl Actor for Counter {
+        type Msg = Msg;
+
+        fn handle(&mut self, msg: Msg, ctx: &mut Context<Self>) {
+            match msg {
+                Msg::Add(n) => self.count += n,
+                Msg::Get(reply) => reply.send(self.count),
+                Msg::Panic => panic!("counter panicked"),
+                Msg::Stop => ctx.stop(),
+            }
+        }
+    }
+}
+
+/// 여러 thread 에서 send 한 뒤 ask 로 결과를 확인 (mailbox 는 순서대로 처리되므로 lock 이 필요 없다)
+#[test]
+fn send_and_ask() {
+    use counter::{Counter, Msg};
+    use std::thread;
+
+    const NUM_THREADS: i64 = 4;
+    const NUM_LOOP: i64 = 1_000;
+
+    let addr = spawn(Counter { count: 0 }, 16);
+    let mut v = Vec::new();
+    for _ in 0..NUM_THREADS {
+        let addr = addr.clone();
+        v.push(thread::spawn(move || {
+            for _ in 0..NUM_LOOP {
+                addr.send(Msg::Add(1)).unwrap();
+            }
+        }));
+    }
+    for t in v {
+        t.join().unwrap();
+    }
+
+    assert_eq!(addr.ask(Msg::Get), Ok(NUM_THREADS * NUM_LOOP));
+
+    // Stop 이후의 message 는 처리되지 않는다 (mailbox 에 남아있던 Reply 도 drop 된다)
+    addr.send(Msg::Stop).unwrap();
+    assert!(addr.ask(Msg::Get).is_err());
+}
+
+/// panic 하면 factory 로 새로 만들어진 actor 가 다음 message 부터 처리한다.
+#[test]
+fn restart() {
+    use counter::{Counter, Msg};
+
+    let addr = spawn_supervised(|| Counter { count: 100 }, RestartStrategy::Always, 4);
+    addr.send(Msg::Add(1)).unwrap();
+    assert_eq!(addr.ask(Msg::Get), Ok(101));
+
+    addr.send(Msg::Panic).unwrap();
+    // 상태는 초기화된다
+    assert_eq!(addr.ask(Msg::Get), Ok(100));
+    assert!(addr.is_alive());
+}
+
+/// 제한 횟수를 넘게 panic 하면 종료되고 ask 는 실패한다.
+#[test]
+fn restart_limit() {
+    use counter::{Counter, Msg};
+
+    let strategy = RestartStrategy::Limited {
+        max_restarts: 2,
+        within: Duration::from_secs(60),
+    };
+    let addr = spawn_supervised(|| Counter { count: 0 }, strategy, 4);
+    for _ in 0..2 {
+        addr.send(Msg::Panic).unwrap();
+        assert_eq!(addr.ask(Msg::Get), Ok(0));
+    }
+
+    addr.send(Msg::Panic).unwrap();
+    assert!(addr.ask(Msg::Get).is_err());
+    assert!(!addr.is_alive());
+}
+
+/// started 에서 panic 해도 is_alive 가 false 가 되고 ask 는 실패한다.
+#[test]
+fn panic_in_started() {
+    use counter::Msg;
+
+    struct Broken;
+
+    impl Actor for Broken {
+        type Msg = Msg;
+
+        fn handle(&mut self, _msg: Msg, _ctx: &mut Context<Self>) {}
+
+        fn started(&mut self, _ctx: &mut Context<Self>) {
+            panic!("failed to start");
+        }
+    }
+
+    let addr = spawn_supervised(|| Broken, RestartStrategy::Always, 4);
+    assert!(addr.ask(Msg::Get).is_err());
+    assert!(!addr.is_alive());
+}
+
+/// worker 2 개의 runtime 위에서 actor 수천개가 task 로 실행된다 (actor 마다 thread 를 만들지 않는다)
+#[test]
+fn thousands_of_actors() {
+    use counter::{Counter, Msg};
+
+    const NUM_ACTORS: i64 = 2_000;
+
+    let runtime = Runtime::new(2);
+    let total = runtime.block_on(async {
+        let addrs: Vec<_> = (0..NUM_ACTORS)
+            .map(|i| spawn(Counter { count: i }, 4))
+            .collect();
+        let mut total = 0;
+        for addr in &addrs {
+            addr.send_async(Msg::Add(1)).await.unwrap();
+            total += addr.ask_async(Msg::Get).await.unwrap();
+        }
+        total
+    });
+    assert_eq!(total, (0..NUM_ACTORS).map(|i| i + 1).sum::<i64>());
+}
@@ -0,0 +1,119 @@
+use std::any::Any;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::sync::{Mutex, OnceLock};
+
+use super::{Actor, Addr};
+
+/// 이름으로 actor 의 Addr 를 찾는 registry
+///
+/// actor 의 type 이 서로 다르므로 Addr 는 Any 로 저장하고, lookup 할 때 요청한 type 으로 downcast 한다.
+/// 등록된 Addr 는 registry 가 가지고 있으므로 unregister 할 때까지 actor 가 종료되지 않는다.
+/// (actor 가 스스로 종료되었으면 lookup 시 registry 에서 제거된다)
+#[derive(Default)]
+pub struct Registry {
+    actors: Mutex<HashMap<String, Entry>>,
+}
+
+/// 이미 같은 이름으로 살아있는 actor 가 등록되어 있음
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegisterError(pub String);
+
+impl fmt::Display for RegisterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "an actor named {:?} is already registered", self.0)
+    }
+}
+
+impl Error for RegisterError {}
+
+// 종료된 actor 의 이름은 다시 사용할 수 있도록 type 을 모르는 상태에서도 확인
+trait Liveness {
+    fn alive(&self) -> bool;
+}
+
+impl<A: Actor> Liveness for Addr<A> {
+    fn alive(&self) -> bool {
+        self.is_alive()
+    }
+}
+
+struct Entry {
+    addr: Box<dyn Any + Send + Sync>,
+    liveness: Box<dyn Liveness + Send + Sync>,
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Registry::default()
+    }
+
+    pub fn register<A: Actor>(&self, name: &str, addr: Addr<A>) -> Result<(), RegisterError> {
+        let mut actors = self.actors.lock().unwrap();
+        if actors.get(name).is_some_and(|e| e.liveness.alive()) {
+            return Err(RegisterError(name.to_string()));
+        }
+        let entry = Entry {
+            addr: Box::new(addr.clone()),
+            liveness: Box::new(addr),
+        };
+        actors.insert(name.to_string(), entry);
+        Ok(())
+    }
+
+    /// 이름이 없거나, 다른 type 의 actor 이거나, 종료되었으면 None
+    pub fn lookup<A: Actor>(&self, name: &str) -> Option<Addr<A>> {
+        let mut actors = self.actors.lock().unwrap();
+        let entry = actors.get(name)?;
+        if !entry.liveness.alive() {
+            actors.remove(name);
+            return None;
+        }
+        entry.addr.downcast_ref::<Addr<A>>().cloned()
+    }
+
+    /// 등록 해제. 다른 Addr 가 없으면 actor 는 mailbox 를 비운 뒤 종료된다.
+    pub fn unregister(&self, name: &str) -> bool {
+        self.actors.lock().unwrap().remove(name).is_some()
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.actors.lock().unwrap().keys().cloned().collect()
+    }
+}
+
+/// process 전체에서 공유하는 registry
+pub fn registry() -> &'static Registry {
+    static REGISTRY: OnceLock<Registry> = OnceLock::new();
+    REGISTRY.get_or_init(Registry::new)
+}
+
+#[test]
+fn named_actor() {
+    use super::{spawn, Context, Reply};
+
+    struct Echo;
+
+    impl Actor for Echo {
+        type Msg = (String, Reply<String>);
+
+        fn handle(&mut self, (msg, reply): Self::Msg, _ctx: &mut Context<Self>) {
+            reply.send(msg);
+        }
+    }
+
+    let registry = Registry::new();
+    registry.register("echo", spawn(Echo, 4)).unwrap();
+    assert_eq!(
+        registry.register("echo", spawn(Echo, 4)),
+        Err(RegisterError("echo".to_string()))
+    );
+
+    let echo = registry.lookup::<Echo>("echo").unwrap();
+    assert_eq!(echo.ask(|r| ("hello".to_string(), r)), Ok("hello".to_string()));
+    assert!(registry.lookup::<Echo>("unknown").is_none());
+
+    assert!(registry.unregister("echo"));
+    assert!(registry.names().is_empty());
+}
@@ -0,0 +1,184 @@
+use std::cell::RefCell;
+use std::cmp;
+use std::mem;
+
+/// 같은 type 의 값을 큰 chunk 에 차례로 놓는 bump allocator
+///
+/// - `alloc` 은 현재 chunk 의 끝에 값을 놓고 `&'arena mut T` 를 돌려준다. 값 하나마다 `Box` 를 할당하는 대신
+///   chunk 가 찰 때만 (이전 chunk 의 두 배 크기로) 할당하므로 할당 횟수가 O(log n) 이고, 값들이 memory 에 붙어있다.
+/// - 값을 하나씩 해제할 수는 없다. arena 가 drop 될 때 모든 값이 (drop 을 구현했으면 drop 을 실행하고) 한꺼번에 해제된다.
+/// - `&self` 로 할당하므로 arena 를 빌린 여러 값이 서로를 참조하는 구조 (tree, graph) 를 만들 수 있다.
+pub struct Arena<T> {
+    chunks: RefCell<Chunks<T>>,
+}
+
+struct Chunks<T> {
+    current: Vec<T>,
+    rest: Vec<Vec<T>>, // 다 찬 chunk. heap buffer 는 옮겨지지 않으므로 이전에 돌려준 참조는 유효
+}
+
+const MIN_CHUNK: usize = 8;
+
+impl<T> Chunks<T> {
+    /// 현재 chunk 에 additional 개가 들어갈 자리가 없으면 새 chunk 로 바꾼다
+    fn reserve(&mut self, additional: usize) {
+        if self.current.capacity() - self.current.len() >= additional {
+            return;
+        }
+        let size = cmp::max(self.current.capacity() * 2, additional).max(MIN_CHUNK);
+        let full = mem::replace(&mut self.current, Vec::with_capacity(size));
+        if !full.is_empty() {
+            self.rest.push(full);
+        }
+    }
+}
+
+impl<T> Arena<T> {
+    pub fn new() -> Self {
+        Self::with_capacity(MIN_CHUNK)
+    }
+
+    /// 첫 chunk 의 크기를 지정
+    pub fn with_capacity(capacity: usize) -> Self {
+        Arena {
+            chunks: RefCell::new(Chunks {
+                current: Vec::with_capacity(capacity),
+                rest: Vec::new(),
+            }),
+        }
+    }
+
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc(&self, value: T) -> &mut T {
+        let mut chunks = self.chunks.borrow_mut();
+        chunks.reserve(1);
+        chunks.current.push(value);
+        let last = chunks.current.len() - 1;
+        // alloc_extend 와 같은 이유로 &self 의 lifetime 동안 유효하다
+        unsafe { &mut *chunks.current.as_mut_ptr().add(last) }
+    }
+
+    /// iterator 의 값들을 이어진 slice 로 할당
+    ///
+    /// iterator 를 먼저 끝까지 모은 뒤에 chunk 를 빌리므로, iterator 안에서 같은 arena 에 할당해도 된다.
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_extend(&self, values: impl IntoIterator<Item = T>) -> &mut [T] {
+        let values: Vec<T> = values.into_iter().collect();
+        let len = values.len();
+
+        let mut chunks = self.chunks.borrow_mut();
+        chunks.reserve(len);
+        let start = chunks.current.len();
+        // reserve 했으므로 capacity 를 넘지 않는다 (넘기면 재할당되어 이전 참조가 무효가 됨)
+        chunks.current.extend(values);
+        // [start, start + len) 은 방금 넣은 자리이고 다른 참조가 없다.
+        // current 는 capacity 안에서만 push 하므로 buffer 가 옮겨지지 않고, rest 로 옮겨도 buffer 는 그대로이며,
+        // arena 가 살아있는 동안 값이 제거되지 않으므로 &self 의 lifetime 동안 유효하다.
+        unsafe { std::slice::from_raw_parts_mut(chunks.current.as_mut_ptr().add(start), len) }
+    }
+
+    /// 할당된 값의 수
+    pub fn len(&self) -> usize {
+        let chunks = self.chunks.borrow();
+        chunks.current.len() + chunks.rest.iter().map(Vec::len).sum::<usize>()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// heap 할당을 한 chunk 수
+    pub fn chunk_count(&self) -> usize {
+        let chunks = self.chunks.borrow();
+        chunks.rest.len() + usize::from(chunks.current.capacity() > 0)
+    }
+
+    /// 할당한 순서대로 값을 꺼낸다
+    pub fn into_vec(self) -> Vec<T> {
+        let Chunks { current, rest } = self.chunks.into_inner();
+        let mut values: Vec<T> = rest.into_iter().flatten().collect();
+        values.extend(current);
+        values
+    }
+}
+
+impl<T> Default for Arena<T> {
+    fn default() -> Self {
+        Arena::new()
+    }
+}
+
+/// chunk 가 바뀌어도 이전에 받은 참조가 그대로이고, arena 를 빌린 값끼리 참조할 수 있다
+#[test]
+fn references_are_stable() {
+    struct Node<'a> {
+        value: u32,
+        parent: Option<&'a Node<'a>>,
+    }
+
+    let arena = Arena::new();
+    let root = arena.alloc(Node {
+        value: 0,
+        parent: None,
+    });
+    let root = &*root;
+    let mut nodes = Vec::new();
+    let mut parent = root;
+    for value in 1..1000 {
+        parent = arena.alloc(Node {
+            value,
+            parent: Some(parent),
+        });
+        nodes.push(parent);
+    }
+
+    assert_eq!(arena.len(), 1000);
+    assert!(arena.chunk_count() <= 8);
+    let depth = std::iter::successors(Some(parent), |n| n.parent).count();
+    assert_eq!(depth, 1000);
+    assert!(nodes.iter().zip(1..).all(|(n, v)| n.value == v));
+    assert_eq!(nodes[0].parent.unwrap().value, root.value);
+}
+
+#[test]
+fn alloc_extend() {
+    let arena = Arena::with_capacity(4);
+    let a = arena.alloc_extend([1, 2, 3]);
+    let b = arena.alloc_extend(0..10); // 현재 chunk 에 자리가 없으므로 새 chunk
+    let c = arena.alloc_extend((0..20).filter(|x| x % 2 == 0)); // 길이를 모르는 iterator
+    a[0] = 100;
+    assert_eq!(a, [100, 2, 3]);
+    assert_eq!(b.len(), 10);
+    assert_eq!(c.len(), 10);
+    assert!(arena.alloc_extend(std::iter::empty()).is_empty());
+
+    let values = arena.into_vec();
+    assert_eq!(values.len(), 23);
+    assert_eq!(values[..4], [100, 2, 3, 0]);
+}
+
+/// iterator 가 같은 arena 에 할당해도 chunk 를 빌리는 것과 겹치지 않는다
+#[test]
+fn reentrant_alloc_extend() {
+    let arena = Arena::new();
+    let values = arena.alloc_extend((0..20).map(|i| *arena.alloc(i) * 10));
+    assert_eq!(values.len(), 20);
+    assert_eq!(values[19], 190);
+    assert_eq!(arena.len(), 40);
+}
+
+/// arena 가 drop 될 때 모든 값이 한 번씩 drop 된다
+#[test]
+fn drops_every_value() {
+    let tracker = super::DropTracker::new();
+    {
+        let arena = Arena::new();
+        for i in 0..100 {
+            arena.alloc(tracker.track(i));
+        }
+        arena.alloc_extend((0..50).map(|i| tracker.track(i)));
+        assert_eq!(tracker.alive(), 150);
+    }
+    assert_eq!(tracker.created(), 150);
+    assert_eq!(tracker.dropped(), 150);
+}
@@ -0,0 +1,135 @@
+use super::Arena;
+use std::fmt;
+use std::ops::Deref;
+
+/// guides 의 idioms1 HVec 과 같은 모양의 collection (element 마다 따로 놓인 값을 가리키는 목록)
+///
+/// idioms1 의 HVec 은 `Vec<Box<T>>` 라서 element 마다 heap 할당을 한 번씩 한다.
+/// 여기서는 값을 Arena 에 놓고 참조만 모으므로, 값을 위한 할당은 arena 의 chunk 가 찰 때만 일어나고
+/// 값들이 memory 에 이어져 있다. 대신 값은 arena 보다 오래 살 수 없고, arena 가 drop 될 때 한꺼번에 해제된다.
+pub struct HVec<'a, T> {
+    arena: &'a Arena<T>,
+    data: Vec<&'a T>,
+}
+
+impl<'a, T> HVec<'a, T> {
+    pub fn new_in(arena: &'a Arena<T>) -> Self {
+        HVec {
+            arena,
+            data: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, value: T) {
+        self.data.push(self.arena.alloc(value));
+    }
+}
+
+impl<'a, T: Clone> HVec<'a, T> {
+    /// idioms1 의 `HVec::new` 처럼 slice 의 값을 하나씩 복사
+    pub fn from_slice_in(arena: &'a Arena<T>, values: &[T]) -> Self {
+        let mut hvec = HVec::new_in(arena);
+        hvec.data.reserve(values.len());
+        for value in values {
+            hvec.push(value.clone());
+        }
+        hvec
+    }
+}
+
+impl<'a, T> Deref for HVec<'a, T> {
+    type Target = Vec<&'a T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.data
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for HVec<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.data.iter()).finish()
+    }
+}
+
+#[test]
+fn same_as_boxed() {
+    let values = [1, 2, 3, 4, 5];
+    let boxed: Vec<Box<i32>> = values.iter().cloned().map(Box::new).collect();
+
+    let arena = Arena::with_capacity(2);
+    let hvec = HVec::from_slice_in(&arena, &values);
+    assert!(hvec.iter().map(|v| **v).eq(boxed.iter().map(|v| **v)));
+    assert_eq!(format!("{:?}", hvec), format!("{:?}", boxed));
+    assert_eq!(arena.len(), 5);
+    assert!(arena.chunk_count() < values.len());
+
+    // 값들이 이어진 자리에 놓인다
+    let addrs: Vec<usize> = hvec.iter().map(|v| *v as *const i32 as usize).collect();
+    assert_eq!(addrs[1] - addrs[0], std::mem::size_of::<i32>());
+}
+
+/// element 마다 Box 를 할당하는 것과 arena 에 놓는 것의 비교 (만들기, 순회, 해제)
+///
+/// cargo test --release bench_box_vs_arena -- --ignored --nocapture
+#[test]
+#[ignore]
+fn bench_box_vs_arena() {
+    use std::hint::black_box;
+    use std::time::{Duration, Instant};
+
+    const NUM_ELEMENTS: usize = 1_000_000;
+    const ROUNDS: u32 = 10;
+
+    #[derive(Clone)]
+    struct Item {
+        id: u64,
+        _payload: [u64; 3],
+    }
+
+    let values: Vec<Item> = (0..NUM_ELEMENTS as u64)
+        .map(|id| Item {
+            id,
+            _payload: [id; 3],
+        })
+        .collect();
+
+    let report = |name: &str, build: Duration, sum: Duration, drop: Duration| {
+        println!(
+            "{:<8} build {:>10.2?}  sum {:>10.2?}  drop {:>10.2?}",
+            name,
+            build / ROUNDS,
+            sum / ROUNDS,
+            drop / ROUNDS
+        );
+    };
+
+    let (mut build, mut sum, mut drop) = Default::default();
+    for _ in 0..ROUNDS {
+        let start = Instant::now();
+        let boxed: Vec<Box<Item>> = values.iter().cloned().map(Box::new).collect();
+        build += start.elapsed();
+        let start = Instant::now();
+        black_box(boxed.iter().map(|v| v.id).sum::<u64>());
+        sum += start.elapsed();
+        let start = Instant::now();
+        std::mem::drop(boxed);
+        drop += start.elapsed();
+    }
+    report("Box", build, sum, drop);
+
+    let (mut build, mut sum, mut drop) = Default::default();
+    for _ in 0..ROUNDS {
+        let start = Instant::now();
+        let arena = Arena::new();
+        let hvec = HVec::from_slice_in(&arena, &values);
+        build += start.elapsed();
+        let start = Instant::now();
+        black_box(hvec.iter().map(|v| v.id).sum::<u64>());
+        sum += start.elapsed();
+        let start = Instant::now();
+        std::mem::drop(hvec);
+        std::mem::drop(arena);
+        drop += start.elapsed();
+    }
+    report("Arena", build, sum, drop);
+}
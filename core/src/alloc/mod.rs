@@ -0,0 +1,13 @@
+/// 같은 type 의 값을 chunk 에 이어서 놓고 `&'arena T` 를 돌려주는 bump allocator
+pub mod arena;
+/// Arena 에 element 를 놓는 HVec (guides 의 idioms1 HVec 과 비교용)
+pub mod hvec;
+/// 세대 (generation) 를 붙인 key 로 값을 찾는 slab
+pub mod slab;
+/// 만들어지고 drop 된 값의 수를 세는 DropTracker
+pub mod tracker;
+
+pub use arena::Arena;
+pub use hvec::HVec;
+pub use slab::{Key, Slab};
+pub use tracker::{DropTracker, Tracked};
@@ -0,0 +1,329 @@
+use std::fmt;
+use std::mem;
+use std::ops::{Index, IndexMut};
+
+/// Slab 의 값을 가리키는 key
+///
+/// 자리 (index) 와 그 자리의 세대 (generation) 로 이루어진다. 값을 지우면 그 자리의 세대가 올라가므로,
+/// 지워진 값의 key 로는 나중에 같은 자리에 들어온 다른 값을 찾을 수 없다 (ABA 문제 방지).
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Key {
+    index: u32,
+    generation: u32,
+}
+
+impl Key {
+    pub fn index(&self) -> usize {
+        self.index as usize
+    }
+
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+}
+
+impl fmt::Debug for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}v{}", self.index, self.generation)
+    }
+}
+
+/// 값들을 한 Vec 에 두고 key 로 찾는 저장소
+///
+/// - 지운 자리는 free list 로 이어두었다가 다음 insert 에 다시 쓴다. 자리가 옮겨지지 않으므로 key 는 계속 유효하다.
+/// - `Box` 로 따로 할당해서 pointer 로 연결하던 구조 (linked list, graph, 서로 참조하는 object) 를
+///   pointer 대신 key 로 연결하면 할당 없이, borrow checker 와 싸우지 않고 만들 수 있다.
+pub struct Slab<T> {
+    slots: Vec<Slot<T>>,
+    free: Option<u32>, // 비어있는 자리의 list 의 머리
+    len: usize,
+}
+
+struct Slot<T> {
+    generation: u32,
+    entry: Entry<T>,
+}
+
+enum Entry<T> {
+    Occupied(T),
+    Vacant { next_free: Option<u32> },
+}
+
+impl<T> Slab<T> {
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Slab {
+            slots: Vec::with_capacity(capacity),
+            free: None,
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn insert(&mut self, value: T) -> Key {
+        self.insert_with(|_| value)
+    }
+
+    /// 자신의 key 를 알아야 하는 값 (예: key 를 field 로 갖는 node) 을 넣을 때
+    ///
+    /// key 를 먼저 정하고 `f` 가 끝난 뒤에 free list 를 바꾸므로, `f` 가 panic 해도 slot 이 새지 않는다.
+    pub fn insert_with(&mut self, f: impl FnOnce(Key) -> T) -> Key {
+        let key = match self.free {
+            Some(index) => Key {
+                index,
+                generation: self.slots[index as usize].generation,
+            },
+            None => Key {
+                index: u32::try_from(self.slots.len()).expect("slab is full"),
+                generation: 0,
+            },
+        };
+        let value = f(key);
+        match self.free {
+            Some(index) => {
+                let slot = &mut self.slots[index as usize];
+                let Entry::Vacant { next_free } = slot.entry else {
+                    unreachable!("free list points to an occupied slot");
+                };
+                self.free = next_free;
+                slot.entry = Entry::Occupied(value);
+            }
+            None => self.slots.push(Slot {
+                generation: 0,
+                entry: Entry::Occupied(value),
+            }),
+        }
+        self.len += 1;
+        key
+    }
+
+    pub fn get(&self, key: Key) -> Option<&T> {
+        match self.slots.get(key.index())? {
+            Slot {
+                generation,
+                entry: Entry::Occupied(value),
+            } if *generation == key.generation => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn get_mut(&mut self, key: Key) -> Option<&mut T> {
+        match self.slots.get_mut(key.index())? {
+            Slot {
+                generation,
+                entry: Entry::Occupied(value),
+            } if *generation == key.generation => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn contains(&self, key: Key) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// 값을 꺼내고 자리의 세대를 올린다. 이미 지워진 key 이면 None.
+    pub fn remove(&mut self, key: Key) -> Option<T> {
+        self.get(key)?;
+        let slot = &mut self.slots[key.index()];
+        let vacant = Entry::Vacant {
+            next_free: self.free,
+        };
+        let Entry::Occupied(value) = mem::replace(&mut slot.entry, vacant) else {
+            unreachable!();
+        };
+        // 세대가 한 바퀴 돌면 아주 오래된 key 가 다시 유효해질 수 있지만, 2^32 번 재사용된 뒤의 일이다
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free = Some(key.index);
+        self.len -= 1;
+        Some(value)
+    }
+
+    /// f 가 false 를 반환한 값을 모두 지운다
+    pub fn retain(&mut self, mut f: impl FnMut(Key, &mut T) -> bool) {
+        let keys: Vec<Key> = self
+            .iter_mut()
+            .filter_map(|(key, value)| (!f(key, value)).then_some(key))
+            .collect();
+        for key in keys {
+            self.remove(key);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.retain(|_, _| false);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Key, &T)> {
+        self.slots
+            .iter()
+            .zip(0..)
+            .filter_map(|(slot, index)| match &slot.entry {
+                Entry::Occupied(value) => Some((
+                    Key {
+                        index,
+                        generation: slot.generation,
+                    },
+                    value,
+                )),
+                Entry::Vacant { .. } => None,
+            })
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Key, &mut T)> {
+        self.slots
+            .iter_mut()
+            .zip(0..)
+            .filter_map(|(slot, index)| match &mut slot.entry {
+                Entry::Occupied(value) => Some((
+                    Key {
+                        index,
+                        generation: slot.generation,
+                    },
+                    value,
+                )),
+                Entry::Vacant { .. } => None,
+            })
+    }
+}
+
+impl<T> Default for Slab<T> {
+    fn default() -> Self {
+        Slab::new()
+    }
+}
+
+impl<T> Index<Key> for Slab<T> {
+    type Output = T;
+
+    fn index(&self, key: Key) -> &T {
+        self.get(key).expect("stale or invalid slab key")
+    }
+}
+
+impl<T> IndexMut<Key> for Slab<T> {
+    fn index_mut(&mut self, key: Key) -> &mut T {
+        self.get_mut(key).expect("stale or invalid slab key")
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Slab<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/// 지운 자리를 다시 써도 이전 key 로는 새 값을 찾을 수 없다
+#[test]
+fn stale_keys() {
+    let mut slab = Slab::new();
+    let a = slab.insert("a");
+    let b = slab.insert("b");
+    assert_eq!(slab.remove(a), Some("a"));
+    assert_eq!(slab.remove(a), None);
+
+    let c = slab.insert("c");
+    assert_eq!(c.index(), a.index()); // 같은 자리
+    assert_ne!(c, a);
+    assert_eq!(slab.get(a), None);
+    assert_eq!(slab[c], "c");
+    assert_eq!(slab[b], "b");
+    assert_eq!(slab.len(), 2);
+
+    slab.retain(|_, v| *v != "b");
+    assert!(!slab.contains(b));
+    assert_eq!(slab.iter().map(|(_, v)| *v).collect::<Vec<_>>(), ["c"]);
+}
+
+/// key 로 연결한 doubly linked list (Box / Rc 없이)
+#[test]
+fn linked_list() {
+    struct Node {
+        value: u32,
+        prev: Option<Key>,
+        next: Option<Key>,
+    }
+
+    let mut slab = Slab::new();
+    let mut head: Option<Key> = None;
+    for value in (0..10).rev() {
+        let node = slab.insert(Node {
+            value,
+            prev: None,
+            next: head,
+        });
+        if let Some(h) = head {
+            slab[h].prev = Some(node);
+        }
+        head = Some(node);
+    }
+
+    // 짝수를 list 에서 빼낸다
+    let mut cur = head;
+    while let Some(key) = cur {
+        let Node {
+            value, prev, next, ..
+        } = slab[key];
+        if value % 2 == 0 {
+            match prev {
+                Some(p) => slab[p].next = next,
+                None => head = next,
+            }
+            if let Some(n) = next {
+                slab[n].prev = prev;
+            }
+            slab.remove(key);
+        }
+        cur = next;
+    }
+
+    let values: Vec<u32> = std::iter::successors(head, |&k| slab[k].next)
+        .map(|k| slab[k].value)
+        .collect();
+    assert_eq!(values, [1, 3, 5, 7, 9]);
+    assert_eq!(slab.len(), 5);
+}
+
+#[test]
+fn drops_every_value() {
+    let tracker = super::DropTracker::new();
+    let mut slab = Slab::new();
+    let keys: Vec<Key> = (0..10).map(|i| slab.insert(tracker.track(i))).collect();
+    drop(slab.remove(keys[3]));
+    assert_eq!(tracker.dropped(), 1);
+    slab.insert(tracker.track(10)); // 자리 3 을 다시 쓴다
+    assert_eq!(slab.slots.len(), 10);
+    drop(slab);
+    assert_eq!(tracker.created(), 11);
+    assert_eq!(tracker.dropped(), 11);
+}
+
+/// insert_with 의 f 가 panic 해도 slot 과 free list 가 그대로이다
+#[test]
+fn panic_in_insert_with() {
+    use std::panic::{self, AssertUnwindSafe};
+
+    let mut slab = Slab::new();
+    let a = slab.insert(1);
+    slab.remove(a);
+    for _ in 0..2 {
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            slab.insert_with(|_| -> i32 { panic!("boom") })
+        }));
+        assert!(result.is_err());
+    }
+    assert_eq!(slab.len(), 0);
+    let b = slab.insert(2);
+    assert_eq!(b.index(), a.index()); // 비워둔 자리를 다시 쓴다
+    slab.insert(3);
+    assert_eq!(slab.slots.len(), 2);
+}
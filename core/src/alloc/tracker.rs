@@ -0,0 +1,87 @@
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// 값이 몇 개 만들어지고 몇 개 drop 되었는지 세는 counter
+///
+/// `track` 으로 감싼 값이 drop 될 때 센다. arena / slab 같은 저장소가 값을 빠짐없이, 한 번씩만 drop 하는지
+/// (leak 이나 double drop 이 없는지) 확인할 때 쓴다. clone 은 같은 counter 를 공유한다.
+#[derive(Clone, Default)]
+pub struct DropTracker {
+    counts: Arc<Counts>,
+}
+
+#[derive(Default)]
+struct Counts {
+    created: AtomicUsize,
+    dropped: AtomicUsize,
+}
+
+impl DropTracker {
+    pub fn new() -> Self {
+        DropTracker::default()
+    }
+
+    pub fn track<T>(&self, value: T) -> Tracked<T> {
+        self.counts.created.fetch_add(1, Ordering::Relaxed);
+        Tracked {
+            value,
+            tracker: self.clone(),
+        }
+    }
+
+    pub fn created(&self) -> usize {
+        self.counts.created.load(Ordering::Relaxed)
+    }
+
+    pub fn dropped(&self) -> usize {
+        self.counts.dropped.load(Ordering::Relaxed)
+    }
+
+    /// 아직 drop 되지 않은 값의 수
+    pub fn alive(&self) -> usize {
+        self.created() - self.dropped()
+    }
+}
+
+impl fmt::Debug for DropTracker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DropTracker")
+            .field("created", &self.created())
+            .field("dropped", &self.dropped())
+            .finish()
+    }
+}
+
+/// drop 될 때 DropTracker 에 알리는 값
+pub struct Tracked<T> {
+    value: T,
+    tracker: DropTracker,
+}
+
+impl<T> Deref for Tracked<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T> DerefMut for Tracked<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Tracked<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.value.fmt(f)
+    }
+}
+
+impl<T> Drop for Tracked<T> {
+    fn drop(&mut self) {
+        self.tracker.counts.dropped.fetch_add(1, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,337 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::sync::{Condvar, Mutex};
+
+/// 은행원 알고리즘 (ch04 p152 의 Resource 를 정리)
+///
+/// p152 의 Resource<NRES, NTH> 는 resource / thread 수가 const generic 이라 compile 시점에 고정되고,
+/// 한번에 resource 1개씩만 요청할 수 있었다.
+/// 여기서는
+/// - resource 종류 수는 생성 시점에, thread 는 실행 중에 register / deregister
+/// - request(id, &[..]) 로 여러 resource 를 한번에 요청하고 실패 이유를 RequestError 로 반환
+/// - request_wait 는 요청이 safe 해질 때까지 Condvar 로 대기 (release / deregister 시 깨움)
+///
+/// available  : 은행이 지금 빌려줄 수 있는 resource 수
+/// allocation : 각 thread 가 현재 빌린 resource 수
+/// max        : 각 thread 가 최대로 빌릴 수 있다고 미리 선언한 resource 수
+pub struct Banker {
+    state: Mutex<State>,
+    cond: Condvar,
+}
+
+struct State {
+    total: Vec<usize>,
+    available: Vec<usize>,
+    clients: BTreeMap<usize, Client>,
+    next_id: usize,
+}
+
+struct Client {
+    max: Vec<usize>,
+    allocation: Vec<usize>,
+}
+
+impl Client {
+    fn need(&self) -> impl Iterator<Item = usize> + '_ {
+        self.max.iter().zip(&self.allocation).map(|(m, a)| m - a)
+    }
+}
+
+/// request 가 거절된 이유
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RequestError {
+    /// 등록되지 않은 thread id
+    UnknownThread(usize),
+    /// 선언한 max 를 넘는 요청 (기다려도 절대 허가되지 않음)
+    Denied,
+    /// 지금 남아있는 resource 가 부족함
+    Unavailable,
+    /// 빌려주면 모든 thread 가 끝날 수 있는 순서가 없어짐 (unsafe state)
+    WouldBeUnsafe,
+    /// register 의 max 가 은행의 총 보유량보다 큼
+    ExceedsTotal,
+    /// 빌린 양보다 많이 반환하려 함
+    OverRelease,
+    /// resource vector 의 길이가 resource 종류 수와 다름
+    LengthMismatch { expected: usize, found: usize },
+}
+
+impl fmt::Display for RequestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RequestError::UnknownThread(id) => write!(f, "thread {} is not registered", id),
+            RequestError::Denied => write!(f, "request exceeds the declared maximum claim"),
+            RequestError::Unavailable => write!(f, "not enough resources available"),
+            RequestError::WouldBeUnsafe => write!(f, "granting the request leads to an unsafe state"),
+            RequestError::ExceedsTotal => write!(f, "maximum claim exceeds the total resources"),
+            RequestError::OverRelease => write!(f, "releasing more than is allocated"),
+            RequestError::LengthMismatch { expected, found } => {
+                write!(f, "expected {} resource kinds, found {}", expected, found)
+            }
+        }
+    }
+}
+
+impl Error for RequestError {}
+
+/// 현재 상태와 그 상태에서 찾은 safe sequence (없으면 None = unsafe state)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    pub available: Vec<usize>,
+    pub allocation: BTreeMap<usize, Vec<usize>>,
+    pub max: BTreeMap<usize, Vec<usize>>,
+    pub safe_sequence: Option<Vec<usize>>,
+}
+
+impl State {
+    fn check_len(&self, amount: &[usize]) -> Result<(), RequestError> {
+        if amount.len() == self.total.len() {
+            Ok(())
+        } else {
+            Err(RequestError::LengthMismatch {
+                expected: self.total.len(),
+                found: amount.len(),
+            })
+        }
+    }
+
+    // p152 의 is_safe : 모든 thread 가 끝날 수 있는 순서를 찾으면 그 순서를 반환
+    fn safe_sequence(&self) -> Option<Vec<usize>> {
+        let mut work = self.available.clone();
+        let mut finish: BTreeMap<usize, bool> = self.clients.keys().map(|&id| (id, false)).collect();
+        let mut sequence = Vec::with_capacity(self.clients.len());
+
+        loop {
+            let found = self.clients.iter().find(|(id, c)| {
+                !finish[*id] && work.iter().zip(c.need()).all(|(w, n)| *w >= n)
+            });
+
+            match found {
+                Some((&id, c)) => {
+                    // thread id 가 끝나면 빌렸던 resource 를 모두 돌려준다고 가정
+                    finish.insert(id, true);
+                    for (w, a) in work.iter_mut().zip(&c.allocation) {
+                        *w += *a;
+                    }
+                    sequence.push(id);
+                }
+                None => break,
+            }
+        }
+
+        if sequence.len() == self.clients.len() {
+            Some(sequence)
+        } else {
+            None
+        }
+    }
+
+    fn try_request(&mut self, id: usize, request: &[usize]) -> Result<(), RequestError> {
+        self.check_len(request)?;
+
+        let client = self.clients.get(&id).ok_or(RequestError::UnknownThread(id))?;
+        if client.need().zip(request).any(|(n, r)| *r > n) {
+            return Err(RequestError::Denied);
+        }
+        if self.available.iter().zip(request).any(|(a, r)| r > a) {
+            return Err(RequestError::Unavailable);
+        }
+
+        // 일단 빌려준 뒤 safe 한지 확인하고, 아니면 되돌린다 (p152 의 ake 와 같은 방식)
+        self.apply(id, request, true);
+        if self.safe_sequence().is_some() {
+            Ok(())
+        } else {
+            self.apply(id, request, false);
+            Err(RequestError::WouldBeUnsafe)
+        }
+    }
+
+    fn apply(&mut self, id: usize, amount: &[usize], grant: bool) {
+        let client = self.clients.get_mut(&id).unwrap();
+        for ((a, c), n) in self.available.iter_mut().zip(client.allocation.iter_mut()).zip(amount) {
+            if grant {
+                *a -= n;
+                *c += n;
+            } else {
+                *a += n;
+                *c -= n;
+            }
+        }
+    }
+}
+
+impl Banker {
+    /// resource 종류별 총 보유량
+    pub fn new(total: &[usize]) -> Self {
+        Banker {
+            state: Mutex::new(State {
+                total: total.to_vec(),
+                available: total.to_vec(),
+                clients: BTreeMap::new(),
+                next_id: 0,
+            }),
+            cond: Condvar::new(),
+        }
+    }
+
+    /// thread 를 등록하고 id 를 받는다. max 가 총 보유량보다 크면 ExceedsTotal
+    pub fn register(&self, max: &[usize]) -> Result<usize, RequestError> {
+        let mut state = self.state.lock().unwrap();
+        state.check_len(max)?;
+        if max.iter().zip(&state.total).any(|(m, t)| m > t) {
+            return Err(RequestError::ExceedsTotal);
+        }
+
+        let id = state.next_id;
+        state.next_id += 1;
+        state.clients.insert(
+            id,
+            Client {
+                max: max.to_vec(),
+                allocation: vec![0; max.len()],
+            },
+        );
+        Ok(id)
+    }
+
+    /// 등록 해제. 빌리고 있던 resource 는 모두 반환된다.
+    pub fn deregister(&self, id: usize) -> Result<(), RequestError> {
+        let mut state = self.state.lock().unwrap();
+        let client = state.clients.remove(&id).ok_or(RequestError::UnknownThread(id))?;
+        for (a, c) in state.available.iter_mut().zip(&client.allocation) {
+            *a += c;
+        }
+        self.cond.notify_all();
+        Ok(())
+    }
+
+    /// 대기하지 않고 바로 요청
+    pub fn request(&self, id: usize, request: &[usize]) -> Result<(), RequestError> {
+        self.state.lock().unwrap().try_request(id, request)
+    }
+
+    /// 요청이 safe 하게 허가될 수 있을 때까지 대기
+    /// Unavailable / WouldBeUnsafe 는 다른 thread 가 반환하면 바뀔 수 있으므로 기다리고,
+    /// UnknownThread / Denied / LengthMismatch 는 기다려도 바뀌지 않으므로 바로 반환
+    pub fn request_wait(&self, id: usize, request: &[usize]) -> Result<(), RequestError> {
+        let mut state = self.state.lock().unwrap();
+        loop {
+            match state.try_request(id, request) {
+                Err(RequestError::Unavailable) | Err(RequestError::WouldBeUnsafe) => {
+                    state = self.cond.wait(state).unwrap();
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// 빌린 resource 일부를 반환. 빌린 양보다 많이 반환하려 하면 OverRelease
+    pub fn release(&self, id: usize, amount: &[usize]) -> Result<(), RequestError> {
+        let mut state = self.state.lock().unwrap();
+        state.check_len(amount)?;
+
+        let client = state.clients.get(&id).ok_or(RequestError::UnknownThread(id))?;
+        if client.allocation.iter().zip(amount).any(|(c, n)| n > c) {
+            return Err(RequestError::OverRelease);
+        }
+        state.apply(id, amount, false);
+        self.cond.notify_all();
+        Ok(())
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        let state = self.state.lock().unwrap();
+        Snapshot {
+            available: state.available.clone(),
+            allocation: state.clients.iter().map(|(&id, c)| (id, c.allocation.clone())).collect(),
+            max: state.clients.iter().map(|(&id, c)| (id, c.max.clone())).collect(),
+            safe_sequence: state.safe_sequence(),
+        }
+    }
+}
+
+/// 교과서 (Silberschatz) 예제 : resource 3종 (10, 5, 7), thread 5개
+#[test]
+fn p152() {
+    let banker = Banker::new(&[10, 5, 7]);
+    let max = [[7, 5, 3], [3, 2, 2], [9, 0, 2], [2, 2, 2], [4, 3, 3]];
+    let allocation = [[0, 1, 0], [2, 0, 0], [3, 0, 2], [2, 1, 1], [0, 0, 2]];
+
+    let ids: Vec<usize> = max.iter().map(|m| banker.register(m).unwrap()).collect();
+    for (id, a) in ids.iter().zip(&allocation) {
+        banker.request(*id, a).unwrap();
+    }
+
+    let snapshot = banker.snapshot();
+    assert_eq!(snapshot.available, vec![3, 3, 2]);
+    assert_eq!(snapshot.safe_sequence, Some(vec![1, 3, 0, 2, 4]));
+
+    // thread 1 의 (1, 0, 2) 요청은 safe
+    banker.request(ids[1], &[1, 0, 2]).unwrap();
+    // 그 상태에서 thread 0 의 (0, 2, 0) 요청은 available 은 충분하지만 unsafe
+    assert_eq!(banker.request(ids[0], &[0, 2, 0]), Err(RequestError::WouldBeUnsafe));
+    // thread 4 의 (3, 3, 0) 은 남은 양이 부족
+    assert_eq!(banker.request(ids[4], &[3, 3, 0]), Err(RequestError::Unavailable));
+    // 선언한 max 를 넘는 요청
+    assert_eq!(banker.request(ids[3], &[1, 1, 1]), Err(RequestError::Denied));
+    assert_eq!(banker.request(99, &[0, 0, 0]), Err(RequestError::UnknownThread(99)));
+    // 총 보유량보다 큰 max 와 빌린 양보다 많은 반환
+    assert_eq!(banker.register(&[11, 0, 0]), Err(RequestError::ExceedsTotal));
+    assert_eq!(banker.release(ids[4], &[0, 0, 3]), Err(RequestError::OverRelease));
+
+    // 거절된 요청은 상태를 바꾸지 않는다
+    assert_eq!(banker.snapshot().available, vec![2, 3, 0]);
+}
+
+/// 4.1 의 철학자 문제를 은행원 알고리즘으로 : 포크 2개, 철학자 2명이 각각 포크를 하나씩 요청
+/// 두번째 철학자가 하나를 먼저 가져가면 deadlock 이 되므로 첫번째 철학자가 끝날 때까지 기다린다.
+#[test]
+fn request_wait() {
+    use std::sync::Arc;
+    use std::thread;
+
+    const NUM_LOOP: usize = 1_000;
+
+    let banker = Arc::new(Banker::new(&[1, 1]));
+    let mut v = Vec::new();
+
+    for order in [[0, 1], [1, 0]] {
+        let b = banker.clone();
+        let t = thread::spawn(move || {
+            let id = b.register(&[1, 1]).unwrap();
+            for _ in 0..NUM_LOOP {
+                for fork in order {
+                    let mut req = [0, 0];
+                    req[fork] = 1;
+                    b.request_wait(id, &req).unwrap();
+                }
+                b.release(id, &[1, 1]).unwrap();
+            }
+            b.deregister(id).unwrap();
+        });
+        v.push(t);
+    }
+
+    for t in v {
+        t.join().unwrap();
+    }
+    assert_eq!(banker.snapshot().available, vec![1, 1]);
+}
+
+/// resource 종류 수와 길이가 다른 vector 는 panic 하지 않고 LengthMismatch
+#[test]
+fn length_mismatch() {
+    let banker = Banker::new(&[3, 3]);
+    fn mismatch<T>(found: usize) -> Result<T, RequestError> {
+        Err(RequestError::LengthMismatch { expected: 2, found })
+    }
+    assert_eq!(banker.register(&[1]), mismatch(1));
+    let id = banker.register(&[2, 2]).unwrap();
+    assert_eq!(banker.request(id, &[1, 1, 1]), mismatch(3));
+    assert_eq!(banker.request_wait(id, &[]), mismatch(0));
+    banker.request(id, &[1, 1]).unwrap();
+    assert_eq!(banker.release(id, &[1]), mismatch(1));
+    assert_eq!(banker.snapshot().available, [2, 2]);
+}
@@ -0,0 +1,9 @@
+/// Deadlock 회피 / 검출 도구 (ch04 4.1 ~ 4.3)
+///
+/// 4.3 은행원 알고리즘
+pub mod banker;
+/// 4.1 lock 순서 역전 (AB / BA) 검출 (debug build 전용)
+pub mod tracked;
+
+pub use banker::{Banker, RequestError, Snapshot};
+pub use tracked::{TrackedMutex, TrackedMutexGuard};
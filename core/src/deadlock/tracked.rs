@@ -0,0 +1,303 @@
+/// lock 순서 검사용 Mutex (debug build 전용)
+///
+/// 4.1 의 철학자 문제처럼 thread 마다 lock 을 잡는 순서가 다르면 (A -> B, B -> A)
+/// 언젠가 deadlock 이 발생하지만, 실제로 발생할 때까지는 test 를 아무리 돌려도 보이지 않는다.
+///
+/// TrackedMutex 는 "A 를 잡은 채로 B 를 잡았다" 를 전역 graph 의 A -> B edge 로 기록하고,
+/// 새 edge 가 cycle 을 만들면 (이미 B -> .. -> A 경로가 있으면) 실제 deadlock 이 나지 않았더라도
+/// 두 lock 순서가 처음 기록된 위치와 backtrace 를 출력하며 panic 한다.
+///
+/// release build 에서는 std::sync::Mutex 의 type alias 이므로 비용이 없다.
+#[cfg(debug_assertions)]
+pub use self::debug::{TrackedMutex, TrackedMutexGuard};
+
+#[cfg(not(debug_assertions))]
+pub type TrackedMutex<T> = std::sync::Mutex<T>;
+#[cfg(not(debug_assertions))]
+pub type TrackedMutexGuard<'a, T> = std::sync::MutexGuard<'a, T>;
+
+#[cfg(debug_assertions)]
+mod debug {
+    use crate::dsa::graph::bfs_path;
+    use std::backtrace::Backtrace;
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+    use std::fmt::Write;
+    use std::ops::{Deref, DerefMut};
+    use std::panic::Location;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{LockResult, Mutex, MutexGuard, OnceLock, PoisonError, TryLockError, TryLockResult};
+
+    static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+    // from -> to : from 을 잡은 상태에서 to 를 잡은 적이 있다.
+    struct Edge {
+        from_site: &'static Location<'static>,
+        to_site: &'static Location<'static>,
+        backtrace: Backtrace,
+    }
+
+    type Graph = HashMap<usize, HashMap<usize, Edge>>;
+
+    fn graph() -> &'static Mutex<Graph> {
+        static GRAPH: OnceLock<Mutex<Graph>> = OnceLock::new();
+        GRAPH.get_or_init(|| Mutex::new(HashMap::new()))
+    }
+
+    thread_local! {
+        // 현재 thread 가 잡고 있는 lock (id, lock 을 잡은 위치)
+        static HELD: RefCell<Vec<(usize, &'static Location<'static>)>> = const { RefCell::new(Vec::new()) };
+    }
+
+    // from 에서 to 로 가는 가장 짧은 경로 (edge 목록)
+    fn find_path(graph: &Graph, from: usize, to: usize) -> Option<Vec<(usize, usize)>> {
+        let path = bfs_path(
+            from,
+            |node| graph.get(node).into_iter().flat_map(|e| e.keys().copied()),
+            |&node| node == to,
+        )?;
+        Some(path.windows(2).map(|w| (w[0], w[1])).collect())
+    }
+
+    // id 를 잡기 전에, 지금 잡고 있는 lock 들 -> id 의 edge 를 추가. cycle 이 생기면 panic
+    fn before_lock(id: usize, site: &'static Location<'static>) {
+        let held = HELD.with(|h| h.borrow().clone());
+        if held.is_empty() {
+            return;
+        }
+
+        let mut graph = graph().lock().unwrap_or_else(PoisonError::into_inner);
+        for &(held_id, held_site) in &held {
+            if held_id == id {
+                panic!(
+                    "TrackedMutex locked twice on the same thread (deadlock)\n  first locked at {}\n  locked again at {}",
+                    held_site, site
+                );
+            }
+            if graph.get(&held_id).is_some_and(|e| e.contains_key(&id)) {
+                continue;
+            }
+
+            if let Some(path) = find_path(&graph, id, held_id) {
+                let mut report = String::new();
+                let _ = writeln!(report, "lock order inversion detected (potential deadlock)");
+                let _ = writeln!(report, "  this thread holds the lock acquired at {}", held_site);
+                let _ = writeln!(report, "  and is acquiring the lock at {}", site);
+                let _ = writeln!(report, "{}", Backtrace::force_capture());
+                let _ = writeln!(report, "  but the opposite order was recorded earlier:");
+                for (from, to) in path {
+                    let edge = &graph[&from][&to];
+                    let _ = writeln!(report, "  lock acquired at {}", edge.from_site);
+                    let _ = writeln!(report, "  then lock acquired at {}", edge.to_site);
+                    let _ = writeln!(report, "{}", edge.backtrace);
+                }
+                drop(graph);
+                panic!("{}", report);
+            }
+
+            graph.entry(held_id).or_default().insert(
+                id,
+                Edge {
+                    from_site: held_site,
+                    to_site: site,
+                    backtrace: Backtrace::force_capture(),
+                },
+            );
+        }
+    }
+
+    fn push_held(id: usize, site: &'static Location<'static>) {
+        HELD.with(|h| h.borrow_mut().push((id, site)));
+    }
+
+    fn pop_held(id: usize) {
+        // guard 는 잡은 역순이 아닌 순서로 drop 될 수도 있다
+        HELD.with(|h| {
+            let mut h = h.borrow_mut();
+            if let Some(pos) = h.iter().rposition(|&(i, _)| i == id) {
+                h.remove(pos);
+            }
+        });
+    }
+
+    pub struct TrackedMutex<T> {
+        id: usize,
+        inner: Mutex<T>,
+    }
+
+    impl<T> TrackedMutex<T> {
+        pub fn new(v: T) -> Self {
+            TrackedMutex {
+                id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+                inner: Mutex::new(v),
+            }
+        }
+
+        #[track_caller]
+        pub fn lock(&self) -> LockResult<TrackedMutexGuard<'_, T>> {
+            let site = Location::caller();
+            before_lock(self.id, site);
+
+            let result = self.inner.lock();
+            push_held(self.id, site);
+            match result {
+                Ok(guard) => Ok(TrackedMutexGuard { id: self.id, guard }),
+                Err(e) => Err(PoisonError::new(TrackedMutexGuard {
+                    id: self.id,
+                    guard: e.into_inner(),
+                })),
+            }
+        }
+
+        /// 대기하지 않으므로 deadlock 의 원인이 되지 않아 edge 는 추가하지 않는다.
+        /// (잡은 뒤에 다른 lock 을 잡으면 그 순서는 기록된다)
+        #[track_caller]
+        pub fn try_lock(&self) -> TryLockResult<TrackedMutexGuard<'_, T>> {
+            let site = Location::caller();
+            match self.inner.try_lock() {
+                Ok(guard) => {
+                    push_held(self.id, site);
+                    Ok(TrackedMutexGuard { id: self.id, guard })
+                }
+                Err(TryLockError::Poisoned(e)) => {
+                    push_held(self.id, site);
+                    Err(TryLockError::Poisoned(PoisonError::new(TrackedMutexGuard {
+                        id: self.id,
+                        guard: e.into_inner(),
+                    })))
+                }
+                Err(TryLockError::WouldBlock) => Err(TryLockError::WouldBlock),
+            }
+        }
+
+        pub fn get_mut(&mut self) -> LockResult<&mut T> {
+            self.inner.get_mut()
+        }
+
+        pub fn into_inner(self) -> LockResult<T> {
+            // Drop 을 구현했으므로 field 를 move 할 수 없어 ManuallyDrop + ptr::read 로 꺼낸다
+            let this = std::mem::ManuallyDrop::new(self);
+            forget_node(this.id);
+            let inner = unsafe { std::ptr::read(&this.inner) };
+            inner.into_inner()
+        }
+    }
+
+    impl<T: Default> Default for TrackedMutex<T> {
+        fn default() -> Self {
+            TrackedMutex::new(T::default())
+        }
+    }
+
+    // 사라진 lock 의 edge 를 지워서 graph 가 계속 커지지 않도록
+    fn forget_node(id: usize) {
+        let mut graph = graph().lock().unwrap_or_else(PoisonError::into_inner);
+        graph.remove(&id);
+        for edges in graph.values_mut() {
+            edges.remove(&id);
+        }
+    }
+
+    impl<T> Drop for TrackedMutex<T> {
+        fn drop(&mut self) {
+            forget_node(self.id);
+        }
+    }
+
+    pub struct TrackedMutexGuard<'a, T> {
+        id: usize,
+        guard: MutexGuard<'a, T>,
+    }
+
+    impl<'a, T> Drop for TrackedMutexGuard<'a, T> {
+        fn drop(&mut self) {
+            pop_held(self.id);
+        }
+    }
+
+    impl<'a, T> Deref for TrackedMutexGuard<'a, T> {
+        type Target = T;
+
+        fn deref(&self) -> &T {
+            &self.guard
+        }
+    }
+
+    impl<'a, T> DerefMut for TrackedMutexGuard<'a, T> {
+        fn deref_mut(&mut self) -> &mut T {
+            &mut self.guard
+        }
+    }
+}
+
+/// 4.1 p144 의 철학자 문제 : 실제 deadlock 이 나기 전에 두번째 순서 (B -> A) 에서 검출
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic(expected = "lock order inversion")]
+fn p144() {
+    let fork_a = TrackedMutex::new(());
+    let fork_b = TrackedMutex::new(());
+
+    // 철학자1 : A -> B
+    {
+        let _n1 = fork_a.lock().unwrap();
+        let _n2 = fork_b.lock().unwrap();
+    }
+    // 철학자2 : B -> A (동시에 실행되지 않아서 실제로는 deadlock 이 나지 않지만 검출된다)
+    {
+        let _n1 = fork_b.lock().unwrap();
+        let _n2 = fork_a.lock().unwrap();
+    }
+}
+
+/// A -> B -> C 와 C -> A 처럼 여러 단계의 cycle
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic(expected = "lock order inversion")]
+fn three_locks() {
+    let a = TrackedMutex::new(0);
+    let b = TrackedMutex::new(0);
+    let c = TrackedMutex::new(0);
+
+    {
+        let _a = a.lock().unwrap();
+        let _b = b.lock().unwrap();
+    }
+    {
+        let _b = b.lock().unwrap();
+        let _c = c.lock().unwrap();
+    }
+    {
+        let _c = c.lock().unwrap();
+        let _a = a.lock().unwrap();
+    }
+}
+
+/// 모든 thread 가 같은 순서로 잡으면 문제 없다.
+#[test]
+fn consistent_order() {
+    use std::sync::Arc;
+    use std::thread;
+
+    let a = Arc::new(TrackedMutex::new(0));
+    let b = Arc::new(TrackedMutex::new(0));
+    let mut v = Vec::new();
+
+    for _ in 0..4 {
+        let (a, b) = (a.clone(), b.clone());
+        v.push(thread::spawn(move || {
+            for _ in 0..1_000 {
+                let mut x = a.lock().unwrap();
+                let mut y = b.lock().unwrap();
+                *x += 1;
+                *y += 1;
+            }
+        }));
+    }
+    for t in v {
+        t.join().unwrap();
+    }
+
+    assert_eq!(*a.lock().unwrap(), 4_000);
+    assert_eq!(*b.lock().unwrap(), 4_000);
+}
@@ -0,0 +1,543 @@
+use crate::sync::queue::CachePadded;
+use crate::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::borrow::Borrow;
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+use std::iter::FromIterator;
+use std::ops::{Deref, DerefMut};
+use std::thread;
+
+/// shard 로 나눈 concurrent hash map
+///
+/// key 의 hash 로 shard 를 고르고, shard 마다 따로 있는 RwLock (crate::sync::RwLock, writer 우선) 으로 보호한다.
+/// 다른 shard 의 key 는 서로 기다리지 않고 동시에 읽고 쓸 수 있다.
+/// shard 의 lock 들은 CachePadded 로 떨어뜨려서 false sharing 을 피한다.
+///
+/// get 이 반환하는 Ref / entry 가 반환하는 Entry 는 그 shard 의 lock 을, snapshot 은 모든 shard 의 lock 을 잡고 있다.
+/// 같은 thread 에서 이것들을 들고 있는 동안에는 같은 map 을 읽는 것 (get, contains_key, len, snapshot 등) 도 포함해서
+/// 어떤 접근도 하지 말고 먼저 drop 할 것. (여러 값을 함께 봐야 하면 get_cloned 로 복사하거나 Snapshot 하나로 본다)
+/// - 쓰기는 같은 shard 이면 바로 deadlock 이다.
+/// - 읽기도 writer 우선 lock 이라서, 그 사이에 다른 thread 의 writer 가 기다리기 시작하면 뒤의 읽기가 writer 를,
+///   writer 는 앞의 Ref 를 기다리므로 deadlock 이다. (테스트에서는 드물게만 나타난다)
+pub struct ConcurrentHashMap<K, V, S = RandomState> {
+    shards: Box<[Shard<K, V, S>]>,
+    shift: u32, // shard 번호 = hash 의 위쪽 bit
+    hasher: S,
+}
+
+type Shard<K, V, S> = CachePadded<RwLock<HashMap<K, V, S>>>;
+
+impl<K: Hash + Eq, V> ConcurrentHashMap<K, V> {
+    /// shard 수 = (CPU 수 * 4) 이상의 2의 거듭제곱
+    pub fn new() -> Self {
+        let cpus = thread::available_parallelism().map_or(1, |n| n.get());
+        Self::with_shard_count(cpus * 4)
+    }
+
+    /// shard 수는 2의 거듭제곱으로 올림
+    pub fn with_shard_count(shards: usize) -> Self {
+        Self::with_shard_count_and_hasher(shards, RandomState::new())
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher + Clone> ConcurrentHashMap<K, V, S> {
+    pub fn with_shard_count_and_hasher(shards: usize, hasher: S) -> Self {
+        assert!(shards > 0, "shard count must be positive");
+        let shards = shards.next_power_of_two();
+        ConcurrentHashMap {
+            shards: (0..shards)
+                .map(|_| CachePadded::new(RwLock::new(HashMap::with_hasher(hasher.clone()))))
+                .collect(),
+            shift: 64 - shards.trailing_zeros(),
+            hasher,
+        }
+    }
+
+    pub fn shard_count(&self) -> usize {
+        self.shards.len()
+    }
+
+    fn shard<Q: Hash + ?Sized>(&self, key: &Q) -> &RwLock<HashMap<K, V, S>> {
+        if self.shards.len() == 1 {
+            return &self.shards[0];
+        }
+        // 위쪽 7 bit 는 shard 안의 HashMap 이 bucket 의 tag 로 쓰므로 건너뛴다
+        let hash = self.hasher.hash_one(key);
+        &self.shards[((hash << 7) >> self.shift) as usize]
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<Ref<'_, K, V, S>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let guard = self.shard(key).read();
+        let (k, v) = guard.get_key_value(key)?;
+        let (key, value) = (k as *const K, v as *const V);
+        Some(Ref {
+            _guard: guard,
+            key,
+            value,
+        })
+    }
+
+    /// lock 을 들고 있지 않도록 값을 복사해서 반환
+    pub fn get_cloned<Q>(&self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+        V: Clone,
+    {
+        self.shard(key).read().get(key).cloned()
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.shard(key).read().contains_key(key)
+    }
+
+    /// 같은 key 가 있었으면 이전 값을 반환
+    pub fn insert(&self, key: K, value: V) -> Option<V> {
+        self.shard(&key).write().insert(key, value)
+    }
+
+    pub fn remove<Q>(&self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.shard(key).write().remove(key)
+    }
+
+    /// key 가 있는 shard 의 write lock 을 잡은 채로 확인하고 수정 (read-modify-write 사이에 다른 thread 가 끼어들지 않음)
+    pub fn entry(&self, key: K) -> Entry<'_, K, V, S> {
+        let guard = self.shard(&key).write();
+        if guard.contains_key(&key) {
+            Entry::Occupied(OccupiedEntry { guard, key })
+        } else {
+            Entry::Vacant(VacantEntry { guard, key })
+        }
+    }
+
+    /// shard 마다 lock 을 잡고 세므로, 다른 thread 가 쓰는 중이면 근사값
+    pub fn len(&self) -> usize {
+        self.shards.iter().map(|s| s.read().len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.shards.iter().all(|s| s.read().is_empty())
+    }
+
+    pub fn clear(&self) {
+        for s in self.shards.iter() {
+            s.write().clear();
+        }
+    }
+
+    /// 모든 shard 의 read lock 을 (번호 순서로) 잡아서 한 시점의 내용을 본다.
+    ///
+    /// 먼저 잡은 shard 는 마지막 shard 를 잡을 때까지 바뀌지 않으므로, 마지막 lock 을 잡은 시점의 map 과 같다.
+    /// Snapshot 이 살아있는 동안 모든 쓰기가 기다린다.
+    pub fn snapshot(&self) -> Snapshot<'_, K, V, S> {
+        Snapshot {
+            guards: self.shards.iter().map(|s| s.read()).collect(),
+        }
+    }
+}
+
+impl<K: Hash + Eq, V> Default for ConcurrentHashMap<K, V> {
+    fn default() -> Self {
+        ConcurrentHashMap::new()
+    }
+}
+
+impl<K, V, S> fmt::Debug for ConcurrentHashMap<K, V, S>
+where
+    K: Hash + Eq + fmt::Debug,
+    V: fmt::Debug,
+    S: BuildHasher + Clone,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.snapshot().iter()).finish()
+    }
+}
+
+impl<K: Hash + Eq, V> FromIterator<(K, V)> for ConcurrentHashMap<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = ConcurrentHashMap::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher + Clone> Extend<(K, V)> for ConcurrentHashMap<K, V, S> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (k, v) in iter {
+            self.insert(k, v);
+        }
+    }
+}
+
+/// get 의 결과. shard 의 read lock 을 잡고 있으므로 들고 있는 동안 같은 map 에 접근하지 않는다.
+pub struct Ref<'a, K, V, S> {
+    _guard: RwLockReadGuard<'a, HashMap<K, V, S>>,
+    // _guard 가 read lock 을 잡고 있는 동안 HashMap 이 바뀌지 않으므로 유효
+    key: *const K,
+    value: *const V,
+}
+
+impl<'a, K, V, S> Ref<'a, K, V, S> {
+    pub fn key(&self) -> &K {
+        unsafe { &*self.key }
+    }
+
+    pub fn value(&self) -> &V {
+        unsafe { &*self.value }
+    }
+}
+
+impl<'a, K, V, S> Deref for Ref<'a, K, V, S> {
+    type Target = V;
+
+    fn deref(&self) -> &V {
+        self.value()
+    }
+}
+
+/// entry 로 얻은 값. shard 의 write lock 을 잡고 있다.
+pub struct RefMut<'a, K, V, S> {
+    _guard: RwLockWriteGuard<'a, HashMap<K, V, S>>,
+    // _guard 를 통해서는 이 값만 수정할 수 있으므로 (HashMap 구조는 바뀌지 않음) 유효
+    value: *mut V,
+}
+
+impl<'a, K, V, S> Deref for RefMut<'a, K, V, S> {
+    type Target = V;
+
+    fn deref(&self) -> &V {
+        unsafe { &*self.value }
+    }
+}
+
+impl<'a, K, V, S> DerefMut for RefMut<'a, K, V, S> {
+    fn deref_mut(&mut self) -> &mut V {
+        unsafe { &mut *self.value }
+    }
+}
+
+/// std::collections::hash_map::Entry 와 같은 사용법
+pub enum Entry<'a, K, V, S> {
+    Occupied(OccupiedEntry<'a, K, V, S>),
+    Vacant(VacantEntry<'a, K, V, S>),
+}
+
+pub struct OccupiedEntry<'a, K, V, S> {
+    guard: RwLockWriteGuard<'a, HashMap<K, V, S>>,
+    key: K,
+}
+
+pub struct VacantEntry<'a, K, V, S> {
+    guard: RwLockWriteGuard<'a, HashMap<K, V, S>>,
+    key: K,
+}
+
+impl<'a, K: Hash + Eq, V, S: BuildHasher> Entry<'a, K, V, S> {
+    pub fn key(&self) -> &K {
+        match self {
+            Entry::Occupied(e) => &e.key,
+            Entry::Vacant(e) => &e.key,
+        }
+    }
+
+    pub fn or_insert(self, default: V) -> RefMut<'a, K, V, S> {
+        self.or_insert_with(|| default)
+    }
+
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> RefMut<'a, K, V, S> {
+        match self {
+            Entry::Occupied(e) => e.into_ref(),
+            Entry::Vacant(e) => e.insert(default()),
+        }
+    }
+
+    pub fn or_default(self) -> RefMut<'a, K, V, S>
+    where
+        V: Default,
+    {
+        self.or_insert_with(V::default)
+    }
+
+    pub fn and_modify<F: FnOnce(&mut V)>(mut self, f: F) -> Self {
+        if let Entry::Occupied(e) = &mut self {
+            f(e.get_mut());
+        }
+        self
+    }
+}
+
+impl<'a, K: Hash + Eq, V, S: BuildHasher> OccupiedEntry<'a, K, V, S> {
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    pub fn get(&self) -> &V {
+        &self.guard[&self.key]
+    }
+
+    pub fn get_mut(&mut self) -> &mut V {
+        self.guard.get_mut(&self.key).unwrap()
+    }
+
+    /// 값을 바꾸고 이전 값을 반환
+    pub fn insert(&mut self, value: V) -> V {
+        std::mem::replace(self.get_mut(), value)
+    }
+
+    pub fn remove(mut self) -> V {
+        self.guard.remove(&self.key).unwrap()
+    }
+
+    pub fn into_ref(mut self) -> RefMut<'a, K, V, S> {
+        let value = self.get_mut() as *mut V;
+        RefMut {
+            _guard: self.guard,
+            value,
+        }
+    }
+}
+
+impl<'a, K: Hash + Eq, V, S: BuildHasher> VacantEntry<'a, K, V, S> {
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    pub fn insert(mut self, value: V) -> RefMut<'a, K, V, S> {
+        let value = self.guard.entry(self.key).or_insert(value) as *mut V;
+        RefMut {
+            _guard: self.guard,
+            value,
+        }
+    }
+}
+
+/// snapshot 한 시점의 모든 shard 의 read lock
+pub struct Snapshot<'a, K, V, S> {
+    guards: Vec<RwLockReadGuard<'a, HashMap<K, V, S>>>,
+}
+
+impl<'a, K, V, S> Snapshot<'a, K, V, S> {
+    pub fn len(&self) -> usize {
+        self.guards.iter().map(|g| g.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.guards.iter().all(|g| g.is_empty())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> + '_ {
+        self.guards.iter().flat_map(|g| g.iter())
+    }
+}
+
+#[test]
+fn entry() {
+    let map: ConcurrentHashMap<&str, usize> = ConcurrentHashMap::with_shard_count(3);
+    assert_eq!(map.shard_count(), 4);
+
+    assert_eq!(map.insert("a", 1), None);
+    assert_eq!(map.insert("a", 2), Some(1));
+    assert_eq!(*map.get("a").unwrap(), 2);
+    assert_eq!(map.get("a").unwrap().key(), &"a");
+    assert!(map.get("b").is_none());
+
+    *map.entry("a").and_modify(|v| *v += 10).or_insert(0) += 1;
+    *map.entry("b").and_modify(|v| *v += 10).or_insert(0) += 1;
+    assert_eq!(map.get_cloned("a"), Some(13));
+    assert_eq!(map.get_cloned("b"), Some(1));
+
+    match map.entry("a") {
+        Entry::Occupied(mut e) => {
+            assert_eq!(e.insert(100), 13);
+            assert_eq!(e.remove(), 100);
+        }
+        Entry::Vacant(_) => unreachable!(),
+    }
+    assert!(!map.contains_key("a"));
+    assert_eq!(*map.entry("c").or_default(), 0);
+    assert_eq!(map.len(), 2);
+
+    map.clear();
+    assert!(map.is_empty());
+}
+
+/// ch03 의 thread test 와 같은 구성 : 여러 thread 가 같은 key 집합에 entry 로 더하고, 합이 맞는지 확인
+#[test]
+fn stress() {
+    use std::sync::Arc;
+
+    const NUM_THREADS: usize = 8;
+    const NUM_LOOP: usize = 10_000;
+    const NUM_KEYS: usize = 64;
+
+    let map = Arc::new(ConcurrentHashMap::with_shard_count(8));
+    let mut v = Vec::new();
+
+    for i in 0..NUM_THREADS {
+        let map0 = map.clone();
+        let t = thread::spawn(move || {
+            for j in 0..NUM_LOOP {
+                let key = (i * 7 + j) % NUM_KEYS;
+                *map0.entry(key).or_insert(0) += 1;
+                if j % 100 == 0 {
+                    map0.insert(NUM_KEYS + i, j);
+                    assert_eq!(map0.remove(&(NUM_KEYS + i)), Some(j));
+                }
+            }
+        });
+        v.push(t);
+    }
+
+    for t in v {
+        t.join().unwrap();
+    }
+
+    let snapshot = map.snapshot();
+    assert_eq!(snapshot.len(), NUM_KEYS);
+    assert_eq!(
+        snapshot.iter().map(|(_, v)| v).sum::<usize>(),
+        NUM_THREADS * NUM_LOOP
+    );
+}
+
+/// writer 는 token 을 다음 key 로 옮긴다 (다음 key 를 넣은 뒤 이전 key 를 지움)
+/// 어느 시점에도 token 은 1개 또는 연속한 2개이고, snapshot 도 그래야 한다.
+/// (shard 를 하나씩 잠그고 읽으면 token 이 없거나 떨어진 2개로 보일 수 있다)
+#[test]
+fn snapshot_is_consistent() {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    const NUM_MOVES: usize = 2_000;
+
+    let map = Arc::new(ConcurrentHashMap::with_shard_count(16));
+    map.insert(0, ());
+    let done = Arc::new(AtomicBool::new(false));
+
+    let writer = {
+        let (map, done) = (map.clone(), done.clone());
+        thread::spawn(move || {
+            for k in 0..NUM_MOVES {
+                map.insert(k + 1, ());
+                map.remove(&k);
+                thread::yield_now();
+            }
+            done.store(true, Ordering::Release);
+        })
+    };
+
+    loop {
+        // writer 가 끝난 뒤에도 한 번 더 확인
+        let finished = done.load(Ordering::Acquire);
+        let mut keys: Vec<usize> = map.snapshot().iter().map(|(k, _)| *k).collect();
+        keys.sort();
+        match keys[..] {
+            [_] => {}
+            [a, b] => assert_eq!(a + 1, b),
+            _ => panic!("inconsistent snapshot: {:?}", keys),
+        }
+        if finished {
+            break;
+        }
+        thread::yield_now();
+    }
+    writer.join().unwrap();
+
+    assert_eq!(
+        map.snapshot().iter().map(|(k, _)| *k).collect::<Vec<_>>(),
+        [NUM_MOVES]
+    );
+}
+
+/// 읽기 90% / 쓰기 10% 를 여러 thread 에서 실행한 처리량 비교
+/// - ConcurrentHashMap (shard 수 별)
+/// - Mutex<HashMap> / std RwLock<HashMap> (lock 하나)
+///
+/// cargo test --release bench_concurrent_map -- --ignored --nocapture
+#[test]
+#[ignore]
+fn bench_concurrent_map() {
+    use std::sync::{Arc, Mutex};
+    use std::time::Instant;
+
+    const NUM_THREADS: usize = 4;
+    const NUM_OPS: usize = 500_000;
+    const NUM_KEYS: usize = 10_000;
+
+    fn run<M: Send + Sync + 'static>(
+        name: &str,
+        map: M,
+        read: fn(&M, usize) -> bool,
+        write: fn(&M, usize),
+    ) {
+        let map = Arc::new(map);
+        let start = Instant::now();
+        let handles: Vec<_> = (0..NUM_THREADS)
+            .map(|t| {
+                let map = map.clone();
+                thread::spawn(move || {
+                    let mut hits = 0;
+                    for i in 0..NUM_OPS {
+                        let key = (i * 31 + t * 7919) % NUM_KEYS;
+                        if i % 10 == 0 {
+                            write(&map, key);
+                        } else if read(&map, key) {
+                            hits += 1;
+                        }
+                    }
+                    hits
+                })
+            })
+            .collect();
+        let hits: usize = handles.into_iter().map(|h| h.join().unwrap()).sum();
+        let elapsed = start.elapsed();
+        println!(
+            "{:<30} {:>10.2?} ({:.1} Mops/s, hits {})",
+            name,
+            elapsed,
+            (NUM_THREADS * NUM_OPS) as f64 / elapsed.as_secs_f64() / 1e6,
+            hits
+        );
+    }
+
+    for shards in [1, 4, 16, 64] {
+        run(
+            &format!("ConcurrentHashMap ({} shards)", shards),
+            ConcurrentHashMap::<usize, usize>::with_shard_count(shards),
+            |m, k| m.contains_key(&k),
+            |m, k| {
+                m.insert(k, k);
+            },
+        );
+    }
+    run(
+        "Mutex<HashMap>",
+        Mutex::new(HashMap::<usize, usize>::new()),
+        |m, k| m.lock().unwrap().contains_key(&k),
+        |m, k| {
+            m.lock().unwrap().insert(k, k);
+        },
+    );
+    run(
+        "std RwLock<HashMap>",
+        std::sync::RwLock::new(HashMap::<usize, usize>::new()),
+        |m, k| m.read().unwrap().contains_key(&k),
+        |m, k| {
+            m.write().unwrap().insert(k, k);
+        },
+    );
+}
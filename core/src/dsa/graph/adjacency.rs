@@ -0,0 +1,146 @@
+use super::Adjacency;
+use std::ops::{Index, IndexMut};
+
+/// adjacency list 로 만든 방향 graph
+///
+/// node 는 추가한 순서대로 0, 1, 2 .. 번호를 받고, node 마다 값 (N) 을 가진다.
+/// 무방향 graph 는 add_undirected_edge 로 양쪽 방향 edge 를 넣는다.
+#[derive(Debug, Clone)]
+pub struct Graph<N, E> {
+    nodes: Vec<N>,
+    edges: Vec<Vec<(usize, E)>>,
+    edge_count: usize,
+}
+
+impl<N, E> Graph<N, E> {
+    pub fn new() -> Self {
+        Graph {
+            nodes: Vec::new(),
+            edges: Vec::new(),
+            edge_count: 0,
+        }
+    }
+
+    /// 새 node 의 번호를 반환
+    pub fn add_node(&mut self, value: N) -> usize {
+        self.nodes.push(value);
+        self.edges.push(Vec::new());
+        self.nodes.len() - 1
+    }
+
+    pub fn add_edge(&mut self, from: usize, to: usize, weight: E) {
+        assert!(to < self.nodes.len(), "node {} does not exist", to);
+        self.edges[from].push((to, weight));
+        self.edge_count += 1;
+    }
+
+    pub fn add_undirected_edge(&mut self, a: usize, b: usize, weight: E)
+    where
+        E: Clone,
+    {
+        self.add_edge(a, b, weight.clone());
+        self.add_edge(b, a, weight);
+    }
+
+    /// from -> to edge 를 모두 지우고 지운 개수를 반환
+    pub fn remove_edges(&mut self, from: usize, to: usize) -> usize {
+        let before = self.edges[from].len();
+        self.edges[from].retain(|&(t, _)| t != to);
+        let removed = before - self.edges[from].len();
+        self.edge_count -= removed;
+        removed
+    }
+
+    pub fn contains_edge(&self, from: usize, to: usize) -> bool {
+        self.edges[from].iter().any(|&(t, _)| t == to)
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.edge_count
+    }
+
+    pub fn nodes(&self) -> &[N] {
+        &self.nodes
+    }
+
+    /// 모든 edge 를 (from, to, weight) 로
+    pub fn all_edges(&self) -> impl Iterator<Item = (usize, usize, &E)> {
+        self.edges
+            .iter()
+            .enumerate()
+            .flat_map(|(from, out)| out.iter().map(move |(to, w)| (from, *to, w)))
+    }
+}
+
+impl<E> Graph<(), E> {
+    /// node 값이 없는 graph 를 node 수와 edge 목록으로 만든다.
+    pub fn from_edges<I>(node_count: usize, edges: I) -> Self
+    where
+        I: IntoIterator<Item = (usize, usize, E)>,
+    {
+        let mut g = Graph::new();
+        for _ in 0..node_count {
+            g.add_node(());
+        }
+        for (from, to, w) in edges {
+            g.add_edge(from, to, w);
+        }
+        g
+    }
+}
+
+impl<N, E> Default for Graph<N, E> {
+    fn default() -> Self {
+        Graph::new()
+    }
+}
+
+impl<N, E> Adjacency for Graph<N, E> {
+    type Weight = E;
+
+    fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn edges(&self, node: usize) -> impl Iterator<Item = (usize, &E)> {
+        self.edges[node].iter().map(|(to, w)| (*to, w))
+    }
+}
+
+impl<N, E> Index<usize> for Graph<N, E> {
+    type Output = N;
+
+    fn index(&self, node: usize) -> &N {
+        &self.nodes[node]
+    }
+}
+
+impl<N, E> IndexMut<usize> for Graph<N, E> {
+    fn index_mut(&mut self, node: usize) -> &mut N {
+        &mut self.nodes[node]
+    }
+}
+
+#[test]
+fn build() {
+    let mut g: Graph<&str, u32> = Graph::new();
+    let a = g.add_node("a");
+    let b = g.add_node("b");
+    let c = g.add_node("c");
+    g.add_edge(a, b, 1);
+    g.add_undirected_edge(b, c, 2);
+    g.add_edge(a, b, 3);
+
+    assert_eq!(g.node_count(), 3);
+    assert_eq!(g.edge_count(), 4);
+    assert_eq!(g[c], "c");
+    assert!(g.contains_edge(c, b));
+    assert!(!g.contains_edge(c, a));
+    assert_eq!(g.neighbors(a).collect::<Vec<_>>(), [b, b]);
+
+    assert_eq!(g.remove_edges(a, b), 2);
+    assert_eq!(g.edge_count(), 2);
+    g[a] = "A";
+    assert_eq!(g.nodes(), ["A", "b", "c"]);
+    assert_eq!(g.all_edges().collect::<Vec<_>>(), [(b, c, &2), (c, b, &2)]);
+}
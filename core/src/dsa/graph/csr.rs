@@ -0,0 +1,100 @@
+use super::{Adjacency, Graph};
+
+/// compressed sparse row (CSR) 형식의 읽기 전용 방향 graph
+///
+/// node i 의 edge 는 targets[offsets[i]..offsets[i + 1]] 에 연속으로 들어있다.
+/// node 마다 Vec 을 따로 갖는 Graph 보다 메모리가 작고, 순회할 때 cache 에 잘 맞는다.
+#[derive(Debug, Clone)]
+pub struct Csr<E> {
+    offsets: Vec<usize>,
+    targets: Vec<usize>,
+    weights: Vec<E>,
+}
+
+impl<E> Csr<E> {
+    /// edge 순서와 상관없이 from 별로 모은다. (같은 from 안에서는 입력 순서 유지)
+    pub fn from_edges<I>(node_count: usize, edges: I) -> Self
+    where
+        I: IntoIterator<Item = (usize, usize, E)>,
+    {
+        let edges: Vec<(usize, usize, E)> = edges.into_iter().collect();
+
+        // from 별 개수 -> 누적합이 각 node 의 시작 위치
+        let mut offsets = vec![0; node_count + 1];
+        for &(from, to, _) in &edges {
+            assert!(
+                from < node_count && to < node_count,
+                "edge {} -> {} out of range",
+                from,
+                to
+            );
+            offsets[from + 1] += 1;
+        }
+        for i in 0..node_count {
+            offsets[i + 1] += offsets[i];
+        }
+
+        let mut next = offsets.clone();
+        let mut slots: Vec<Option<(usize, E)>> = (0..edges.len()).map(|_| None).collect();
+        for (from, to, w) in edges {
+            slots[next[from]] = Some((to, w));
+            next[from] += 1;
+        }
+        let (targets, weights) = slots.into_iter().map(Option::unwrap).unzip();
+
+        Csr {
+            offsets,
+            targets,
+            weights,
+        }
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.targets.len()
+    }
+
+    /// node 에서 나가는 edge 의 도착 node 들
+    pub fn targets(&self, node: usize) -> &[usize] {
+        &self.targets[self.offsets[node]..self.offsets[node + 1]]
+    }
+}
+
+impl<E> Adjacency for Csr<E> {
+    type Weight = E;
+
+    fn node_count(&self) -> usize {
+        self.offsets.len() - 1
+    }
+
+    fn edges(&self, node: usize) -> impl Iterator<Item = (usize, &E)> {
+        let range = self.offsets[node]..self.offsets[node + 1];
+        self.targets[range.clone()]
+            .iter()
+            .copied()
+            .zip(&self.weights[range])
+    }
+}
+
+impl<N, E: Clone> From<&Graph<N, E>> for Csr<E> {
+    fn from(g: &Graph<N, E>) -> Self {
+        Csr::from_edges(
+            g.node_count(),
+            g.all_edges().map(|(from, to, w)| (from, to, w.clone())),
+        )
+    }
+}
+
+#[test]
+fn from_graph() {
+    let g = Graph::from_edges(4, [(2, 0, 'a'), (0, 1, 'b'), (2, 3, 'c'), (0, 3, 'd')]);
+    let csr = Csr::from(&g);
+
+    assert_eq!(csr.node_count(), 4);
+    assert_eq!(csr.edge_count(), 4);
+    assert_eq!(csr.targets(0), [1, 3]);
+    assert_eq!(csr.targets(1), []);
+    assert_eq!(csr.targets(2), [0, 3]);
+    for n in 0..4 {
+        assert!(csr.edges(n).eq(g.edges(n)));
+    }
+}
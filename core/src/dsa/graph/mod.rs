@@ -0,0 +1,58 @@
+use std::ops::Add;
+
+/// node 마다 나가는 edge 목록을 가진 graph (node / edge 추가 가능)
+pub mod adjacency;
+/// compressed sparse row : edge 를 한 배열에 node 순서로 모아둔 읽기 전용 graph
+pub mod csr;
+/// union-find, Kruskal 최소 신장 tree (MST)
+pub mod mst;
+/// topological sort, cycle 찾기, Tarjan 강한 연결 요소 (SCC)
+pub mod order;
+/// Dijkstra, A*
+pub mod shortest;
+/// BFS / DFS iterator, 임의의 (implicit) graph 에서의 최단 경로 탐색
+pub mod traverse;
+
+pub use adjacency::Graph;
+pub use csr::Csr;
+pub use mst::{kruskal, UnionFind};
+pub use order::{find_cycle, tarjan_scc, toposort, Cycle};
+pub use shortest::{astar, dijkstra, ShortestPaths};
+pub use traverse::{bfs_path, Bfs, Dfs};
+
+/// 알고리즘들이 사용하는 graph 의 공통 interface
+///
+/// node 는 0..node_count() 의 번호, edge 는 (도착 node, weight).
+/// Graph (adjacency list) 와 Csr 가 구현한다.
+pub trait Adjacency {
+    type Weight;
+
+    fn node_count(&self) -> usize;
+
+    /// node 에서 나가는 edge
+    fn edges(&self, node: usize) -> impl Iterator<Item = (usize, &Self::Weight)>;
+
+    fn neighbors(&self, node: usize) -> impl Iterator<Item = usize> {
+        self.edges(node).map(|(to, _)| to)
+    }
+}
+
+/// 최단 경로의 edge weight. 서로 비교 가능해야 한다. (f64 는 Ord 가 아니므로 정수 단위로 바꿔서 사용)
+///
+/// Dijkstra / A* 는 음수 edge 가 있으면 틀린 답을 조용히 반환하므로 unsigned 정수에만 구현한다.
+/// 음수 weight 가 필요하면 Bellman-Ford 같은 다른 알고리즘을 써야 한다.
+pub trait Weight: Copy + Ord + Add<Output = Self> {
+    fn zero() -> Self;
+}
+
+macro_rules! weight {
+    ($($t:ty),*) => {
+        $(impl Weight for $t {
+            fn zero() -> Self {
+                0
+            }
+        })*
+    };
+}
+
+weight!(u8, u16, u32, u64, usize);
@@ -0,0 +1,132 @@
+use super::Adjacency;
+
+/// 서로소 집합 (disjoint set union)
+///
+/// union by size + path halving 으로 find / union 이 거의 O(1) (inverse Ackermann)
+#[derive(Debug, Clone)]
+pub struct UnionFind {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+    sets: usize,
+}
+
+impl UnionFind {
+    /// 0..n 이 각각 따로인 집합
+    pub fn new(n: usize) -> Self {
+        UnionFind {
+            parent: (0..n).collect(),
+            size: vec![1; n],
+            sets: n,
+        }
+    }
+
+    /// x 가 속한 집합의 대표
+    pub fn find(&mut self, mut x: usize) -> usize {
+        while self.parent[x] != x {
+            self.parent[x] = self.parent[self.parent[x]];
+            x = self.parent[x];
+        }
+        x
+    }
+
+    /// 두 집합을 합친다. 이미 같은 집합이면 false
+    pub fn union(&mut self, a: usize, b: usize) -> bool {
+        let (mut a, mut b) = (self.find(a), self.find(b));
+        if a == b {
+            return false;
+        }
+        if self.size[a] < self.size[b] {
+            std::mem::swap(&mut a, &mut b);
+        }
+        self.parent[b] = a;
+        self.size[a] += self.size[b];
+        self.sets -= 1;
+        true
+    }
+
+    pub fn connected(&mut self, a: usize, b: usize) -> bool {
+        self.find(a) == self.find(b)
+    }
+
+    /// 집합의 개수
+    pub fn sets(&self) -> usize {
+        self.sets
+    }
+}
+
+/// Kruskal 알고리즘으로 최소 신장 forest 의 edge (from, to, weight) 를 weight 순서로
+///
+/// edge 의 방향은 무시한다. (무방향 graph 로 취급)
+/// weight 가 작은 edge 부터, 이미 연결된 두 node 를 잇는 (cycle 을 만드는) edge 는 건너뛰면서 고른다.
+/// 연결 요소가 k 개이면 node_count - k 개의 edge 가 나온다.
+pub fn kruskal<G>(graph: &G) -> Vec<(usize, usize, G::Weight)>
+where
+    G: Adjacency,
+    G::Weight: Ord + Copy,
+{
+    let n = graph.node_count();
+    let mut edges: Vec<(G::Weight, usize, usize)> = (0..n)
+        .flat_map(|from| graph.edges(from).map(move |(to, &w)| (w, from, to)))
+        .collect();
+    edges.sort();
+
+    let mut sets = UnionFind::new(n);
+    let mut tree = Vec::with_capacity(n.saturating_sub(1));
+    for (w, from, to) in edges {
+        if sets.union(from, to) {
+            tree.push((from, to, w));
+        }
+    }
+    tree
+}
+
+#[test]
+fn union_find() {
+    let mut uf = UnionFind::new(6);
+    assert!(uf.union(0, 1));
+    assert!(uf.union(2, 3));
+    assert!(uf.union(1, 3));
+    assert!(!uf.union(0, 2));
+    assert!(uf.connected(0, 3));
+    assert!(!uf.connected(0, 4));
+    assert_eq!(uf.sets(), 3);
+}
+
+#[test]
+fn minimum_spanning_tree() {
+    use super::Graph;
+
+    //   0 --1-- 1 --4-- 2
+    //   |     / |       |
+    //   3   2   5       6
+    //   | /     |       |
+    //   3 --7-- 4       5   6 (혼자)
+    let mut g = Graph::new();
+    for _ in 0..7 {
+        g.add_node(());
+    }
+    for (a, b, w) in [
+        (0, 1, 1),
+        (1, 2, 4),
+        (0, 3, 3),
+        (1, 3, 2),
+        (1, 4, 5),
+        (3, 4, 7),
+        (2, 5, 6),
+    ] {
+        g.add_undirected_edge(a, b, w);
+    }
+
+    let tree = kruskal(&g);
+    assert_eq!(tree.len(), 5);
+    assert_eq!(
+        tree.iter().map(|&(_, _, w)| w).sum::<i32>(),
+        1 + 2 + 4 + 5 + 6
+    );
+
+    let mut uf = UnionFind::new(7);
+    for &(a, b, _) in &tree {
+        uf.union(a, b);
+    }
+    assert_eq!(uf.sets(), 2);
+}
@@ -0,0 +1,237 @@
+use super::Adjacency;
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt;
+
+/// toposort 가 실패한 이유 : graph 안의 cycle 하나 (첫 node 로 돌아오는 순서)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cycle {
+    nodes: Vec<usize>,
+}
+
+impl Cycle {
+    pub fn nodes(&self) -> &[usize] {
+        &self.nodes
+    }
+}
+
+impl fmt::Display for Cycle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "graph has a cycle: ")?;
+        for n in &self.nodes {
+            write!(f, "{} -> ", n)?;
+        }
+        write!(f, "{}", self.nodes[0])
+    }
+}
+
+impl Error for Cycle {}
+
+/// 모든 edge (a -> b) 에서 a 가 b 보다 앞에 오는 순서 (Kahn 알고리즘)
+///
+/// 들어오는 edge 가 없는 node 부터 꺼내고, 꺼낸 node 의 edge 를 지워가며 반복한다.
+/// 끝까지 꺼내지 못한 node 가 있으면 cycle 이 있는 것이므로 그 cycle 을 찾아서 반환
+pub fn toposort<G: Adjacency>(graph: &G) -> Result<Vec<usize>, Cycle> {
+    let n = graph.node_count();
+    let mut indegree = vec![0usize; n];
+    for node in 0..n {
+        for next in graph.neighbors(node) {
+            indegree[next] += 1;
+        }
+    }
+
+    let mut queue: VecDeque<usize> = (0..n).filter(|&i| indegree[i] == 0).collect();
+    let mut order = Vec::with_capacity(n);
+    while let Some(node) = queue.pop_front() {
+        order.push(node);
+        for next in graph.neighbors(node) {
+            indegree[next] -= 1;
+            if indegree[next] == 0 {
+                queue.push_back(next);
+            }
+        }
+    }
+
+    if order.len() == n {
+        Ok(order)
+    } else {
+        Err(find_cycle(graph).expect("unsorted nodes must contain a cycle"))
+    }
+}
+
+/// DFS 로 cycle 을 하나 찾는다. (self loop 도 cycle)
+///
+/// 방문 중 (stack 위에 있는) node 로 가는 edge 를 만나면, stack 에서 그 node 부터 현재 node 까지가 cycle
+pub fn find_cycle<G: Adjacency>(graph: &G) -> Option<Cycle> {
+    #[derive(Clone, Copy, PartialEq)]
+    enum Color {
+        White, // 아직 방문 안 함
+        Gray,  // stack 위에 있음
+        Black, // 끝남
+    }
+
+    let n = graph.node_count();
+    let mut color = vec![Color::White; n];
+    for root in 0..n {
+        if color[root] != Color::White {
+            continue;
+        }
+        color[root] = Color::Gray;
+        let mut stack = vec![(root, graph.neighbors(root))];
+        while let Some((node, edges)) = stack.last_mut() {
+            let node = *node;
+            match edges.next() {
+                Some(next) => match color[next] {
+                    Color::White => {
+                        color[next] = Color::Gray;
+                        stack.push((next, graph.neighbors(next)));
+                    }
+                    Color::Gray => {
+                        let start = stack.iter().position(|(n, _)| *n == next).unwrap();
+                        let nodes = stack[start..].iter().map(|(n, _)| *n).collect();
+                        return Some(Cycle { nodes });
+                    }
+                    Color::Black => {}
+                },
+                None => {
+                    color[node] = Color::Black;
+                    stack.pop();
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Tarjan 알고리즘으로 강한 연결 요소 (서로 오갈 수 있는 node 집합) 를 찾는다.
+///
+/// - index : DFS 로 처음 방문한 순서, low : 그 node 의 subtree 에서 stack 위의 node 로 갈 수 있는 가장 작은 index
+/// - low == index 인 node 는 SCC 의 root 이므로 stack 에서 자기까지 꺼낸 것이 하나의 SCC
+///
+/// SCC 는 역 topological 순서로 나온다. (뒤에 오는 SCC 에서 앞의 SCC 로 가는 edge 는 없음)
+/// 재귀 대신 명시적인 call stack 을 사용
+pub fn tarjan_scc<G: Adjacency>(graph: &G) -> Vec<Vec<usize>> {
+    let n = graph.node_count();
+    let mut index: Vec<Option<usize>> = vec![None; n];
+    let mut low = vec![0; n];
+    let mut on_stack = vec![false; n];
+    let mut stack = Vec::new();
+    let mut next_index = 0;
+    let mut sccs = Vec::new();
+
+    for root in 0..n {
+        if index[root].is_some() {
+            continue;
+        }
+        index[root] = Some(next_index);
+        low[root] = next_index;
+        next_index += 1;
+        stack.push(root);
+        on_stack[root] = true;
+        let mut call = vec![(root, graph.neighbors(root))];
+
+        while let Some((node, edges)) = call.last_mut() {
+            let node = *node;
+            match edges.next() {
+                Some(next) => match index[next] {
+                    None => {
+                        index[next] = Some(next_index);
+                        low[next] = next_index;
+                        next_index += 1;
+                        stack.push(next);
+                        on_stack[next] = true;
+                        call.push((next, graph.neighbors(next)));
+                    }
+                    Some(i) if on_stack[next] => low[node] = low[node].min(i),
+                    Some(_) => {}
+                },
+                None => {
+                    call.pop();
+                    if let Some((parent, _)) = call.last() {
+                        low[*parent] = low[*parent].min(low[node]);
+                    }
+                    if Some(low[node]) == index[node] {
+                        let mut scc = Vec::new();
+                        loop {
+                            let m = stack.pop().unwrap();
+                            on_stack[m] = false;
+                            scc.push(m);
+                            if m == node {
+                                break;
+                            }
+                        }
+                        sccs.push(scc);
+                    }
+                }
+            }
+        }
+    }
+    sccs
+}
+
+#[test]
+fn toposort_and_cycle() {
+    use super::Graph;
+
+    // 옷 입는 순서 : 0 속옷, 1 바지, 2 벨트, 3 셔츠, 4 넥타이, 5 재킷, 6 양말, 7 신발
+    let mut g = Graph::from_edges(
+        8,
+        [
+            (0, 1),
+            (0, 7),
+            (1, 2),
+            (1, 7),
+            (3, 2),
+            (3, 4),
+            (2, 5),
+            (4, 5),
+            (6, 7),
+        ]
+        .map(|(a, b)| (a, b, ())),
+    );
+    let order = toposort(&g).unwrap();
+    let pos = |n: usize| order.iter().position(|&m| m == n).unwrap();
+    assert_eq!(order.len(), 8);
+    assert!(g.all_edges().all(|(a, b, _)| pos(a) < pos(b)));
+    assert!(find_cycle(&g).is_none());
+
+    // 재킷 -> 바지 를 넣으면 1 -> 2 -> 5 -> 1
+    g.add_edge(5, 1, ());
+    let cycle = toposort(&g).unwrap_err();
+    assert_eq!(cycle.nodes(), [1, 2, 5]);
+    assert_eq!(cycle.to_string(), "graph has a cycle: 1 -> 2 -> 5 -> 1");
+
+    let self_loop = Graph::from_edges(2, [(0, 1, ()), (1, 1, ())]);
+    assert_eq!(find_cycle(&self_loop).unwrap().nodes(), [1]);
+}
+
+#[test]
+fn scc() {
+    use super::Csr;
+
+    // {0, 1, 2} -> {3, 4} -> {5},  {6} 혼자
+    let g = Csr::from_edges(
+        7,
+        [
+            (0, 1),
+            (1, 2),
+            (2, 0),
+            (2, 3),
+            (3, 4),
+            (4, 3),
+            (4, 5),
+            (6, 6),
+        ]
+        .map(|(a, b)| (a, b, ())),
+    );
+    let mut sccs = tarjan_scc(&g);
+    for scc in &mut sccs {
+        scc.sort();
+    }
+    assert_eq!(sccs, [vec![5], vec![3, 4], vec![0, 1, 2], vec![6]]);
+
+    // 긴 경로에서도 stack overflow 없이
+    const N: usize = 200_000;
+    let chain = Csr::from_edges(N, (0..N).map(|i| (i, (i + 1) % N, ())));
+    assert_eq!(tarjan_scc(&chain).len(), 1);
+}
@@ -0,0 +1,181 @@
+use super::{Adjacency, Weight};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+/// dijkstra 의 결과 : start 에서 각 node 까지의 거리와 최단 경로 tree
+#[derive(Debug, Clone)]
+pub struct ShortestPaths<W> {
+    start: usize,
+    dist: Vec<Option<W>>,
+    prev: Vec<Option<usize>>,
+}
+
+impl<W: Weight> ShortestPaths<W> {
+    /// 도달할 수 없으면 None
+    pub fn distance(&self, target: usize) -> Option<W> {
+        self.dist[target]
+    }
+
+    /// start -> target 경로 (양 끝 포함)
+    pub fn path_to(&self, target: usize) -> Option<Vec<usize>> {
+        self.dist[target]?;
+        Some(walk_back(&self.prev, self.start, target))
+    }
+}
+
+fn walk_back(prev: &[Option<usize>], start: usize, target: usize) -> Vec<usize> {
+    let mut path = vec![target];
+    let mut node = target;
+    while node != start {
+        node = prev[node].expect("broken predecessor chain");
+        path.push(node);
+    }
+    path.reverse();
+    path
+}
+
+/// start 에서 모든 node 까지의 최단 거리 (weight 는 음수가 아니어야 함)
+///
+/// 아직 확정되지 않은 node 중 가장 가까운 것을 heap 에서 꺼내 확정하고, 그 edge 들로 거리를 줄인다.
+/// 같은 node 가 heap 에 여러 번 들어갈 수 있으므로 꺼낸 거리가 이미 확정된 거리보다 크면 건너뛴다.
+pub fn dijkstra<G>(graph: &G, start: usize) -> ShortestPaths<G::Weight>
+where
+    G: Adjacency,
+    G::Weight: Weight,
+{
+    let n = graph.node_count();
+    let mut dist = vec![None; n];
+    let mut prev = vec![None; n];
+    let mut heap = BinaryHeap::new();
+
+    dist[start] = Some(G::Weight::zero());
+    heap.push(Reverse((G::Weight::zero(), start)));
+
+    while let Some(Reverse((d, node))) = heap.pop() {
+        if dist[node].is_some_and(|best| d > best) {
+            continue;
+        }
+        for (next, &w) in graph.edges(node) {
+            let nd = d + w;
+            if dist[next].is_none_or(|best| nd < best) {
+                dist[next] = Some(nd);
+                prev[next] = Some(node);
+                heap.push(Reverse((nd, next)));
+            }
+        }
+    }
+
+    ShortestPaths { start, dist, prev }
+}
+
+/// heuristic 으로 goal 쪽 node 를 먼저 확장하는 최단 경로 탐색. (거리, 경로) 반환
+///
+/// heuristic(n) 은 n 에서 goal 까지의 실제 거리보다 크지 않아야 (admissible) 최단 경로가 보장된다.
+/// heuristic 이 항상 0 이면 dijkstra 와 같다.
+pub fn astar<G>(
+    graph: &G,
+    start: usize,
+    goal: usize,
+    heuristic: impl Fn(usize) -> G::Weight,
+) -> Option<(G::Weight, Vec<usize>)>
+where
+    G: Adjacency,
+    G::Weight: Weight,
+{
+    let n = graph.node_count();
+    let mut dist = vec![None; n];
+    let mut prev = vec![None; n];
+    let mut heap = BinaryHeap::new();
+
+    dist[start] = Some(G::Weight::zero());
+    heap.push(Reverse((heuristic(start), G::Weight::zero(), start)));
+
+    while let Some(Reverse((_, d, node))) = heap.pop() {
+        if node == goal {
+            return Some((d, walk_back(&prev, start, goal)));
+        }
+        if dist[node].is_some_and(|best| d > best) {
+            continue;
+        }
+        for (next, &w) in graph.edges(node) {
+            let nd = d + w;
+            if dist[next].is_none_or(|best| nd < best) {
+                dist[next] = Some(nd);
+                prev[next] = Some(node);
+                heap.push(Reverse((nd + heuristic(next), nd, next)));
+            }
+        }
+    }
+    None
+}
+
+#[test]
+fn dijkstra_paths() {
+    use super::Graph;
+
+    //      1       2
+    //   0 ---> 1 ---> 3
+    //   |  4         ^ 1
+    //   +----> 2 ----+      4 (도달 불가)
+    let g = Graph::from_edges(
+        5,
+        [(0, 1, 1u32), (0, 2, 4), (1, 3, 2), (2, 3, 1), (1, 2, 2)],
+    );
+    let sp = dijkstra(&g, 0);
+
+    assert_eq!(sp.distance(0), Some(0));
+    assert_eq!(sp.distance(2), Some(3));
+    assert_eq!(sp.distance(3), Some(3));
+    assert_eq!(sp.path_to(3), Some(vec![0, 1, 3]));
+    assert_eq!(sp.path_to(2), Some(vec![0, 1, 2]));
+    assert_eq!(sp.distance(4), None);
+    assert_eq!(sp.path_to(4), None);
+}
+
+/// 10x10 격자에서 벽을 돌아가는 경로. manhattan 거리 heuristic 을 쓰면 dijkstra 와 같은 거리
+#[test]
+fn astar_grid() {
+    use super::Csr;
+
+    const N: usize = 10;
+    let wall = |x: usize, y: usize| x == 5 && y < 8;
+    let id = |x: usize, y: usize| y * N + x;
+
+    let mut edges = Vec::new();
+    for y in 0..N {
+        for x in 0..N {
+            if wall(x, y) {
+                continue;
+            }
+            let mut link = |nx: usize, ny: usize| {
+                if !wall(nx, ny) {
+                    edges.push((id(x, y), id(nx, ny), 1u32));
+                }
+            };
+            if x + 1 < N {
+                link(x + 1, y);
+            }
+            if x > 0 {
+                link(x - 1, y);
+            }
+            if y + 1 < N {
+                link(x, y + 1);
+            }
+            if y > 0 {
+                link(x, y - 1);
+            }
+        }
+    }
+    let g = Csr::from_edges(N * N, edges);
+
+    let (start, goal) = (id(0, 0), id(9, 0));
+    let manhattan = |n: usize| ((n % N).abs_diff(9) + (n / N).abs_diff(0)) as u32;
+    let (d, path) = astar(&g, start, goal, manhattan).unwrap();
+
+    assert_eq!(Some(d), dijkstra(&g, start).distance(goal));
+    assert_eq!(d, 9 + 8 * 2);
+    assert_eq!(path.len(), d as usize + 1);
+    assert_eq!((path[0], path[path.len() - 1]), (start, goal));
+    assert!(path.iter().all(|&n| !wall(n % N, n / N)));
+    assert_eq!(astar(&g, start, id(5, 0), manhattan), None);
+}
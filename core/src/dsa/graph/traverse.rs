@@ -0,0 +1,152 @@
+use super::Adjacency;
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+
+/// start 에서 도달할 수 있는 node 를 가까운 순서로 (breadth first)
+pub struct Bfs<'a, G> {
+    graph: &'a G,
+    queue: VecDeque<usize>,
+    discovered: Vec<bool>,
+}
+
+impl<'a, G: Adjacency> Bfs<'a, G> {
+    pub fn new(graph: &'a G, start: usize) -> Self {
+        let mut discovered = vec![false; graph.node_count()];
+        discovered[start] = true;
+        Bfs {
+            graph,
+            queue: VecDeque::from([start]),
+            discovered,
+        }
+    }
+}
+
+impl<'a, G: Adjacency> Iterator for Bfs<'a, G> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        let node = self.queue.pop_front()?;
+        for next in self.graph.neighbors(node) {
+            if !self.discovered[next] {
+                self.discovered[next] = true;
+                self.queue.push_back(next);
+            }
+        }
+        Some(node)
+    }
+}
+
+/// start 에서 도달할 수 있는 node 를 depth first 로 (preorder)
+///
+/// 재귀 대신 stack 을 쓰므로 긴 경로에서도 stack overflow 가 나지 않는다.
+pub struct Dfs<'a, G> {
+    graph: &'a G,
+    stack: Vec<usize>,
+    visited: Vec<bool>,
+}
+
+impl<'a, G: Adjacency> Dfs<'a, G> {
+    pub fn new(graph: &'a G, start: usize) -> Self {
+        Dfs {
+            graph,
+            stack: vec![start],
+            visited: vec![false; graph.node_count()],
+        }
+    }
+}
+
+impl<'a, G: Adjacency> Iterator for Dfs<'a, G> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        while let Some(node) = self.stack.pop() {
+            if self.visited[node] {
+                continue;
+            }
+            self.visited[node] = true;
+            // 먼저 나온 neighbor 를 먼저 방문하도록 역순으로 push
+            let start = self.stack.len();
+            self.stack
+                .extend(self.graph.neighbors(node).filter(|&n| !self.visited[n]));
+            self.stack[start..].reverse();
+            return Some(node);
+        }
+        None
+    }
+}
+
+/// node 를 미리 만들지 않고 successors 로만 주어지는 graph 에서 start -> goal 의 최단 (edge 수) 경로
+///
+/// 반환하는 경로는 start 와 goal 을 포함한다.
+/// crate::deadlock::TrackedMutex 가 lock 순서 graph 에서 cycle 을 찾을 때 사용
+pub fn bfs_path<N, I>(
+    start: N,
+    mut successors: impl FnMut(&N) -> I,
+    mut goal: impl FnMut(&N) -> bool,
+) -> Option<Vec<N>>
+where
+    N: Hash + Eq + Clone,
+    I: IntoIterator<Item = N>,
+{
+    // node -> 처음 발견한 이전 node
+    let mut parent: HashMap<N, Option<N>> = HashMap::from([(start.clone(), None)]);
+    let mut queue = VecDeque::from([start]);
+
+    while let Some(node) = queue.pop_front() {
+        if goal(&node) {
+            let mut path = vec![node];
+            while let Some(Some(prev)) = parent.get(path.last().unwrap()) {
+                path.push(prev.clone());
+            }
+            path.reverse();
+            return Some(path);
+        }
+        for next in successors(&node) {
+            if !parent.contains_key(&next) {
+                parent.insert(next.clone(), Some(node.clone()));
+                queue.push_back(next);
+            }
+        }
+    }
+    None
+}
+
+//   0 -> 1 -> 3
+//   |         ^
+//   +--> 2 ---+    4 (도달 불가)
+#[cfg(test)]
+fn sample() -> super::Graph<(), ()> {
+    super::Graph::from_edges(5, [(0, 1, ()), (0, 2, ()), (1, 3, ()), (2, 3, ())])
+}
+
+#[test]
+fn bfs_dfs() {
+    let g = sample();
+    assert_eq!(Bfs::new(&g, 0).collect::<Vec<_>>(), [0, 1, 2, 3]);
+    assert_eq!(Dfs::new(&g, 0).collect::<Vec<_>>(), [0, 1, 3, 2]);
+    assert_eq!(Bfs::new(&g, 2).collect::<Vec<_>>(), [2, 3]);
+    assert_eq!(Dfs::new(&g, 4).collect::<Vec<_>>(), [4]);
+}
+
+#[test]
+fn implicit_path() {
+    // 1 에서 시작해서 (x2, +3) 으로 20 을 만드는 가장 짧은 방법
+    let path = bfs_path(
+        1u32,
+        |&n| [n * 2, n + 3].into_iter().filter(|&m| m <= 20),
+        |&n| n == 20,
+    );
+    assert_eq!(path, Some(vec![1, 2, 5, 10, 20]));
+    assert_eq!(
+        bfs_path(
+            1u32,
+            |&n| [n * 2].into_iter().filter(|&m| m <= 20),
+            |&n| n == 20
+        ),
+        None
+    );
+
+    let g = sample();
+    let path = bfs_path(0, |&n| g.neighbors(n).collect::<Vec<_>>(), |&n| n == 3);
+    assert_eq!(path, Some(vec![0, 1, 3]));
+}
@@ -0,0 +1,11 @@
+/// 자료구조 모음
+///
+/// 수정해도 이전 version 이 그대로 남는 persistent (immutable) collection
+/// clone 은 O(1) 이고, 수정할 때는 바뀌는 경로의 node 만 복사하고 나머지는 공유한다. (structural sharing)
+pub mod persistent;
+/// shard 마다 RwLock 을 두는 concurrent hash map (consistent snapshot 지원)
+pub mod concurrent;
+/// graph 와 graph 알고리즘 (BFS / DFS, 최단 경로, topological sort, SCC, MST)
+pub mod graph;
+/// 정렬된 map (B-tree, red-black tree, skip list, interval tree) 과 공통 trait OrderedMap
+pub mod ordered;
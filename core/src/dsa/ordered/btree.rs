@@ -0,0 +1,351 @@
+use super::{after_end, before_start, OrderedMap};
+use std::mem;
+use std::ops::RangeBounds;
+
+/// 최소 차수 (minimum degree). root 가 아닌 node 는 T - 1 ..= 2T - 1 개의 key 를 가진다.
+const T: usize = 6;
+const MAX_KEYS: usize = 2 * T - 1;
+
+/// B-tree 로 만든 정렬 map (CLRS 18 장)
+///
+/// - node 하나에 key 를 여러 개 (최대 2T - 1) 정렬해서 두고, key 사이사이에 child 를 둔다.
+/// - 모든 leaf 의 깊이가 같고, node 가 크므로 binary tree 보다 높이가 낮고 cache 에 잘 맞는다.
+/// - 내려가기 전에 미리 맞춰둔다 : insert 는 가득 찬 child 를 먼저 나누고 (split),
+///   remove 는 key 가 T - 1 개뿐인 child 를 형제에게 빌리거나 합쳐서 (merge) T 개 이상으로 만든 뒤 내려간다.
+///   그래서 한 번 내려가는 동안 다시 올라오며 고칠 일이 없다.
+pub struct BTree<K, V> {
+    root: Option<Node<K, V>>,
+    len: usize,
+}
+
+struct Node<K, V> {
+    keys: Vec<K>,
+    vals: Vec<V>,
+    children: Vec<Node<K, V>>, // leaf 이면 비어있고, 아니면 keys.len() + 1 개
+}
+
+impl<K, V> Node<K, V> {
+    fn new() -> Self {
+        Node {
+            keys: Vec::with_capacity(MAX_KEYS),
+            vals: Vec::with_capacity(MAX_KEYS),
+            children: Vec::new(),
+        }
+    }
+
+    fn is_leaf(&self) -> bool {
+        self.children.is_empty()
+    }
+
+    /// 가득 찬 children[i] 를 가운데 key 를 기준으로 둘로 나누고, 가운데 key 를 이 node 로 올린다.
+    fn split_child(&mut self, i: usize) {
+        let child = &mut self.children[i];
+        let mut right = Node::new();
+        right.keys = child.keys.split_off(T);
+        right.vals = child.vals.split_off(T);
+        if !child.is_leaf() {
+            right.children = child.children.split_off(T);
+        }
+        let key = child.keys.pop().unwrap();
+        let val = child.vals.pop().unwrap();
+        self.keys.insert(i, key);
+        self.vals.insert(i, val);
+        self.children.insert(i + 1, right);
+    }
+
+    /// children[i], keys[i], children[i + 1] 을 children[i] 하나로 합친다.
+    fn merge(&mut self, i: usize) {
+        let right = self.children.remove(i + 1);
+        let key = self.keys.remove(i);
+        let val = self.vals.remove(i);
+        let left = &mut self.children[i];
+        left.keys.push(key);
+        left.vals.push(val);
+        let Node {
+            keys,
+            vals,
+            children,
+        } = right;
+        left.keys.extend(keys);
+        left.vals.extend(vals);
+        left.children.extend(children);
+    }
+
+    /// children[i] 가 key 를 T 개 이상 갖도록 만들고, 내려갈 child 의 index 를 반환
+    fn fill_child(&mut self, i: usize) -> usize {
+        if self.children[i].keys.len() >= T {
+            return i;
+        }
+        if i > 0 && self.children[i - 1].keys.len() >= T {
+            // 왼쪽 형제의 마지막 key 를 부모로, 부모의 key 를 child 의 맨 앞으로
+            let (left, right) = self.children.split_at_mut(i);
+            let (left, child) = (&mut left[i - 1], &mut right[0]);
+            let key = mem::replace(&mut self.keys[i - 1], left.keys.pop().unwrap());
+            let val = mem::replace(&mut self.vals[i - 1], left.vals.pop().unwrap());
+            child.keys.insert(0, key);
+            child.vals.insert(0, val);
+            if let Some(c) = left.children.pop() {
+                child.children.insert(0, c);
+            }
+            i
+        } else if i + 1 < self.children.len() && self.children[i + 1].keys.len() >= T {
+            // 오른쪽 형제의 첫 key 를 부모로, 부모의 key 를 child 의 맨 뒤로
+            let (left, right) = self.children.split_at_mut(i + 1);
+            let (child, right) = (&mut left[i], &mut right[0]);
+            let key = mem::replace(&mut self.keys[i], right.keys.remove(0));
+            let val = mem::replace(&mut self.vals[i], right.vals.remove(0));
+            child.keys.push(key);
+            child.vals.push(val);
+            if !right.is_leaf() {
+                child.children.push(right.children.remove(0));
+            }
+            i
+        } else if i + 1 < self.children.len() {
+            self.merge(i);
+            i
+        } else {
+            self.merge(i - 1);
+            i - 1
+        }
+    }
+
+    fn pop_max(&mut self) -> (K, V) {
+        if self.is_leaf() {
+            return (self.keys.pop().unwrap(), self.vals.pop().unwrap());
+        }
+        let i = self.fill_child(self.children.len() - 1);
+        self.children[i].pop_max()
+    }
+
+    fn pop_min(&mut self) -> (K, V) {
+        if self.is_leaf() {
+            return (self.keys.remove(0), self.vals.remove(0));
+        }
+        let i = self.fill_child(0);
+        self.children[i].pop_min()
+    }
+}
+
+impl<K: Ord, V> Node<K, V> {
+    /// 가득 차지 않은 node 에 insert
+    fn insert_non_full(&mut self, key: K, value: V) -> Option<V> {
+        let mut node = self;
+        loop {
+            let mut i = match node.keys.binary_search(&key) {
+                Ok(i) => return Some(mem::replace(&mut node.vals[i], value)),
+                Err(i) => i,
+            };
+            if node.is_leaf() {
+                node.keys.insert(i, key);
+                node.vals.insert(i, value);
+                return None;
+            }
+            if node.children[i].keys.len() == MAX_KEYS {
+                node.split_child(i);
+                match key.cmp(&node.keys[i]) {
+                    std::cmp::Ordering::Equal => {
+                        return Some(mem::replace(&mut node.vals[i], value))
+                    }
+                    std::cmp::Ordering::Greater => i += 1,
+                    std::cmp::Ordering::Less => {}
+                }
+            }
+            node = &mut node.children[i];
+        }
+    }
+
+    fn remove(&mut self, key: &K) -> Option<(K, V)> {
+        match self.keys.binary_search(key) {
+            Ok(i) if self.is_leaf() => Some((self.keys.remove(i), self.vals.remove(i))),
+            Ok(i) => {
+                // 내부 node 의 key 는 앞 (왼쪽 subtree 의 최댓값) 이나 뒤 (오른쪽 subtree 의 최솟값) 로 바꾸고 지운다
+                if self.children[i].keys.len() >= T {
+                    let (k, v) = self.children[i].pop_max();
+                    Some(self.replace(i, k, v))
+                } else if self.children[i + 1].keys.len() >= T {
+                    let (k, v) = self.children[i + 1].pop_min();
+                    Some(self.replace(i, k, v))
+                } else {
+                    self.merge(i);
+                    self.children[i].remove(key)
+                }
+            }
+            Err(_) if self.is_leaf() => None,
+            Err(i) => {
+                let i = self.fill_child(i);
+                self.children[i].remove(key)
+            }
+        }
+    }
+
+    fn replace(&mut self, i: usize, key: K, value: V) -> (K, V) {
+        (
+            mem::replace(&mut self.keys[i], key),
+            mem::replace(&mut self.vals[i], value),
+        )
+    }
+}
+
+impl<K: Ord, V> BTree<K, V> {
+    pub fn new() -> Self {
+        BTree { root: None, len: 0 }
+    }
+}
+
+impl<K: Ord, V> Default for BTree<K, V> {
+    fn default() -> Self {
+        BTree::new()
+    }
+}
+
+impl<K: Ord, V> OrderedMap<K, V> for BTree<K, V> {
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn get(&self, key: &K) -> Option<&V> {
+        let mut node = self.root.as_ref()?;
+        loop {
+            match node.keys.binary_search(key) {
+                Ok(i) => return Some(&node.vals[i]),
+                Err(_) if node.is_leaf() => return None,
+                Err(i) => node = &node.children[i],
+            }
+        }
+    }
+
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let root = self.root.get_or_insert_with(Node::new);
+        if root.keys.len() == MAX_KEYS {
+            // root 가 가득 차면 새 root 아래로 내리고 나눈다 (높이가 1 늘어나는 유일한 경우)
+            let old = mem::replace(root, Node::new());
+            root.children.push(old);
+            root.split_child(0);
+        }
+        let old = root.insert_non_full(key, value);
+        if old.is_none() {
+            self.len += 1;
+        }
+        old
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        let root = self.root.as_mut()?;
+        let removed = root.remove(key);
+        if root.keys.is_empty() {
+            // merge 로 root 의 key 가 없어지면 높이가 1 줄어든다
+            self.root = root.children.pop();
+        }
+        let (_, v) = removed?;
+        self.len -= 1;
+        Some(v)
+    }
+
+    fn range<'a, R: RangeBounds<K>>(&'a self, range: R) -> impl Iterator<Item = (&'a K, &'a V)>
+    where
+        K: 'a,
+        V: 'a,
+    {
+        // (node, i) : node 의 keys[i] 가 다음 차례 (children[i] 는 이미 stack 위에 있음)
+        let mut stack: Vec<(&Node<K, V>, usize)> = Vec::new();
+        let mut node = self.root.as_ref();
+        while let Some(n) = node {
+            let i = n.keys.partition_point(|k| before_start(&range, k));
+            stack.push((n, i));
+            node = n.children.get(i);
+        }
+
+        std::iter::from_fn(move || loop {
+            let (n, i) = stack.last_mut()?;
+            let (n, i) = (*n, mem::replace(i, *i + 1));
+            if i >= n.keys.len() {
+                stack.pop();
+                continue;
+            }
+            if after_end(&range, &n.keys[i]) {
+                stack.clear();
+                return None;
+            }
+            let mut child = n.children.get(i + 1);
+            while let Some(c) = child {
+                stack.push((c, 0));
+                child = c.children.first();
+            }
+            return Some((&n.keys[i], &n.vals[i]));
+        })
+    }
+
+    fn last(&self) -> Option<(&K, &V)> {
+        let mut node = self.root.as_ref()?;
+        while let Some(c) = node.children.last() {
+            node = c;
+        }
+        Some((node.keys.last()?, node.vals.last()?))
+    }
+}
+
+/// 모든 leaf 의 깊이가 같고, node 마다 key 수가 범위 안이고, key 가 정렬되어 있는지
+#[cfg(test)]
+fn check_invariants<K: Ord, V>(tree: &BTree<K, V>) {
+    fn depth<K: Ord, V>(n: &Node<K, V>, is_root: bool, lo: Option<&K>, hi: Option<&K>) -> usize {
+        assert!(n.keys.len() <= MAX_KEYS);
+        assert!(is_root || n.keys.len() >= T - 1, "underfull node");
+        assert_eq!(n.keys.len(), n.vals.len());
+        assert!(n.keys.windows(2).all(|w| w[0] < w[1]));
+        assert!(lo.is_none_or(|lo| n.keys.iter().all(|k| k > lo)));
+        assert!(hi.is_none_or(|hi| n.keys.iter().all(|k| k < hi)));
+        if n.is_leaf() {
+            return 1;
+        }
+        assert_eq!(n.children.len(), n.keys.len() + 1);
+        let depths: Vec<usize> = (0..n.children.len())
+            .map(|i| {
+                let lo = if i == 0 { lo } else { Some(&n.keys[i - 1]) };
+                let hi = n.keys.get(i).or(hi);
+                depth(&n.children[i], false, lo, hi)
+            })
+            .collect();
+        assert!(
+            depths.windows(2).all(|w| w[0] == w[1]),
+            "leaves at different depths"
+        );
+        depths[0] + 1
+    }
+
+    if let Some(root) = &tree.root {
+        assert!(!root.keys.is_empty());
+        depth(root, true, None, None);
+    }
+}
+
+#[test]
+fn property() {
+    use rand::Rng;
+
+    super::check_against_btree_map(|rng| rng.gen_range(0u16..512), check_invariants::<u16, u32>);
+}
+
+/// 순서대로 넣고 빼면 split / merge / 형제에게 빌리기가 모두 일어난다.
+#[test]
+fn sequential() {
+    let mut tree = BTree::new();
+    for i in 0..10_000 {
+        tree.insert(i, i * 2);
+    }
+    check_invariants(&tree);
+    assert_eq!(tree.len(), 10_000);
+    assert_eq!(tree.get(&1234), Some(&2468));
+    assert!(tree.range(100..110).map(|(k, _)| *k).eq(100..110));
+    assert_eq!(tree.last(), Some((&9999, &19998)));
+
+    for i in (0..10_000).step_by(2) {
+        assert_eq!(tree.remove(&i), Some(i * 2));
+    }
+    check_invariants(&tree);
+    assert!(tree.iter().map(|(k, _)| *k).eq((1..10_000).step_by(2)));
+    for i in (1..10_000).step_by(2).rev() {
+        assert_eq!(tree.remove(&i), Some(i * 2));
+    }
+    assert!(tree.is_empty());
+    assert!(tree.root.is_none());
+}
@@ -0,0 +1,433 @@
+use super::{BstNode, BstRange, OrderedMap};
+use std::cmp::Ordering;
+use std::mem;
+use std::ops::{Bound, RangeBounds};
+
+/// 반열린 구간 [start, end)
+///
+/// (start, end) 의 사전식 순서로 정렬된다.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Interval<T> {
+    pub start: T,
+    pub end: T,
+}
+
+impl<T: Ord> Interval<T> {
+    pub fn new(start: T, end: T) -> Self {
+        assert!(
+            start <= end,
+            "interval start must not be greater than its end"
+        );
+        Interval { start, end }
+    }
+
+    /// 겹치는 부분이 있는지 (끝이 맞닿기만 하면 겹치지 않음)
+    pub fn overlaps(&self, other: &Interval<T>) -> bool {
+        self.start < other.end && other.start < self.end
+    }
+
+    pub fn contains(&self, point: &T) -> bool {
+        self.start <= *point && *point < self.end
+    }
+}
+
+/// 구간을 key 로 하는 map 에서 주어진 구간과 겹치는 구간을 찾는다 (CLRS 14.3)
+///
+/// - 구간의 start 순으로 정렬된 AVL tree 에, node 마다 subtree 안 구간들의 end 최댓값 (`max_end`) 을 같이 둔다.
+/// - subtree 의 `max_end` 가 찾는 구간의 start 이하이면 그 subtree 에는 겹치는 구간이 없으므로 통째로 건너뛴다.
+///   겹치는 구간이 k 개이면 O(k log n).
+/// - `max_end` 는 child 가 바뀌는 곳 (회전, insert / remove 후 올라오는 길) 에서만 다시 계산하면 된다.
+pub struct IntervalTree<T, V> {
+    root: Link<T, V>,
+    len: usize,
+}
+
+type Link<T, V> = Option<Box<Node<T, V>>>;
+
+struct Node<T, V> {
+    key: Interval<T>,
+    value: V,
+    height: usize,
+    max_end: T,
+    left: Link<T, V>,
+    right: Link<T, V>,
+}
+
+impl<T: Ord, V> BstNode for Node<T, V> {
+    type Key = Interval<T>;
+    type Value = V;
+
+    fn key(&self) -> &Interval<T> {
+        &self.key
+    }
+
+    fn value(&self) -> &V {
+        &self.value
+    }
+
+    fn left(&self) -> Option<&Self> {
+        self.left.as_deref()
+    }
+
+    fn right(&self) -> Option<&Self> {
+        self.right.as_deref()
+    }
+}
+
+fn height<T, V>(link: &Link<T, V>) -> usize {
+    link.as_ref().map_or(0, |n| n.height)
+}
+
+/// child 로부터 height 와 max_end 를 다시 계산
+fn update<T: Ord + Copy, V>(n: &mut Node<T, V>) {
+    n.height = 1 + height(&n.left).max(height(&n.right));
+    n.max_end = [&n.left, &n.right]
+        .into_iter()
+        .flatten()
+        .map(|c| c.max_end)
+        .fold(n.key.end, T::max);
+}
+
+fn rotate_left<T: Ord + Copy, V>(mut n: Box<Node<T, V>>) -> Box<Node<T, V>> {
+    let mut x = n.right.take().unwrap();
+    n.right = x.left.take();
+    update(&mut n);
+    x.left = Some(n);
+    update(&mut x);
+    x
+}
+
+fn rotate_right<T: Ord + Copy, V>(mut n: Box<Node<T, V>>) -> Box<Node<T, V>> {
+    let mut x = n.left.take().unwrap();
+    n.left = x.right.take();
+    update(&mut n);
+    x.right = Some(n);
+    update(&mut x);
+    x
+}
+
+/// 양쪽 높이 차가 2 가 되면 회전해서 1 이하로
+fn rebalance<T: Ord + Copy, V>(mut n: Box<Node<T, V>>) -> Box<Node<T, V>> {
+    update(&mut n);
+    let (l, r) = (height(&n.left), height(&n.right));
+    if l > r + 1 {
+        let left = n.left.as_ref().unwrap();
+        if height(&left.left) < height(&left.right) {
+            n.left = n.left.take().map(rotate_left);
+        }
+        rotate_right(n)
+    } else if r > l + 1 {
+        let right = n.right.as_ref().unwrap();
+        if height(&right.right) < height(&right.left) {
+            n.right = n.right.take().map(rotate_right);
+        }
+        rotate_left(n)
+    } else {
+        n
+    }
+}
+
+fn insert<T: Ord + Copy, V>(
+    link: Link<T, V>,
+    key: Interval<T>,
+    value: V,
+    old: &mut Option<V>,
+) -> Box<Node<T, V>> {
+    let Some(mut n) = link else {
+        return Box::new(Node {
+            max_end: key.end,
+            key,
+            value,
+            height: 1,
+            left: None,
+            right: None,
+        });
+    };
+    match key.cmp(&n.key) {
+        Ordering::Less => n.left = Some(insert(n.left.take(), key, value, old)),
+        Ordering::Greater => n.right = Some(insert(n.right.take(), key, value, old)),
+        Ordering::Equal => {
+            *old = Some(mem::replace(&mut n.value, value));
+            return n;
+        }
+    }
+    rebalance(n)
+}
+
+/// 가장 작은 node 를 떼어내고 (남은 subtree, 떼어낸 node) 를 반환
+fn remove_min<T: Ord + Copy, V>(mut n: Box<Node<T, V>>) -> (Link<T, V>, Box<Node<T, V>>) {
+    match n.left.take() {
+        None => (n.right.take(), n),
+        Some(left) => {
+            let (left, min) = remove_min(left);
+            n.left = left;
+            (Some(rebalance(n)), min)
+        }
+    }
+}
+
+fn remove<T: Ord + Copy, V>(
+    link: Link<T, V>,
+    key: &Interval<T>,
+    removed: &mut Option<V>,
+) -> Link<T, V> {
+    let mut n = link?;
+    match key.cmp(&n.key) {
+        Ordering::Less => n.left = remove(n.left.take(), key, removed),
+        Ordering::Greater => n.right = remove(n.right.take(), key, removed),
+        Ordering::Equal => {
+            let Node {
+                value, left, right, ..
+            } = *n;
+            *removed = Some(value);
+            return match (left, right) {
+                (None, child) | (child, None) => child,
+                (left, Some(right)) => {
+                    // 오른쪽 subtree 의 최솟값을 이 자리로
+                    let (right, mut min) = remove_min(right);
+                    min.left = left;
+                    min.right = right;
+                    Some(rebalance(min))
+                }
+            };
+        }
+    }
+    Some(rebalance(n))
+}
+
+impl<T: Ord + Copy, V> IntervalTree<T, V> {
+    pub fn new() -> Self {
+        IntervalTree { root: None, len: 0 }
+    }
+
+    /// `query` 와 겹치는 구간들 (순서는 정해져 있지 않음)
+    pub fn overlapping(&self, query: Interval<T>) -> Search<'_, T, V> {
+        Search::new(
+            self.root.as_deref(),
+            query.start,
+            Bound::Excluded(query.end),
+        )
+    }
+
+    /// `point` 를 포함하는 구간들 (순서는 정해져 있지 않음)
+    pub fn containing(&self, point: T) -> Search<'_, T, V> {
+        Search::new(self.root.as_deref(), point, Bound::Included(point))
+    }
+}
+
+impl<T: Ord + Copy, V> Default for IntervalTree<T, V> {
+    fn default() -> Self {
+        IntervalTree::new()
+    }
+}
+
+impl<T: Ord + Copy, V> OrderedMap<Interval<T>, V> for IntervalTree<T, V> {
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn get(&self, key: &Interval<T>) -> Option<&V> {
+        let mut node = self.root.as_deref();
+        while let Some(n) = node {
+            node = match key.cmp(&n.key) {
+                Ordering::Less => n.left.as_deref(),
+                Ordering::Greater => n.right.as_deref(),
+                Ordering::Equal => return Some(&n.value),
+            };
+        }
+        None
+    }
+
+    fn insert(&mut self, key: Interval<T>, value: V) -> Option<V> {
+        let mut old = None;
+        self.root = Some(insert(self.root.take(), key, value, &mut old));
+        if old.is_none() {
+            self.len += 1;
+        }
+        old
+    }
+
+    fn remove(&mut self, key: &Interval<T>) -> Option<V> {
+        let mut removed = None;
+        self.root = remove(self.root.take(), key, &mut removed);
+        if removed.is_some() {
+            self.len -= 1;
+        }
+        removed
+    }
+
+    fn range<'a, R: RangeBounds<Interval<T>>>(
+        &'a self,
+        range: R,
+    ) -> impl Iterator<Item = (&'a Interval<T>, &'a V)>
+    where
+        Interval<T>: 'a,
+        V: 'a,
+    {
+        BstRange::new(self.root.as_deref(), range)
+    }
+
+    fn last(&self) -> Option<(&Interval<T>, &V)> {
+        let mut node = self.root.as_deref()?;
+        while let Some(n) = node.right.as_deref() {
+            node = n;
+        }
+        Some((&node.key, &node.value))
+    }
+}
+
+/// `IntervalTree::overlapping`, `IntervalTree::containing` 의 iterator
+///
+/// end 가 `low` 보다 크고 start 가 `high` 안에 있는 구간을 찾는다.
+pub struct Search<'a, T, V> {
+    stack: Vec<&'a Node<T, V>>,
+    low: T,
+    high: Bound<T>,
+}
+
+impl<'a, T: Ord + Copy, V> Search<'a, T, V> {
+    fn new(root: Option<&'a Node<T, V>>, low: T, high: Bound<T>) -> Self {
+        Search {
+            stack: root.into_iter().collect(),
+            low,
+            high,
+        }
+    }
+
+    fn starts_before_high(&self, start: T) -> bool {
+        match self.high {
+            Bound::Included(high) => start <= high,
+            Bound::Excluded(high) => start < high,
+            Bound::Unbounded => true,
+        }
+    }
+}
+
+impl<'a, T: Ord + Copy, V> Iterator for Search<'a, T, V> {
+    type Item = (&'a Interval<T>, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(n) = self.stack.pop() {
+            if n.max_end <= self.low {
+                // subtree 의 모든 구간이 low 이전에 끝난다
+                continue;
+            }
+            let starts_in = self.starts_before_high(n.key.start);
+            if let Some(left) = n.left.as_deref() {
+                self.stack.push(left);
+            }
+            if starts_in {
+                // 오른쪽 subtree 의 start 는 모두 n 이상이므로, n 이 high 를 넘으면 볼 필요가 없다
+                if let Some(right) = n.right.as_deref() {
+                    self.stack.push(right);
+                }
+                if n.key.end > self.low {
+                    return Some((&n.key, &n.value));
+                }
+            }
+        }
+        None
+    }
+}
+
+/// AVL 균형, height 와 max_end 가 child 로부터 계산한 값과 같은지
+#[cfg(test)]
+fn check_invariants<T: Ord + Copy + std::fmt::Debug, V>(tree: &IntervalTree<T, V>) {
+    fn check<T: Ord + Copy + std::fmt::Debug, V>(link: &Link<T, V>) {
+        let Some(n) = link else {
+            return;
+        };
+        check(&n.left);
+        check(&n.right);
+        let (l, r) = (height(&n.left), height(&n.right));
+        assert!(l.abs_diff(r) <= 1, "unbalanced at {:?}", n.key);
+        assert_eq!(n.height, 1 + l.max(r));
+        let max_end = [&n.left, &n.right]
+            .into_iter()
+            .flatten()
+            .map(|c| c.max_end)
+            .fold(n.key.end, T::max);
+        assert_eq!(n.max_end, max_end, "stale max_end at {:?}", n.key);
+    }
+
+    check(&tree.root);
+    assert!(tree.iter().zip(tree.iter().skip(1)).all(|(a, b)| a.0 < b.0));
+}
+
+#[cfg(test)]
+fn random_interval(rng: &mut rand::rngs::StdRng) -> Interval<u16> {
+    use rand::Rng;
+
+    let start = rng.gen_range(0..64);
+    Interval::new(start, start + rng.gen_range(0..16))
+}
+
+#[test]
+fn property() {
+    super::check_against_btree_map(random_interval, check_invariants::<u16, u32>);
+}
+
+/// 모든 구간을 하나씩 확인하는 것과 같은 결과인지
+#[test]
+fn overlapping_against_brute_force() {
+    use rand::SeedableRng;
+
+    let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+    let mut tree = IntervalTree::new();
+    for i in 0..300 {
+        tree.insert(random_interval(&mut rng), i);
+    }
+    for _ in 0..100 {
+        let removed = random_interval(&mut rng);
+        tree.remove(&removed);
+    }
+    check_invariants(&tree);
+
+    let sorted = |mut v: Vec<Interval<u16>>| {
+        v.sort();
+        v
+    };
+    for _ in 0..500 {
+        let query = random_interval(&mut rng);
+        let expected: Vec<_> = tree
+            .iter()
+            .map(|(k, _)| *k)
+            .filter(|k| k.overlaps(&query))
+            .collect();
+        let found = sorted(tree.overlapping(query).map(|(k, _)| *k).collect());
+        assert_eq!(found, expected, "query {:?}", query);
+
+        let point = query.start;
+        let expected: Vec<_> = tree
+            .iter()
+            .map(|(k, _)| *k)
+            .filter(|k| k.contains(&point))
+            .collect();
+        let found = sorted(tree.containing(point).map(|(k, _)| *k).collect());
+        assert_eq!(found, expected, "point {}", point);
+    }
+}
+
+/// 회의실 예약 : 새 예약과 겹치는 기존 예약 찾기
+#[test]
+fn booking() {
+    let mut bookings = IntervalTree::new();
+    bookings.insert(Interval::new(9, 10), "standup");
+    bookings.insert(Interval::new(13, 15), "review");
+    bookings.insert(Interval::new(10, 12), "design");
+
+    let conflicts: Vec<_> = bookings
+        .overlapping(Interval::new(11, 14))
+        .map(|(_, v)| *v)
+        .collect();
+    assert_eq!(conflicts.len(), 2);
+    assert!(conflicts.contains(&"design") && conflicts.contains(&"review"));
+
+    // 끝이 맞닿는 것은 겹치지 않음
+    assert_eq!(bookings.overlapping(Interval::new(12, 13)).count(), 0);
+    assert_eq!(
+        bookings.containing(10).map(|(_, v)| *v).collect::<Vec<_>>(),
+        ["design"]
+    );
+    assert_eq!(bookings.first(), Some((&Interval::new(9, 10), &"standup")));
+}
@@ -0,0 +1,212 @@
+use std::ops::{Bound, RangeBounds};
+
+/// B-tree (node 마다 여러 key, 모든 leaf 가 같은 깊이)
+pub mod btree;
+/// 구간의 끝 최댓값을 들고 있는 AVL tree 로 겹치는 구간 찾기
+pub mod interval;
+/// left-leaning red-black tree (Box 로 연결한 binary tree 의 회전)
+pub mod rbtree;
+/// skip list (확률적으로 level 을 정하는 linked list, node 는 Vec arena 의 index 로 연결)
+pub mod skiplist;
+
+pub use btree::BTree;
+pub use interval::{Interval, IntervalTree};
+pub use rbtree::RbTree;
+pub use skiplist::SkipList;
+
+/// 정렬된 map 의 공통 interface (std::collections::BTreeMap 의 일부)
+pub trait OrderedMap<K: Ord, V> {
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn get(&self, key: &K) -> Option<&V>;
+
+    fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// 같은 key 가 있었으면 값을 바꾸고 이전 값을 반환
+    fn insert(&mut self, key: K, value: V) -> Option<V>;
+
+    fn remove(&mut self, key: &K) -> Option<V>;
+
+    /// range 안의 (key, value) 를 key 순서로
+    fn range<'a, R: RangeBounds<K>>(&'a self, range: R) -> impl Iterator<Item = (&'a K, &'a V)>
+    where
+        K: 'a,
+        V: 'a;
+
+    fn iter<'a>(&'a self) -> impl Iterator<Item = (&'a K, &'a V)>
+    where
+        K: 'a,
+        V: 'a,
+    {
+        self.range(..)
+    }
+
+    /// 가장 작은 key
+    fn first(&self) -> Option<(&K, &V)> {
+        self.iter().next()
+    }
+
+    /// 가장 큰 key
+    fn last(&self) -> Option<(&K, &V)>;
+}
+
+/// key 가 range 의 시작보다 앞인지
+fn before_start<K: Ord, R: RangeBounds<K>>(range: &R, key: &K) -> bool {
+    match range.start_bound() {
+        Bound::Included(s) => key < s,
+        Bound::Excluded(s) => key <= s,
+        Bound::Unbounded => false,
+    }
+}
+
+/// key 가 range 의 끝보다 뒤인지
+fn after_end<K: Ord, R: RangeBounds<K>>(range: &R, key: &K) -> bool {
+    match range.end_bound() {
+        Bound::Included(e) => key > e,
+        Bound::Excluded(e) => key >= e,
+        Bound::Unbounded => false,
+    }
+}
+
+/// rbtree 와 interval 이 같이 쓰는 binary search tree node
+trait BstNode {
+    type Key: Ord;
+    type Value;
+
+    fn key(&self) -> &Self::Key;
+    fn value(&self) -> &Self::Value;
+    fn left(&self) -> Option<&Self>;
+    fn right(&self) -> Option<&Self>;
+}
+
+/// binary search tree 의 range 순회
+///
+/// 시작 key 이상인 조상 node 들만 stack 에 넣고 내려간 뒤, 꺼낼 때마다 오른쪽 subtree 의 왼쪽 끝까지 넣는다.
+struct BstRange<'a, N, R> {
+    stack: Vec<&'a N>,
+    range: R,
+}
+
+impl<'a, N: BstNode, R: RangeBounds<N::Key>> BstRange<'a, N, R> {
+    fn new(root: Option<&'a N>, range: R) -> Self {
+        let mut stack = Vec::new();
+        let mut node = root;
+        while let Some(n) = node {
+            if before_start(&range, n.key()) {
+                node = n.right();
+            } else {
+                stack.push(n);
+                node = n.left();
+            }
+        }
+        BstRange { stack, range }
+    }
+}
+
+impl<'a, N: BstNode, R: RangeBounds<N::Key>> Iterator for BstRange<'a, N, R> {
+    type Item = (&'a N::Key, &'a N::Value);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let n = self.stack.pop()?;
+        if after_end(&self.range, n.key()) {
+            self.stack.clear();
+            return None;
+        }
+        let mut node = n.right();
+        while let Some(m) = node {
+            self.stack.push(m);
+            node = m.left();
+        }
+        Some((n.key(), n.value()))
+    }
+}
+
+/// 무작위 insert / remove / get / range / first / last 를 BTreeMap 과 같이 실행하면서 결과를 비교하는 property test
+///
+/// - seed 마다 다른 연산 순서, key 범위를 좁게 잡아서 같은 key 의 덮어쓰기와 삭제가 자주 일어나게 한다.
+/// - 중간중간 전체 순회 결과와 각 구현의 불변 조건 (invariant) 을 검사
+#[cfg(test)]
+fn check_against_btree_map<K, M>(
+    mut key: impl FnMut(&mut rand::rngs::StdRng) -> K,
+    invariant: impl Fn(&M),
+) where
+    K: Ord + Clone + std::fmt::Debug,
+    M: OrderedMap<K, u32> + Default,
+{
+    use rand::{Rng, SeedableRng};
+    use std::collections::BTreeMap;
+
+    const SEEDS: u64 = 16;
+    const OPS: u32 = 2_000;
+
+    for seed in 0..SEEDS {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+        let mut map = M::default();
+        let mut model = BTreeMap::new();
+
+        for i in 0..OPS {
+            match rng.gen_range(0..100) {
+                0..=44 => {
+                    let k = key(&mut rng);
+                    assert_eq!(
+                        map.insert(k.clone(), i),
+                        model.insert(k, i),
+                        "seed {}",
+                        seed
+                    );
+                }
+                45..=74 => {
+                    let k = key(&mut rng);
+                    assert_eq!(map.remove(&k), model.remove(&k), "seed {}", seed);
+                }
+                75..=84 => {
+                    let k = key(&mut rng);
+                    assert_eq!(map.get(&k), model.get(&k), "seed {}", seed);
+                    assert_eq!(map.contains_key(&k), model.contains_key(&k));
+                }
+                85..=94 => {
+                    let (mut a, mut b) = (key(&mut rng), key(&mut rng));
+                    if a > b {
+                        std::mem::swap(&mut a, &mut b);
+                    }
+                    let bound = |k: K, rng: &mut rand::rngs::StdRng| match rng.gen_range(0..3) {
+                        0 => Bound::Included(k),
+                        1 => Bound::Excluded(k),
+                        _ => Bound::Unbounded,
+                    };
+                    let mut range = (bound(a.clone(), &mut rng), bound(b, &mut rng));
+                    if let (Bound::Excluded(s), Bound::Excluded(e)) = &range {
+                        if s == e {
+                            // BTreeMap::range 는 (Excluded(x), Excluded(x)) 에서 panic
+                            range.0 = Bound::Included(a);
+                        }
+                    }
+                    assert!(
+                        map.range(range.clone()).eq(model.range(range.clone())),
+                        "seed {} range {:?}",
+                        seed,
+                        range
+                    );
+                }
+                _ => {
+                    assert_eq!(map.first(), model.iter().next(), "seed {}", seed);
+                    assert_eq!(map.last(), model.iter().next_back(), "seed {}", seed);
+                }
+            }
+            assert_eq!(map.len(), model.len(), "seed {}", seed);
+
+            if i % 200 == 0 {
+                assert!(map.iter().eq(model.iter()), "seed {}", seed);
+                invariant(&map);
+            }
+        }
+        assert!(map.iter().eq(model.iter()), "seed {}", seed);
+        invariant(&map);
+    }
+}
@@ -0,0 +1,305 @@
+use super::{BstNode, BstRange, OrderedMap};
+use std::cmp::Ordering;
+use std::mem;
+use std::ops::RangeBounds;
+
+/// left-leaning red-black tree (Sedgewick, Algorithms 4th ed. 3.3)
+///
+/// - 2-3 tree 를 binary tree 로 표현한 것. red link 는 3-node 를 이루는 두 key 를 묶는 link 이고, 항상 왼쪽에만 둔다.
+/// - root 에서 leaf 까지의 black link 수가 모두 같으므로 높이는 2 log n 이하.
+/// - child 는 `Option<Box<Node>>` 로 소유한다. 회전은 Box 를 `take` 로 꺼내서 연결을 바꾸고 새 subtree 의 root 를 반환하고,
+///   재귀 insert / remove 는 subtree 를 받아서 고친 subtree 를 돌려준다 (foundation1 의 mem::take 참고).
+pub struct RbTree<K, V> {
+    root: Link<K, V>,
+    len: usize,
+}
+
+type Link<K, V> = Option<Box<Node<K, V>>>;
+
+struct Node<K, V> {
+    key: K,
+    value: V,
+    red: bool, // 부모에서 이 node 로 오는 link 의 색
+    left: Link<K, V>,
+    right: Link<K, V>,
+}
+
+impl<K: Ord, V> BstNode for Node<K, V> {
+    type Key = K;
+    type Value = V;
+
+    fn key(&self) -> &K {
+        &self.key
+    }
+
+    fn value(&self) -> &V {
+        &self.value
+    }
+
+    fn left(&self) -> Option<&Self> {
+        self.left.as_deref()
+    }
+
+    fn right(&self) -> Option<&Self> {
+        self.right.as_deref()
+    }
+}
+
+fn is_red<K, V>(link: &Link<K, V>) -> bool {
+    link.as_ref().is_some_and(|n| n.red)
+}
+
+/// 왼쪽 child 의 왼쪽 link 가 red 인지
+fn is_left_left_red<K, V>(link: &Link<K, V>) -> bool {
+    link.as_ref().is_some_and(|n| is_red(&n.left))
+}
+
+/// 오른쪽으로 기운 red link 를 왼쪽으로
+fn rotate_left<K, V>(mut h: Box<Node<K, V>>) -> Box<Node<K, V>> {
+    let mut x = h.right.take().expect("rotate_left without right child");
+    h.right = x.left.take();
+    x.red = h.red;
+    h.red = true;
+    x.left = Some(h);
+    x
+}
+
+fn rotate_right<K, V>(mut h: Box<Node<K, V>>) -> Box<Node<K, V>> {
+    let mut x = h.left.take().expect("rotate_right without left child");
+    h.left = x.right.take();
+    x.red = h.red;
+    h.red = true;
+    x.right = Some(h);
+    x
+}
+
+/// 4-node 를 나누거나 (insert) 합친다 (remove)
+fn flip_colors<K, V>(h: &mut Node<K, V>) {
+    h.red = !h.red;
+    for child in [&mut h.left, &mut h.right].into_iter().flatten() {
+        child.red = !child.red;
+    }
+}
+
+/// 올라오면서 red link 가 왼쪽에만, 연속되지 않게 고친다
+fn balance<K, V>(mut h: Box<Node<K, V>>) -> Box<Node<K, V>> {
+    if is_red(&h.right) && !is_red(&h.left) {
+        h = rotate_left(h);
+    }
+    if is_red(&h.left) && is_left_left_red(&h.left) {
+        h = rotate_right(h);
+    }
+    if is_red(&h.left) && is_red(&h.right) {
+        flip_colors(&mut h);
+    }
+    h
+}
+
+/// h 가 red 이고 h.left, h.left.left 가 black 이면, h.left 나 그 child 중 하나를 red 로 만든다
+fn move_red_left<K, V>(mut h: Box<Node<K, V>>) -> Box<Node<K, V>> {
+    flip_colors(&mut h);
+    if is_left_left_red(&h.right) {
+        h.right = h.right.take().map(rotate_right);
+        h = rotate_left(h);
+        flip_colors(&mut h);
+    }
+    h
+}
+
+fn move_red_right<K, V>(mut h: Box<Node<K, V>>) -> Box<Node<K, V>> {
+    flip_colors(&mut h);
+    if is_left_left_red(&h.left) {
+        h = rotate_right(h);
+        flip_colors(&mut h);
+    }
+    h
+}
+
+fn insert<K: Ord, V>(h: Link<K, V>, key: K, value: V, old: &mut Option<V>) -> Box<Node<K, V>> {
+    let Some(mut h) = h else {
+        return Box::new(Node {
+            key,
+            value,
+            red: true,
+            left: None,
+            right: None,
+        });
+    };
+    match key.cmp(&h.key) {
+        Ordering::Less => h.left = Some(insert(h.left.take(), key, value, old)),
+        Ordering::Greater => h.right = Some(insert(h.right.take(), key, value, old)),
+        Ordering::Equal => *old = Some(mem::replace(&mut h.value, value)),
+    }
+    balance(h)
+}
+
+/// 가장 작은 node 를 떼어내고 (남은 subtree, 떼어낸 node) 를 반환
+fn delete_min<K, V>(mut h: Box<Node<K, V>>) -> (Link<K, V>, Box<Node<K, V>>) {
+    if h.left.is_none() {
+        // left-leaning 이므로 왼쪽이 없으면 오른쪽도 없다
+        return (None, h);
+    }
+    if !is_red(&h.left) && !is_left_left_red(&h.left) {
+        h = move_red_left(h);
+    }
+    let (left, min) = delete_min(h.left.take().unwrap());
+    h.left = left;
+    (Some(balance(h)), min)
+}
+
+/// key 가 tree 에 있어야 한다
+fn delete<K: Ord, V>(mut h: Box<Node<K, V>>, key: &K, removed: &mut Option<V>) -> Link<K, V> {
+    if *key < h.key {
+        if !is_red(&h.left) && !is_left_left_red(&h.left) {
+            h = move_red_left(h);
+        }
+        h.left = delete(h.left.take().unwrap(), key, removed);
+    } else {
+        if is_red(&h.left) {
+            h = rotate_right(h);
+        }
+        if *key == h.key && h.right.is_none() {
+            *removed = Some(h.value);
+            return None;
+        }
+        if !is_red(&h.right) && !is_left_left_red(&h.right) {
+            h = move_red_right(h);
+        }
+        if *key == h.key {
+            // 오른쪽 subtree 의 최솟값을 이 자리로 옮긴다
+            let (right, min) = delete_min(h.right.take().unwrap());
+            h.right = right;
+            let Node { key, value, .. } = *min;
+            h.key = key;
+            *removed = Some(mem::replace(&mut h.value, value));
+        } else {
+            h.right = delete(h.right.take().unwrap(), key, removed);
+        }
+    }
+    Some(balance(h))
+}
+
+impl<K: Ord, V> RbTree<K, V> {
+    pub fn new() -> Self {
+        RbTree { root: None, len: 0 }
+    }
+}
+
+impl<K: Ord, V> Default for RbTree<K, V> {
+    fn default() -> Self {
+        RbTree::new()
+    }
+}
+
+impl<K: Ord, V> OrderedMap<K, V> for RbTree<K, V> {
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn get(&self, key: &K) -> Option<&V> {
+        let mut node = self.root.as_deref();
+        while let Some(n) = node {
+            node = match key.cmp(&n.key) {
+                Ordering::Less => n.left.as_deref(),
+                Ordering::Greater => n.right.as_deref(),
+                Ordering::Equal => return Some(&n.value),
+            };
+        }
+        None
+    }
+
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let mut old = None;
+        let mut root = insert(self.root.take(), key, value, &mut old);
+        root.red = false;
+        self.root = Some(root);
+        if old.is_none() {
+            self.len += 1;
+        }
+        old
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        // 위에서 내려가며 미리 red 를 밀어 넣는 방식이라 없는 key 를 지우면 모양이 깨질 수 있다
+        if !self.contains_key(key) {
+            return None;
+        }
+        let mut root = self.root.take().unwrap();
+        if !is_red(&root.left) && !is_red(&root.right) {
+            root.red = true;
+        }
+        let mut removed = None;
+        self.root = delete(root, key, &mut removed);
+        if let Some(root) = &mut self.root {
+            root.red = false;
+        }
+        self.len -= 1;
+        removed
+    }
+
+    fn range<'a, R: RangeBounds<K>>(&'a self, range: R) -> impl Iterator<Item = (&'a K, &'a V)>
+    where
+        K: 'a,
+        V: 'a,
+    {
+        BstRange::new(self.root.as_deref(), range)
+    }
+
+    fn last(&self) -> Option<(&K, &V)> {
+        let mut node = self.root.as_deref()?;
+        while let Some(n) = node.right.as_deref() {
+            node = n;
+        }
+        Some((&node.key, &node.value))
+    }
+}
+
+/// root 가 black, red link 는 왼쪽에만 있고 연속되지 않으며, 모든 경로의 black link 수가 같은지
+#[cfg(test)]
+fn check_invariants<K: Ord, V>(tree: &RbTree<K, V>) {
+    fn black_height<K: Ord, V>(link: &Link<K, V>) -> usize {
+        let Some(n) = link else {
+            return 0;
+        };
+        assert!(!is_red(&n.right), "right-leaning red link");
+        assert!(!(n.red && is_red(&n.left)), "two red links in a row");
+        assert!(n.left.as_ref().is_none_or(|l| l.key < n.key));
+        assert!(n.right.as_ref().is_none_or(|r| r.key > n.key));
+        let (l, r) = (black_height(&n.left), black_height(&n.right));
+        assert_eq!(l, r, "unbalanced black height");
+        l + usize::from(!n.red)
+    }
+
+    assert!(!is_red(&tree.root));
+    black_height(&tree.root);
+    assert!(tree.iter().zip(tree.iter().skip(1)).all(|(a, b)| a.0 < b.0));
+}
+
+#[test]
+fn property() {
+    use rand::Rng;
+
+    super::check_against_btree_map(|rng| rng.gen_range(0u16..512), check_invariants::<u16, u32>);
+}
+
+#[test]
+fn sequential() {
+    let mut tree = RbTree::new();
+    for i in 0..4096 {
+        tree.insert(i, ());
+    }
+    check_invariants(&tree);
+
+    // 정렬된 순서로 넣어도 높이가 2 log n 이하
+    fn height<K, V>(link: &Link<K, V>) -> usize {
+        link.as_ref()
+            .map_or(0, |n| 1 + height(&n.left).max(height(&n.right)))
+    }
+    assert!(height(&tree.root) <= 2 * 12);
+
+    for i in 0..4096 {
+        assert_eq!(tree.remove(&i), Some(()));
+        assert_eq!(tree.remove(&i), None);
+    }
+    assert!(tree.root.is_none());
+}
@@ -0,0 +1,256 @@
+use super::{after_end, before_start, OrderedMap};
+use std::mem;
+use std::ops::RangeBounds;
+
+const MAX_LEVEL: usize = 16;
+
+/// skip list (Pugh, 1990)
+///
+/// - 정렬된 linked list 위에 node 의 절반, 1/4, 1/8 ... 만 잇는 list 를 겹쳐 쌓은 것.
+///   위 level 에서 크게 건너뛰다가 내려오므로 탐색은 기대값 O(log n).
+/// - node 의 level 은 동전 던지기 (p = 1/2) 로 정하므로 회전 같은 재균형이 필요 없다.
+/// - node 는 `Vec` arena 에 두고 index 로 연결한다. 여러 level 에서 같은 node 를 가리켜야 해서
+///   Box 로는 소유 관계를 표현할 수 없고, Rc<RefCell> 로 공유하면 borrow 를 runtime 에 세야 하기 때문.
+pub struct SkipList<K, V> {
+    nodes: Vec<Option<Node<K, V>>>,
+    free: Vec<usize>, // 지워진 node 의 자리
+    head: [Option<usize>; MAX_LEVEL],
+    level: usize, // 사용 중인 level 수
+    len: usize,
+    rng: u64,
+}
+
+struct Node<K, V> {
+    key: K,
+    value: V,
+    next: Vec<Option<usize>>, // level 마다 다음 node, 길이가 이 node 의 level
+}
+
+/// level 마다 마지막으로 지나온 node (None 이면 head)
+type Preds = [Option<usize>; MAX_LEVEL];
+
+impl<K: Ord, V> SkipList<K, V> {
+    pub fn new() -> Self {
+        Self::with_seed(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// level 을 정하는 난수의 seed 를 지정
+    pub fn with_seed(seed: u64) -> Self {
+        SkipList {
+            nodes: Vec::new(),
+            free: Vec::new(),
+            head: [None; MAX_LEVEL],
+            level: 0,
+            len: 0,
+            rng: seed.max(1),
+        }
+    }
+
+    fn node(&self, i: usize) -> &Node<K, V> {
+        self.nodes[i].as_ref().expect("dangling skip list link")
+    }
+
+    fn next(&self, prev: Option<usize>, level: usize) -> Option<usize> {
+        match prev {
+            Some(i) => self.node(i).next[level],
+            None => self.head[level],
+        }
+    }
+
+    fn set_next(&mut self, prev: Option<usize>, level: usize, next: Option<usize>) {
+        match prev {
+            Some(i) => self.nodes[i].as_mut().unwrap().next[level] = next,
+            None => self.head[level] = next,
+        }
+    }
+
+    /// 맨 위 level 부터 `go_right` 가 참인 동안 오른쪽으로 가고, 아니면 한 level 내려간다
+    fn descend(&self, mut go_right: impl FnMut(&K) -> bool) -> Preds {
+        let mut preds = [None; MAX_LEVEL];
+        let mut x = None;
+        for level in (0..self.level).rev() {
+            while let Some(n) = self.next(x, level) {
+                if !go_right(&self.node(n).key) {
+                    break;
+                }
+                x = Some(n);
+            }
+            preds[level] = x;
+        }
+        preds
+    }
+
+    /// xorshift64 로 1 ..= MAX_LEVEL 사이의 level 을 뽑는다 (level k 일 확률 1/2^k)
+    fn random_level(&mut self) -> usize {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        (1 + self.rng.trailing_ones() as usize).min(MAX_LEVEL)
+    }
+}
+
+impl<K: Ord, V> Default for SkipList<K, V> {
+    fn default() -> Self {
+        SkipList::new()
+    }
+}
+
+impl<K: Ord, V> OrderedMap<K, V> for SkipList<K, V> {
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn get(&self, key: &K) -> Option<&V> {
+        let preds = self.descend(|k| k < key);
+        let node = self.node(self.next(preds[0], 0)?);
+        (node.key == *key).then_some(&node.value)
+    }
+
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let preds = self.descend(|k| *k < key);
+        if let Some(n) = self.next(preds[0], 0) {
+            let node = self.nodes[n].as_mut().unwrap();
+            if node.key == key {
+                return Some(mem::replace(&mut node.value, value));
+            }
+        }
+
+        // 새 level 의 preds 는 head (None) 그대로
+        let height = self.random_level();
+        self.level = self.level.max(height);
+        let node = Node {
+            key,
+            value,
+            next: (0..height).map(|l| self.next(preds[l], l)).collect(),
+        };
+        let i = match self.free.pop() {
+            Some(i) => {
+                self.nodes[i] = Some(node);
+                i
+            }
+            None => {
+                self.nodes.push(Some(node));
+                self.nodes.len() - 1
+            }
+        };
+        for (level, &pred) in preds.iter().enumerate().take(height) {
+            self.set_next(pred, level, Some(i));
+        }
+        self.len += 1;
+        None
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        let preds = self.descend(|k| k < key);
+        let i = self.next(preds[0], 0)?;
+        if self.node(i).key != *key {
+            return None;
+        }
+        let node = self.nodes[i].take().unwrap();
+        for (level, next) in node.next.into_iter().enumerate() {
+            self.set_next(preds[level], level, next);
+        }
+        self.free.push(i);
+        while self.level > 0 && self.head[self.level - 1].is_none() {
+            self.level -= 1;
+        }
+        self.len -= 1;
+        Some(node.value)
+    }
+
+    fn range<'a, R: RangeBounds<K>>(&'a self, range: R) -> impl Iterator<Item = (&'a K, &'a V)>
+    where
+        K: 'a,
+        V: 'a,
+    {
+        let preds = self.descend(|k| before_start(&range, k));
+        let mut cur = self.next(preds[0], 0);
+        std::iter::from_fn(move || {
+            let node = self.node(cur?);
+            if after_end(&range, &node.key) {
+                cur = None;
+                return None;
+            }
+            cur = node.next[0];
+            Some((&node.key, &node.value))
+        })
+    }
+
+    fn last(&self) -> Option<(&K, &V)> {
+        let preds = self.descend(|_| true);
+        let node = self.node(preds[0]?);
+        Some((&node.key, &node.value))
+    }
+}
+
+/// level 마다 list 가 정렬되어 있고 바로 아래 level 의 부분 수열인지, 비어있는 맨 위 level 이 없는지
+#[cfg(test)]
+fn check_invariants<K: Ord, V>(list: &SkipList<K, V>) {
+    let chain = |level: usize| {
+        std::iter::successors(list.head[level], move |&i| list.node(i).next[level])
+            .collect::<Vec<_>>()
+    };
+
+    let bottom = chain(0);
+    assert_eq!(bottom.len(), list.len);
+    assert_eq!(list.nodes.iter().flatten().count(), list.len);
+    assert!(list.level == 0 || list.head[list.level - 1].is_some());
+    assert!(list.head[list.level..].iter().all(Option::is_none));
+
+    let mut below = bottom;
+    for level in 0..list.level {
+        let this = chain(level);
+        assert!(this
+            .windows(2)
+            .all(|w| list.node(w[0]).key < list.node(w[1]).key));
+        let mut rest = below.iter();
+        assert!(
+            this.iter().all(|i| rest.any(|j| i == j)),
+            "level {} skips a node",
+            level
+        );
+        assert!(this.iter().all(|&i| list.node(i).next.len() > level));
+        below = this;
+    }
+}
+
+#[test]
+fn property() {
+    use rand::Rng;
+
+    super::check_against_btree_map(|rng| rng.gen_range(0u16..512), check_invariants::<u16, u32>);
+}
+
+/// 지운 node 의 자리를 다시 쓰고, level 분포가 대략 1/2 씩 줄어드는지
+#[test]
+fn levels() {
+    let mut list = SkipList::with_seed(7);
+    for i in 0..4096 {
+        list.insert(i, ());
+    }
+    for i in 0..2048 {
+        list.remove(&i);
+    }
+    for i in 4096..6144 {
+        list.insert(i, ());
+    }
+    assert_eq!(list.nodes.len(), 4096);
+    check_invariants(&list);
+
+    let count = |level: usize| {
+        list.nodes
+            .iter()
+            .flatten()
+            .filter(|n| n.next.len() > level)
+            .count()
+    };
+    for level in 1..6 {
+        let ratio = count(level) as f64 / count(level - 1) as f64;
+        assert!(
+            (0.4..0.6).contains(&ratio),
+            "level {} ratio {}",
+            level,
+            ratio
+        );
+    }
+}
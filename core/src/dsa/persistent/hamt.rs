@@ -0,0 +1,565 @@
+use super::{PointerKind, RcK};
+use std::borrow::Borrow;
+use std::collections::hash_map::DefaultHasher;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::iter::FromIterator;
+use std::mem;
+use std::ops::Index;
+
+const BITS: u32 = 5;
+const MASK: u64 = (1 << BITS) - 1;
+
+/// hash array mapped trie (Bagwell) 로 만든 persistent hash map
+///
+/// - hash 의 5 bit 씩이 각 level 의 child 번호. 32칸 배열 대신 bitmap 에 있는 child 만 저장한다.
+///   (child 의 위치 = bitmap 에서 자기 bit 아래에 켜진 bit 수)
+/// - 64 bit hash 가 완전히 같은 key 들은 Collision node 에 모은다.
+/// - clone 은 O(1), insert / remove 는 root 부터 바뀌는 경로의 node 만 복사한다.
+pub struct HashTrieMap<K, V, P: PointerKind = RcK> {
+    len: usize,
+    root: P::Pointer<Node<K, V, P>>,
+}
+
+enum Node<K, V, P: PointerKind> {
+    Branch {
+        bitmap: u32,
+        entries: Vec<Entry<K, V, P>>,
+    },
+    Collision {
+        hash: u64,
+        pairs: Vec<(K, V)>,
+    },
+}
+
+enum Entry<K, V, P: PointerKind> {
+    Leaf { hash: u64, key: K, value: V },
+    Node(P::Pointer<Node<K, V, P>>),
+}
+
+impl<K: Clone, V: Clone, P: PointerKind> Clone for Node<K, V, P> {
+    fn clone(&self) -> Self {
+        match self {
+            Node::Branch { bitmap, entries } => Node::Branch {
+                bitmap: *bitmap,
+                entries: entries.clone(),
+            },
+            Node::Collision { hash, pairs } => Node::Collision {
+                hash: *hash,
+                pairs: pairs.clone(),
+            },
+        }
+    }
+}
+
+impl<K: Clone, V: Clone, P: PointerKind> Clone for Entry<K, V, P> {
+    fn clone(&self) -> Self {
+        match self {
+            Entry::Leaf { hash, key, value } => Entry::Leaf {
+                hash: *hash,
+                key: key.clone(),
+                value: value.clone(),
+            },
+            Entry::Node(node) => Entry::Node(node.clone()),
+        }
+    }
+}
+
+fn hash_of<Q: Hash + ?Sized>(key: &Q) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// level (shift) 에서의 bit 와 entries 안의 위치
+fn position(bitmap: u32, hash: u64, shift: u32) -> (u32, usize) {
+    let bit = 1 << ((hash >> shift) & MASK);
+    (bit, (bitmap & (bit - 1)).count_ones() as usize)
+}
+
+impl<K, V, P: PointerKind> Node<K, V, P> {
+    fn empty() -> Self {
+        Node::Branch {
+            bitmap: 0,
+            entries: Vec::new(),
+        }
+    }
+
+    fn get<Q>(&self, hash: u64, key: &Q) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+    {
+        let mut node = self;
+        let mut shift = 0;
+        loop {
+            match node {
+                Node::Branch { bitmap, entries } => {
+                    let (bit, pos) = position(*bitmap, hash, shift);
+                    if bitmap & bit == 0 {
+                        return None;
+                    }
+                    match &entries[pos] {
+                        Entry::Leaf {
+                            hash: h,
+                            key: k,
+                            value,
+                        } => {
+                            return (*h == hash && k.borrow() == key).then_some((k, value));
+                        }
+                        Entry::Node(child) => node = child,
+                    }
+                    shift += BITS;
+                }
+                Node::Collision { hash: h, pairs } => {
+                    if *h != hash {
+                        return None;
+                    }
+                    return pairs
+                        .iter()
+                        .find(|(k, _)| k.borrow() == key)
+                        .map(|(k, v)| (k, v));
+                }
+            }
+        }
+    }
+}
+
+impl<K: Eq + Clone, V: Clone, P: PointerKind> Node<K, V, P> {
+    /// 같은 key 가 있었으면 값을 바꾸고 이전 값을 반환
+    fn insert(&mut self, shift: u32, hash: u64, key: K, value: V) -> Option<V> {
+        if let Node::Collision { hash: h, .. } = self {
+            if *h != hash {
+                // 아직 hash 가 갈라지지 않은 level 이므로 collision node 를 branch 아래로 내린다
+                let (bit, _) = position(0, *h, shift);
+                let collision = mem::replace(self, Node::empty());
+                *self = Node::Branch {
+                    bitmap: bit,
+                    entries: vec![Entry::Node(P::new(collision))],
+                };
+            }
+        }
+
+        match self {
+            Node::Collision { pairs, .. } => {
+                if let Some((_, v)) = pairs.iter_mut().find(|(k, _)| *k == key) {
+                    return Some(mem::replace(v, value));
+                }
+                pairs.push((key, value));
+                None
+            }
+            Node::Branch { bitmap, entries } => {
+                let (bit, pos) = position(*bitmap, hash, shift);
+                if *bitmap & bit == 0 {
+                    *bitmap |= bit;
+                    entries.insert(pos, Entry::Leaf { hash, key, value });
+                    return None;
+                }
+                match &mut entries[pos] {
+                    Entry::Node(child) => P::make_mut(child).insert(shift + BITS, hash, key, value),
+                    Entry::Leaf {
+                        hash: h,
+                        key: k,
+                        value: v,
+                    } => {
+                        if *h == hash && *k == key {
+                            return Some(mem::replace(v, value));
+                        }
+                        // 다른 key 와 자리가 겹치면 두 leaf 를 가진 node 로 나눈다
+                        let (h, k, v) = (*h, k.clone(), v.clone());
+                        let node = Self::pair(shift + BITS, (h, k, v), (hash, key, value));
+                        entries[pos] = Entry::Node(P::new(node));
+                        None
+                    }
+                }
+            }
+        }
+    }
+
+    fn pair(shift: u32, a: (u64, K, V), b: (u64, K, V)) -> Self {
+        if a.0 == b.0 {
+            return Node::Collision {
+                hash: a.0,
+                pairs: vec![(a.1, a.2), (b.1, b.2)],
+            };
+        }
+        let (bit_a, _) = position(0, a.0, shift);
+        let (bit_b, _) = position(0, b.0, shift);
+        if bit_a == bit_b {
+            return Node::Branch {
+                bitmap: bit_a,
+                entries: vec![Entry::Node(P::new(Self::pair(shift + BITS, a, b)))],
+            };
+        }
+        let leaf = |(hash, key, value)| Entry::Leaf { hash, key, value };
+        let entries = if bit_a < bit_b {
+            vec![leaf(a), leaf(b)]
+        } else {
+            vec![leaf(b), leaf(a)]
+        };
+        Node::Branch {
+            bitmap: bit_a | bit_b,
+            entries,
+        }
+    }
+
+    fn remove<Q>(&mut self, shift: u32, hash: u64, key: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+    {
+        match self {
+            Node::Collision { hash: h, pairs } => {
+                if *h != hash {
+                    return None;
+                }
+                let i = pairs.iter().position(|(k, _)| k.borrow() == key)?;
+                Some(pairs.swap_remove(i))
+            }
+            Node::Branch { bitmap, entries } => {
+                let (bit, pos) = position(*bitmap, hash, shift);
+                if *bitmap & bit == 0 {
+                    return None;
+                }
+                let removed = match &mut entries[pos] {
+                    Entry::Leaf {
+                        hash: h, key: k, ..
+                    } => {
+                        if *h != hash || (*k).borrow() != key {
+                            return None;
+                        }
+                        *bitmap &= !bit;
+                        match entries.remove(pos) {
+                            Entry::Leaf { key, value, .. } => return Some((key, value)),
+                            Entry::Node(_) => unreachable!(),
+                        }
+                    }
+                    Entry::Node(child) => {
+                        let child = P::make_mut(child);
+                        let removed = child.remove(shift + BITS, hash, key)?;
+                        // leaf 하나만 남은 node 는 위로 끌어올려서 경로를 짧게 유지
+                        if let Some(leaf) = child.take_single_leaf() {
+                            entries[pos] = leaf;
+                        }
+                        removed
+                    }
+                };
+                Some(removed)
+            }
+        }
+    }
+
+    fn take_single_leaf(&mut self) -> Option<Entry<K, V, P>> {
+        match self {
+            Node::Branch { entries, .. } => match entries.as_slice() {
+                [Entry::Leaf { .. }] => entries.pop(),
+                _ => None,
+            },
+            Node::Collision { hash, pairs } if pairs.len() == 1 => {
+                let (key, value) = pairs.pop().unwrap();
+                Some(Entry::Leaf {
+                    hash: *hash,
+                    key,
+                    value,
+                })
+            }
+            Node::Collision { .. } => None,
+        }
+    }
+}
+
+impl<K, V, P: PointerKind> HashTrieMap<K, V, P> {
+    pub fn new() -> Self {
+        HashTrieMap {
+            len: 0,
+            root: P::new(Node::empty()),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn iter(&self) -> Iter<'_, K, V, P> {
+        let entries = match &*self.root {
+            Node::Branch { entries, .. } => entries.iter(),
+            Node::Collision { .. } => unreachable!("root is always a branch"),
+        };
+        Iter {
+            stack: vec![entries],
+            pairs: [].iter(),
+            remaining: self.len,
+        }
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &K> + '_ {
+        self.iter().map(|(k, _)| k)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &V> + '_ {
+        self.iter().map(|(_, v)| v)
+    }
+}
+
+impl<K: Hash + Eq, V, P: PointerKind> HashTrieMap<K, V, P> {
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.root.get(hash_of(key), key).map(|(_, v)| v)
+    }
+
+    pub fn get_key_value<Q>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.root.get(hash_of(key), key)
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get(key).is_some()
+    }
+}
+
+impl<K: Hash + Eq + Clone, V: Clone, P: PointerKind> HashTrieMap<K, V, P> {
+    /// 같은 key 가 있었으면 이전 값을 반환 (std::collections::HashMap::insert 와 같음)
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let hash = hash_of(&key);
+        let old = P::make_mut(&mut self.root).insert(0, hash, key, value);
+        if old.is_none() {
+            self.len += 1;
+        }
+        old
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.remove_entry(key).map(|(_, v)| v)
+    }
+
+    pub fn remove_entry<Q>(&mut self, key: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let hash = hash_of(key);
+        // 없는 key 이면 root 를 복사하지 않도록 먼저 확인
+        self.root.get(hash, key)?;
+        let removed = P::make_mut(&mut self.root).remove(0, hash, key);
+        if removed.is_some() {
+            self.len -= 1;
+        }
+        removed
+    }
+}
+
+impl<K, V, P: PointerKind> Clone for HashTrieMap<K, V, P> {
+    fn clone(&self) -> Self {
+        HashTrieMap {
+            len: self.len,
+            root: self.root.clone(),
+        }
+    }
+}
+
+impl<K, V, P: PointerKind> Default for HashTrieMap<K, V, P> {
+    fn default() -> Self {
+        HashTrieMap::new()
+    }
+}
+
+impl<K: Hash + Eq, V: PartialEq, P: PointerKind> PartialEq for HashTrieMap<K, V, P> {
+    fn eq(&self, other: &Self) -> bool {
+        if P::ptr_eq(&self.root, &other.root) {
+            return true;
+        }
+        self.len == other.len && self.iter().all(|(k, v)| other.get(k) == Some(v))
+    }
+}
+
+impl<K: Hash + Eq, V: Eq, P: PointerKind> Eq for HashTrieMap<K, V, P> {}
+
+impl<K: fmt::Debug, V: fmt::Debug, P: PointerKind> fmt::Debug for HashTrieMap<K, V, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K, Q, V, P> Index<&Q> for HashTrieMap<K, V, P>
+where
+    K: Hash + Eq + Borrow<Q>,
+    Q: Hash + Eq + ?Sized,
+    P: PointerKind,
+{
+    type Output = V;
+
+    fn index(&self, key: &Q) -> &V {
+        self.get(key).expect("key not found in HashTrieMap")
+    }
+}
+
+impl<K: Hash + Eq + Clone, V: Clone, P: PointerKind> FromIterator<(K, V)> for HashTrieMap<K, V, P> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = HashTrieMap::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K: Hash + Eq + Clone, V: Clone, P: PointerKind> Extend<(K, V)> for HashTrieMap<K, V, P> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (k, v) in iter {
+            self.insert(k, v);
+        }
+    }
+}
+
+impl<'a, K, V, P: PointerKind> IntoIterator for &'a HashTrieMap<K, V, P> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V, P>;
+
+    fn into_iter(self) -> Iter<'a, K, V, P> {
+        self.iter()
+    }
+}
+
+/// trie 를 depth first 로 순회 (순서는 hash 순서)
+pub struct Iter<'a, K, V, P: PointerKind> {
+    stack: Vec<std::slice::Iter<'a, Entry<K, V, P>>>,
+    pairs: std::slice::Iter<'a, (K, V)>, // 순회 중인 collision node
+    remaining: usize,
+}
+
+impl<'a, K, V, P: PointerKind> Iterator for Iter<'a, K, V, P> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<(&'a K, &'a V)> {
+        loop {
+            if let Some((k, v)) = self.pairs.next() {
+                self.remaining -= 1;
+                return Some((k, v));
+            }
+            match self.stack.last_mut()?.next() {
+                None => {
+                    self.stack.pop();
+                }
+                Some(Entry::Leaf { key, value, .. }) => {
+                    self.remaining -= 1;
+                    return Some((key, value));
+                }
+                Some(Entry::Node(node)) => match &**node {
+                    Node::Branch { entries, .. } => self.stack.push(entries.iter()),
+                    Node::Collision { pairs, .. } => self.pairs = pairs.iter(),
+                },
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, K, V, P: PointerKind> ExactSizeIterator for Iter<'a, K, V, P> {}
+
+/// std HashMap 과 같은 결과인지 무작위 insert / remove 로 확인
+#[test]
+fn against_std() {
+    use rand::{Rng, SeedableRng};
+    use std::collections::HashMap;
+
+    let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+    let mut map: HashTrieMap<u32, u32> = HashTrieMap::new();
+    let mut std_map = HashMap::new();
+
+    for i in 0..20_000 {
+        let k = rng.gen_range(0..5_000);
+        if rng.gen_bool(0.3) {
+            assert_eq!(map.remove(&k), std_map.remove(&k));
+        } else {
+            assert_eq!(map.insert(k, i), std_map.insert(k, i));
+        }
+        assert_eq!(map.len(), std_map.len());
+    }
+
+    assert_eq!(map.iter().len(), std_map.len());
+    for (k, v) in &map {
+        assert_eq!(std_map.get(k), Some(v));
+    }
+    for (k, v) in &std_map {
+        assert_eq!(map.get(k), Some(v));
+    }
+}
+
+/// hash 가 겹치는 key 들은 collision node 에 들어가고, 지우면 다시 leaf 로 돌아온다.
+#[test]
+fn collision() {
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Collide(u32);
+
+    impl Hash for Collide {
+        fn hash<H: Hasher>(&self, state: &mut H) {
+            (self.0 % 3).hash(state);
+        }
+    }
+
+    let mut map: HashTrieMap<Collide, u32> = (0..30).map(|i| (Collide(i), i)).collect();
+    assert_eq!(map.len(), 30);
+    assert!((0..30).all(|i| map.get(&Collide(i)) == Some(&i)));
+    assert_eq!(map.get(&Collide(30)), None);
+    assert_eq!(map.insert(Collide(4), 40), Some(4));
+
+    for i in (0..30).filter(|i| i % 3 != 0) {
+        assert!(map.remove(&Collide(i)).is_some());
+    }
+    assert_eq!(map.remove(&Collide(1)), None);
+    assert_eq!(map.len(), 10);
+    let mut keys: Vec<u32> = map.keys().map(|k| k.0).collect();
+    keys.sort();
+    assert_eq!(keys, (0..30).step_by(3).collect::<Vec<_>>());
+}
+
+/// 수정해도 clone 해 둔 이전 version 은 그대로이고, ArcK 이면 thread 간에 공유할 수 있다.
+#[test]
+fn persistence() {
+    use super::ArcK;
+    use std::sync::Arc;
+
+    let v1: HashTrieMap<String, usize, ArcK> = (0..1000).map(|i| (i.to_string(), i)).collect();
+    let mut v2 = v1.clone();
+    assert_eq!(v1, v2);
+
+    v2.insert("0".to_string(), 1);
+    v2.remove("1");
+    v2.remove("no such key");
+    assert_eq!(v1.get("0"), Some(&0));
+    assert_eq!(v1.get("1"), Some(&1));
+    assert_eq!(v2.get("0"), Some(&1));
+    assert_eq!(v2.get("1"), None);
+    assert_eq!((v1.len(), v2.len()), (1000, 999));
+    assert_ne!(v1, v2);
+
+    let shared = Arc::new(v1);
+    let handles: Vec<_> = (0..4)
+        .map(|t| {
+            let map = shared.clone();
+            std::thread::spawn(move || (t..1000).step_by(4).all(|i| map[&i.to_string()] == i))
+        })
+        .collect();
+    for h in handles {
+        assert!(h.join().unwrap());
+    }
+}
@@ -0,0 +1,69 @@
+use std::ops::Deref;
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// hash array mapped trie (HAMT) 로 만든 map
+pub mod hamt;
+/// AVL tree 로 만든 정렬된 map
+pub mod ordmap;
+/// 32-way trie + tail 로 만든 vector (Clojure 의 PersistentVector)
+pub mod vector;
+
+pub use hamt::HashTrieMap;
+pub use ordmap::OrdMap;
+pub use vector::Vector;
+
+/// node 를 공유하는 pointer 의 종류 (Rc / Arc)
+///
+/// 한 thread 안에서만 쓰면 RcK (wasm frontend 의 reducer 처럼), thread 간에 보내야 하면 ArcK.
+/// collection 은 `HashTrieMap<K, V, ArcK>` 처럼 pointer 종류를 type parameter 로 받는다.
+pub trait PointerKind {
+    type Pointer<T>: Deref<Target = T> + Clone;
+
+    fn new<T>(v: T) -> Self::Pointer<T>;
+
+    /// 다른 version 과 공유 중이면 복사한 뒤 &mut 를 반환 (copy on write)
+    fn make_mut<T: Clone>(this: &mut Self::Pointer<T>) -> &mut T;
+
+    fn ptr_eq<T>(a: &Self::Pointer<T>, b: &Self::Pointer<T>) -> bool;
+}
+
+/// Rc 로 공유 (Send 가 아님)
+#[derive(Debug)]
+pub enum RcK {}
+
+/// Arc 로 공유 (K, V 가 Send + Sync 이면 collection 도 Send + Sync)
+#[derive(Debug)]
+pub enum ArcK {}
+
+impl PointerKind for RcK {
+    type Pointer<T> = Rc<T>;
+
+    fn new<T>(v: T) -> Rc<T> {
+        Rc::new(v)
+    }
+
+    fn make_mut<T: Clone>(this: &mut Rc<T>) -> &mut T {
+        Rc::make_mut(this)
+    }
+
+    fn ptr_eq<T>(a: &Rc<T>, b: &Rc<T>) -> bool {
+        Rc::ptr_eq(a, b)
+    }
+}
+
+impl PointerKind for ArcK {
+    type Pointer<T> = Arc<T>;
+
+    fn new<T>(v: T) -> Arc<T> {
+        Arc::new(v)
+    }
+
+    fn make_mut<T: Clone>(this: &mut Arc<T>) -> &mut T {
+        Arc::make_mut(this)
+    }
+
+    fn ptr_eq<T>(a: &Arc<T>, b: &Arc<T>) -> bool {
+        Arc::ptr_eq(a, b)
+    }
+}
@@ -0,0 +1,535 @@
+use super::{PointerKind, RcK};
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::fmt;
+use std::iter::FromIterator;
+use std::marker::PhantomData;
+use std::mem;
+use std::ops::{Bound, Index, RangeBounds};
+
+/// AVL tree 로 만든 persistent 정렬 map
+///
+/// - 모든 node 에서 왼쪽 / 오른쪽 subtree 의 높이 차이가 1 이하 -> 높이 O(log n)
+/// - insert / remove 는 root 부터 바뀐 node 까지의 경로만 복사하고, 회전 (rotation) 도 복사한 node 에서만 한다.
+/// - iter / range 는 key 순서로 순회
+pub struct OrdMap<K, V, P: PointerKind = RcK> {
+    len: usize,
+    root: Link<K, V, P>,
+}
+
+type Link<K, V, P> = Option<<P as PointerKind>::Pointer<Node<K, V, P>>>;
+
+struct Node<K, V, P: PointerKind> {
+    key: K,
+    value: V,
+    height: u8,
+    left: Link<K, V, P>,
+    right: Link<K, V, P>,
+}
+
+impl<K: Clone, V: Clone, P: PointerKind> Clone for Node<K, V, P> {
+    fn clone(&self) -> Self {
+        Node {
+            key: self.key.clone(),
+            value: self.value.clone(),
+            height: self.height,
+            left: self.left.clone(),
+            right: self.right.clone(),
+        }
+    }
+}
+
+fn height<K, V, P: PointerKind>(node: Option<&Node<K, V, P>>) -> u8 {
+    node.map_or(0, |n| n.height)
+}
+
+impl<K, V, P: PointerKind> Node<K, V, P> {
+    fn update_height(&mut self) {
+        self.height = 1 + height(self.left.as_deref()).max(height(self.right.as_deref()));
+    }
+
+    /// 오른쪽이 높으면 양수
+    fn balance(&self) -> i16 {
+        height(self.right.as_deref()) as i16 - height(self.left.as_deref()) as i16
+    }
+}
+
+impl<K: Clone, V: Clone, P: PointerKind> Node<K, V, P> {
+    fn leaf(key: K, value: V) -> P::Pointer<Self> {
+        P::new(Node {
+            key,
+            value,
+            height: 1,
+            left: None,
+            right: None,
+        })
+    }
+
+    ///     n            l
+    ///    / \          / \
+    ///   l   c   ->   a   n
+    ///  / \              / \
+    /// a   b            b   c
+    fn rotate_right(link: &mut P::Pointer<Self>) {
+        let n = P::make_mut(link);
+        let mut l = n.left.take().expect("rotate_right without left child");
+        let lm = P::make_mut(&mut l);
+        n.left = lm.right.take();
+        n.update_height();
+        let n = mem::replace(link, l);
+        let l = P::make_mut(link);
+        l.right = Some(n);
+        l.update_height();
+    }
+
+    fn rotate_left(link: &mut P::Pointer<Self>) {
+        let n = P::make_mut(link);
+        let mut r = n.right.take().expect("rotate_left without right child");
+        let rm = P::make_mut(&mut r);
+        n.right = rm.left.take();
+        n.update_height();
+        let n = mem::replace(link, r);
+        let r = P::make_mut(link);
+        r.left = Some(n);
+        r.update_height();
+    }
+
+    /// 자식이 바뀐 뒤 높이를 다시 계산하고, 높이 차이가 2 가 되었으면 회전
+    fn rebalance(link: &mut P::Pointer<Self>) {
+        let n = P::make_mut(link);
+        n.update_height();
+        match n.balance() {
+            2 => {
+                let r = n.right.as_mut().unwrap();
+                if r.balance() < 0 {
+                    Self::rotate_right(r);
+                }
+                Self::rotate_left(link);
+            }
+            -2 => {
+                let l = n.left.as_mut().unwrap();
+                if l.balance() > 0 {
+                    Self::rotate_left(l);
+                }
+                Self::rotate_right(link);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn insert<K: Ord + Clone, V: Clone, P: PointerKind>(
+    link: &mut Link<K, V, P>,
+    key: K,
+    value: V,
+) -> Option<V> {
+    let Some(node) = link else {
+        *link = Some(Node::<K, V, P>::leaf(key, value));
+        return None;
+    };
+    let n = P::make_mut(node);
+    let old = match key.cmp(&n.key) {
+        Ordering::Equal => return Some(mem::replace(&mut n.value, value)),
+        Ordering::Less => insert::<K, V, P>(&mut n.left, key, value),
+        Ordering::Greater => insert::<K, V, P>(&mut n.right, key, value),
+    };
+    Node::<K, V, P>::rebalance(node);
+    old
+}
+
+fn remove<K, V, P, Q>(link: &mut Link<K, V, P>, key: &Q) -> Option<(K, V)>
+where
+    K: Ord + Clone + Borrow<Q>,
+    V: Clone,
+    P: PointerKind,
+    Q: Ord + ?Sized,
+{
+    let node = link.as_mut()?;
+    let n = P::make_mut(node);
+    let removed = match key.cmp(n.key.borrow()) {
+        Ordering::Less => remove::<K, V, P, Q>(&mut n.left, key)?,
+        Ordering::Greater => remove::<K, V, P, Q>(&mut n.right, key)?,
+        Ordering::Equal => match (n.left.is_some(), n.right.is_some()) {
+            (true, true) => {
+                // 오른쪽 subtree 의 가장 작은 node 를 이 자리로
+                let (k, v) = remove_min::<K, V, P>(&mut n.right);
+                (mem::replace(&mut n.key, k), mem::replace(&mut n.value, v))
+            }
+            _ => {
+                // 자식이 하나 이하이면 그 자식이 이 자리로
+                let child = n.left.take().or_else(|| n.right.take());
+                let removed = (n.key.clone(), n.value.clone());
+                *link = child;
+                return Some(removed);
+            }
+        },
+    };
+    Node::<K, V, P>::rebalance(link.as_mut().unwrap());
+    Some(removed)
+}
+
+fn remove_min<K: Clone, V: Clone, P: PointerKind>(link: &mut Link<K, V, P>) -> (K, V) {
+    let node = link.as_mut().expect("remove_min on empty subtree");
+    let n = P::make_mut(node);
+    if n.left.is_none() {
+        let right = n.right.take();
+        let (k, v) = (n.key.clone(), n.value.clone());
+        *link = right;
+        return (k, v);
+    }
+    let min = remove_min::<K, V, P>(&mut n.left);
+    Node::<K, V, P>::rebalance(node);
+    min
+}
+
+impl<K, V, P: PointerKind> OrdMap<K, V, P> {
+    pub fn new() -> Self {
+        OrdMap { len: 0, root: None }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.get_key_value(key).map(|(_, v)| v)
+    }
+
+    pub fn get_key_value<Q>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut link = &self.root;
+        while let Some(n) = link {
+            link = match key.cmp(n.key.borrow()) {
+                Ordering::Equal => return Some((&n.key, &n.value)),
+                Ordering::Less => &n.left,
+                Ordering::Greater => &n.right,
+            };
+        }
+        None
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.get(key).is_some()
+    }
+
+    /// 가장 작은 key
+    pub fn first(&self) -> Option<(&K, &V)> {
+        let mut n = self.root.as_ref()?;
+        while let Some(l) = &n.left {
+            n = l;
+        }
+        Some((&n.key, &n.value))
+    }
+
+    /// 가장 큰 key
+    pub fn last(&self) -> Option<(&K, &V)> {
+        let mut n = self.root.as_ref()?;
+        while let Some(r) = &n.right {
+            n = r;
+        }
+        Some((&n.key, &n.value))
+    }
+
+    pub fn iter(&self) -> Iter<'_, K, V, P> {
+        let mut stack = Vec::new();
+        push_left(&mut stack, self.root.as_deref());
+        Iter {
+            stack,
+            remaining: self.len,
+        }
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &K> + '_ {
+        self.iter().map(|(k, _)| k)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &V> + '_ {
+        self.iter().map(|(_, v)| v)
+    }
+
+    /// range 안의 key 를 순서대로 (BTreeMap::range 와 같음)
+    pub fn range<Q, R>(&self, range: R) -> Range<'_, K, V, P, Q, R>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        // 시작 key 이상인 node 들만 stack 에 넣으면서 내려간다
+        let mut stack = Vec::new();
+        let mut link = &self.root;
+        while let Some(n) = link {
+            let k = n.key.borrow();
+            let after_start = match range.start_bound() {
+                Bound::Included(s) => k >= s,
+                Bound::Excluded(s) => k > s,
+                Bound::Unbounded => true,
+            };
+            if after_start {
+                stack.push(&**n);
+                link = &n.left;
+            } else {
+                link = &n.right;
+            }
+        }
+        Range {
+            stack,
+            range,
+            key: PhantomData,
+        }
+    }
+}
+
+impl<K: Ord + Clone, V: Clone, P: PointerKind> OrdMap<K, V, P> {
+    /// 같은 key 가 있었으면 이전 값을 반환 (BTreeMap::insert 와 같음)
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let old = insert::<K, V, P>(&mut self.root, key, value);
+        if old.is_none() {
+            self.len += 1;
+        }
+        old
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.remove_entry(key).map(|(_, v)| v)
+    }
+
+    pub fn remove_entry<Q>(&mut self, key: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        // 없는 key 이면 경로를 복사하지 않도록 먼저 확인
+        self.get(key)?;
+        let removed = remove::<K, V, P, Q>(&mut self.root, key);
+        if removed.is_some() {
+            self.len -= 1;
+        }
+        removed
+    }
+}
+
+impl<K, V, P: PointerKind> Clone for OrdMap<K, V, P> {
+    fn clone(&self) -> Self {
+        OrdMap {
+            len: self.len,
+            root: self.root.clone(),
+        }
+    }
+}
+
+impl<K, V, P: PointerKind> Default for OrdMap<K, V, P> {
+    fn default() -> Self {
+        OrdMap::new()
+    }
+}
+
+impl<K, Q, V, P> Index<&Q> for OrdMap<K, V, P>
+where
+    K: Borrow<Q>,
+    Q: Ord + ?Sized,
+    P: PointerKind,
+{
+    type Output = V;
+
+    fn index(&self, key: &Q) -> &V {
+        self.get(key).expect("key not found in OrdMap")
+    }
+}
+
+impl<K: PartialEq, V: PartialEq, P: PointerKind> PartialEq for OrdMap<K, V, P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<K: Eq, V: Eq, P: PointerKind> Eq for OrdMap<K, V, P> {}
+
+impl<K: fmt::Debug, V: fmt::Debug, P: PointerKind> fmt::Debug for OrdMap<K, V, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: Ord + Clone, V: Clone, P: PointerKind> FromIterator<(K, V)> for OrdMap<K, V, P> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = OrdMap::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K: Ord + Clone, V: Clone, P: PointerKind> Extend<(K, V)> for OrdMap<K, V, P> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (k, v) in iter {
+            self.insert(k, v);
+        }
+    }
+}
+
+impl<'a, K, V, P: PointerKind> IntoIterator for &'a OrdMap<K, V, P> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V, P>;
+
+    fn into_iter(self) -> Iter<'a, K, V, P> {
+        self.iter()
+    }
+}
+
+/// 아직 방문하지 않은 조상 node 들을 stack 에 두는 in-order 순회
+pub struct Iter<'a, K, V, P: PointerKind> {
+    stack: Vec<&'a Node<K, V, P>>,
+    remaining: usize,
+}
+
+fn push_left<'a, K, V, P: PointerKind>(
+    stack: &mut Vec<&'a Node<K, V, P>>,
+    mut node: Option<&'a Node<K, V, P>>,
+) {
+    while let Some(n) = node {
+        stack.push(n);
+        node = n.left.as_deref();
+    }
+}
+
+impl<'a, K, V, P: PointerKind> Iterator for Iter<'a, K, V, P> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<(&'a K, &'a V)> {
+        let n = self.stack.pop()?;
+        push_left(&mut self.stack, n.right.as_deref());
+        self.remaining -= 1;
+        Some((&n.key, &n.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, K, V, P: PointerKind> ExactSizeIterator for Iter<'a, K, V, P> {}
+
+/// Iter 와 같은 순회를 end bound 를 넘으면 멈춘다.
+pub struct Range<'a, K, V, P: PointerKind, Q: ?Sized, R> {
+    stack: Vec<&'a Node<K, V, P>>,
+    range: R,
+    key: PhantomData<fn(&Q)>,
+}
+
+impl<'a, K, V, P, Q, R> Iterator for Range<'a, K, V, P, Q, R>
+where
+    K: Borrow<Q>,
+    P: PointerKind,
+    Q: Ord + ?Sized,
+    R: RangeBounds<Q>,
+{
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<(&'a K, &'a V)> {
+        let n = self.stack.pop()?;
+        let before_end = match self.range.end_bound() {
+            Bound::Included(e) => n.key.borrow() <= e,
+            Bound::Excluded(e) => n.key.borrow() < e,
+            Bound::Unbounded => true,
+        };
+        if !before_end {
+            self.stack.clear();
+            return None;
+        }
+        push_left(&mut self.stack, n.right.as_deref());
+        Some((&n.key, &n.value))
+    }
+}
+
+#[cfg(test)]
+fn assert_balanced<K: Ord, V, P: PointerKind>(node: Option<&Node<K, V, P>>) -> u8 {
+    let Some(n) = node else { return 0 };
+    let (l, r) = (
+        assert_balanced(n.left.as_deref()),
+        assert_balanced(n.right.as_deref()),
+    );
+    assert!(l.abs_diff(r) <= 1, "unbalanced node");
+    assert_eq!(n.height, 1 + l.max(r));
+    if let Some(left) = &n.left {
+        assert!(left.key < n.key);
+    }
+    if let Some(right) = &n.right {
+        assert!(right.key > n.key);
+    }
+    n.height
+}
+
+/// BTreeMap 과 같은 결과이고, 모든 node 에서 AVL 조건이 유지되는지 무작위 insert / remove 로 확인
+#[test]
+fn against_btree_map() {
+    use rand::{Rng, SeedableRng};
+    use std::collections::BTreeMap;
+
+    let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+    let mut map: OrdMap<u32, u32> = OrdMap::new();
+    let mut std_map = BTreeMap::new();
+
+    for i in 0..20_000 {
+        let k = rng.gen_range(0..2_000);
+        if rng.gen_bool(0.4) {
+            assert_eq!(map.remove(&k), std_map.remove(&k));
+        } else {
+            assert_eq!(map.insert(k, i), std_map.insert(k, i));
+        }
+        assert_eq!(map.len(), std_map.len());
+        if i % 1000 == 0 {
+            assert_balanced(map.root.as_deref());
+        }
+    }
+    assert_balanced(map.root.as_deref());
+
+    assert!(map.iter().eq(std_map.iter()));
+    assert_eq!(map.iter().len(), std_map.len());
+    assert_eq!(map.first(), std_map.iter().next());
+    assert_eq!(map.last(), std_map.iter().next_back());
+    assert!(map.range(100..200).eq(std_map.range(100..200)));
+    assert!(map.range(..=50).eq(std_map.range(..=50)));
+    assert!(map.range(1990..).eq(std_map.range(1990..)));
+}
+
+/// 수정해도 clone 해 둔 이전 version 은 그대로
+#[test]
+fn persistence() {
+    let v1: OrdMap<String, usize> = (0..100).map(|i| (format!("{:03}", i), i)).collect();
+    let mut v2 = v1.clone();
+
+    v2.insert("050".to_string(), 0);
+    v2.remove("000");
+    assert_eq!(v2.remove("no such key"), None);
+    assert_eq!(v1["050"], 50);
+    assert_eq!(v2["050"], 0);
+    assert_eq!(v1.first(), Some((&"000".to_string(), &0)));
+    assert_eq!(v2.first(), Some((&"001".to_string(), &1)));
+    assert_eq!((v1.len(), v2.len()), (100, 99));
+    assert_balanced(v1.root.as_deref());
+    assert_balanced(v2.root.as_deref());
+
+    let keys: Vec<&str> = v1
+        .range::<str, _>((Bound::Excluded("097"), Bound::Unbounded))
+        .map(|(k, _)| k.as_str())
+        .collect();
+    assert_eq!(keys, ["098", "099"]);
+}
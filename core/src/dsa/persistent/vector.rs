@@ -0,0 +1,427 @@
+use super::{PointerKind, RcK};
+use std::fmt;
+use std::iter::FromIterator;
+use std::mem;
+use std::ops::Index;
+
+const BITS: usize = 5;
+const WIDTH: usize = 1 << BITS;
+const MASK: usize = WIDTH - 1;
+
+/// 32-way trie 로 만든 persistent vector
+///
+/// - 원소는 32개씩 leaf 에 들어가고, index 의 5 bit 씩이 각 level 의 child 번호가 된다.
+/// - 마지막 leaf (tail) 는 tree 밖에 두어서 push_back / pop_back 이 대부분 tail 만 건드린다.
+/// - clone 은 pointer 2개 복사 (O(1)), 수정은 root 부터 leaf 까지의 경로만 복사 (O(log32 n))
+///
+/// ```ignore
+/// let v1: Vector<i32> = (0..100).collect();
+/// let mut v2 = v1.clone();
+/// v2.push_back(100);          // v1 은 그대로 100개
+/// ```
+pub struct Vector<T, P: PointerKind = RcK> {
+    len: usize,
+    shift: usize, // root 의 level * BITS
+    root: P::Pointer<Node<T, P>>,
+    tail: P::Pointer<Node<T, P>>, // 항상 Leaf. len > 0 이면 비어있지 않다.
+}
+
+enum Node<T, P: PointerKind> {
+    Branch(Vec<P::Pointer<Node<T, P>>>),
+    Leaf(Vec<T>),
+}
+
+impl<T: Clone, P: PointerKind> Clone for Node<T, P> {
+    fn clone(&self) -> Self {
+        match self {
+            Node::Branch(children) => Node::Branch(children.clone()),
+            Node::Leaf(values) => Node::Leaf(values.clone()),
+        }
+    }
+}
+
+impl<T, P: PointerKind> Node<T, P> {
+    fn children(&self) -> &Vec<P::Pointer<Node<T, P>>> {
+        match self {
+            Node::Branch(children) => children,
+            Node::Leaf(_) => unreachable!("leaf has no children"),
+        }
+    }
+
+    fn children_mut(&mut self) -> &mut Vec<P::Pointer<Node<T, P>>> {
+        match self {
+            Node::Branch(children) => children,
+            Node::Leaf(_) => unreachable!("leaf has no children"),
+        }
+    }
+
+    fn values(&self) -> &Vec<T> {
+        match self {
+            Node::Leaf(values) => values,
+            Node::Branch(_) => unreachable!("branch has no values"),
+        }
+    }
+
+    fn values_mut(&mut self) -> &mut Vec<T> {
+        match self {
+            Node::Leaf(values) => values,
+            Node::Branch(_) => unreachable!("branch has no values"),
+        }
+    }
+}
+
+impl<T, P: PointerKind> Vector<T, P> {
+    pub fn new() -> Self {
+        Vector {
+            len: 0,
+            shift: BITS,
+            root: P::new(Node::Branch(Vec::new())),
+            tail: P::new(Node::Leaf(Vec::new())),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index >= self.len {
+            return None;
+        }
+        self.chunk(index).first()
+    }
+
+    pub fn first(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    pub fn last(&self) -> Option<&T> {
+        self.tail.values().last()
+    }
+
+    pub fn iter(&self) -> Iter<'_, T, P> {
+        Iter {
+            vec: self,
+            index: 0,
+            chunk: [].iter(),
+        }
+    }
+
+    /// tail 앞까지가 tree 에 들어있는 원소
+    fn tail_offset(&self) -> usize {
+        self.len - self.tail.values().len()
+    }
+
+    /// index 가 들어있는 leaf 의 index 부터 끝까지
+    fn chunk(&self, index: usize) -> &[T] {
+        let offset = self.tail_offset();
+        if index >= offset {
+            return &self.tail.values()[index - offset..];
+        }
+        let mut node = &*self.root;
+        let mut level = self.shift;
+        while level > 0 {
+            node = &node.children()[(index >> level) & MASK];
+            level -= BITS;
+        }
+        &node.values()[index & MASK..]
+    }
+}
+
+impl<T: Clone, P: PointerKind> Vector<T, P> {
+    /// index 까지의 경로를 (공유 중이면 복사해서) 수정 가능하게 만든다.
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if index >= self.len {
+            return None;
+        }
+        let offset = self.tail_offset();
+        if index >= offset {
+            return P::make_mut(&mut self.tail)
+                .values_mut()
+                .get_mut(index - offset);
+        }
+        let mut node = P::make_mut(&mut self.root);
+        let mut level = self.shift;
+        while level > 0 {
+            node = P::make_mut(&mut node.children_mut()[(index >> level) & MASK]);
+            level -= BITS;
+        }
+        node.values_mut().get_mut(index & MASK)
+    }
+
+    /// index 의 값을 바꾸고 이전 값을 반환. 범위를 벗어나면 panic
+    pub fn set(&mut self, index: usize, value: T) -> T {
+        let len = self.len;
+        match self.get_mut(index) {
+            Some(slot) => mem::replace(slot, value),
+            None => panic!(
+                "index out of bounds: the len is {} but the index is {}",
+                len, index
+            ),
+        }
+    }
+
+    pub fn push_back(&mut self, value: T) {
+        if self.tail.values().len() == WIDTH {
+            // 가득 찬 tail 을 tree 에 넣고 새 tail 을 시작
+            let full = mem::replace(
+                &mut self.tail,
+                P::new(Node::Leaf(Vec::with_capacity(WIDTH))),
+            );
+            if (self.len >> BITS) > (1 << self.shift) {
+                // root 아래가 가득 찼으면 한 level 위에 새 root
+                let old = mem::replace(&mut self.root, P::new(Node::Branch(Vec::new())));
+                let path = Self::new_path(self.shift, full);
+                *P::make_mut(&mut self.root).children_mut() = vec![old, path];
+                self.shift += BITS;
+            } else {
+                Self::push_tail(P::make_mut(&mut self.root), self.shift, self.len - 1, full);
+            }
+        }
+        P::make_mut(&mut self.tail).values_mut().push(value);
+        self.len += 1;
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        let value = P::make_mut(&mut self.tail).values_mut().pop();
+        self.len -= 1;
+
+        if self.tail.values().is_empty() && self.len > 0 {
+            // tree 의 마지막 leaf 를 꺼내서 tail 로
+            self.tail = Self::pop_tail(P::make_mut(&mut self.root), self.shift);
+            if self.shift > BITS && self.root.children().len() == 1 {
+                self.root = self.root.children()[0].clone();
+                self.shift -= BITS;
+            }
+        }
+        value
+    }
+
+    fn new_path(level: usize, node: P::Pointer<Node<T, P>>) -> P::Pointer<Node<T, P>> {
+        if level == 0 {
+            node
+        } else {
+            P::new(Node::Branch(vec![Self::new_path(level - BITS, node)]))
+        }
+    }
+
+    /// last 는 leaf 에 들어갈 마지막 원소의 index
+    fn push_tail(node: &mut Node<T, P>, level: usize, last: usize, leaf: P::Pointer<Node<T, P>>) {
+        let children = node.children_mut();
+        let sub = (last >> level) & MASK;
+        if level == BITS {
+            children.push(leaf);
+        } else if sub < children.len() {
+            Self::push_tail(P::make_mut(&mut children[sub]), level - BITS, last, leaf);
+        } else {
+            children.push(Self::new_path(level - BITS, leaf));
+        }
+    }
+
+    fn pop_tail(node: &mut Node<T, P>, level: usize) -> P::Pointer<Node<T, P>> {
+        let children = node.children_mut();
+        if level == BITS {
+            return children.pop().unwrap();
+        }
+        let last = children.last_mut().unwrap();
+        let leaf = Self::pop_tail(P::make_mut(last), level - BITS);
+        if last.children().is_empty() {
+            children.pop();
+        }
+        leaf
+    }
+}
+
+impl<T, P: PointerKind> Clone for Vector<T, P> {
+    fn clone(&self) -> Self {
+        Vector {
+            len: self.len,
+            shift: self.shift,
+            root: self.root.clone(),
+            tail: self.tail.clone(),
+        }
+    }
+}
+
+impl<T, P: PointerKind> Default for Vector<T, P> {
+    fn default() -> Self {
+        Vector::new()
+    }
+}
+
+impl<T, P: PointerKind> Index<usize> for Vector<T, P> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        match self.get(index) {
+            Some(v) => v,
+            None => panic!(
+                "index out of bounds: the len is {} but the index is {}",
+                self.len, index
+            ),
+        }
+    }
+}
+
+impl<T: PartialEq, P: PointerKind> PartialEq for Vector<T, P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq, P: PointerKind> Eq for Vector<T, P> {}
+
+impl<T: fmt::Debug, P: PointerKind> fmt::Debug for Vector<T, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: Clone, P: PointerKind> FromIterator<T> for Vector<T, P> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut v = Vector::new();
+        v.extend(iter);
+        v
+    }
+}
+
+impl<T: Clone, P: PointerKind> Extend<T> for Vector<T, P> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.push_back(value);
+        }
+    }
+}
+
+impl<'a, T, P: PointerKind> IntoIterator for &'a Vector<T, P> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T, P>;
+
+    fn into_iter(self) -> Iter<'a, T, P> {
+        self.iter()
+    }
+}
+
+/// leaf 단위로 slice iterator 를 이어서 순회
+pub struct Iter<'a, T, P: PointerKind> {
+    vec: &'a Vector<T, P>,
+    index: usize, // 다음 leaf 의 시작 index
+    chunk: std::slice::Iter<'a, T>,
+}
+
+impl<'a, T, P: PointerKind> Iterator for Iter<'a, T, P> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        if let Some(v) = self.chunk.next() {
+            return Some(v);
+        }
+        if self.index >= self.vec.len {
+            return None;
+        }
+        let chunk = self.vec.chunk(self.index);
+        self.index += chunk.len();
+        self.chunk = chunk.iter();
+        self.chunk.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = self.vec.len - self.index + self.chunk.len();
+        (n, Some(n))
+    }
+}
+
+impl<'a, T, P: PointerKind> ExactSizeIterator for Iter<'a, T, P> {}
+
+/// Vec 과 같은 결과인지 여러 level (32, 32^2, 32^3 경계) 에 걸쳐 확인
+#[test]
+fn push_pop() {
+    const N: usize = 40_000;
+
+    let mut v: Vector<usize> = Vector::new();
+    for i in 0..N {
+        v.push_back(i);
+        assert_eq!(v.last(), Some(&i));
+    }
+    assert_eq!(v.len(), N);
+    assert!((0..N).all(|i| v[i] == i));
+    assert!(v.iter().copied().eq(0..N));
+    assert_eq!(v.iter().len(), N);
+    assert_eq!(v.get(N), None);
+
+    for i in (0..N).rev() {
+        assert_eq!(v.pop_back(), Some(i));
+        assert_eq!(v.len(), i);
+        if i % 997 == 0 {
+            assert!(v.iter().copied().eq(0..i));
+        }
+    }
+    assert_eq!(v.pop_back(), None);
+    assert!(v.is_empty());
+}
+
+/// 수정해도 clone 해 둔 이전 version 은 바뀌지 않는다.
+#[test]
+fn structural_sharing() {
+    let v1: Vector<usize> = (0..1000).collect();
+    let mut v2 = v1.clone();
+
+    v2.set(10, 0);
+    *v2.get_mut(999).unwrap() = 0;
+    v2.push_back(1000);
+    assert_eq!(v1[10], 10);
+    assert_eq!(v1[999], 999);
+    assert_eq!(v1.len(), 1000);
+    assert_eq!(v2[10], 0);
+    assert_eq!(v2[999], 0);
+
+    // 바꾸지 않은 leaf 는 같은 메모리를 가리킨다
+    assert!(std::ptr::eq(&v1[500], &v2[500]));
+    assert!(!std::ptr::eq(&v1[10], &v2[10]));
+
+    let mut v3 = v2.clone();
+    while v3.len() > 10 {
+        v3.pop_back();
+    }
+    assert_eq!(v2.len(), 1001);
+    assert_eq!(v3, (0..10).collect());
+}
+
+/// webassembly 의 AppList reducer : 매 action 마다 Vec<String> 전체를 clone 하는 대신
+/// 이전 state 를 clone (O(1)) 해서 뒤에 4개를 붙인다.
+#[test]
+fn app_list() {
+    use super::ArcK;
+
+    fn concatenate(list: &Vector<String, ArcK>) -> Vector<String, ArcK> {
+        let mut current = list.clone();
+        let index = list.len();
+        current.extend((index + 1..index + 5).map(|i| i.to_string()));
+        current
+    }
+
+    let mut states = vec![Vector::new()];
+    for _ in 0..100 {
+        let next = concatenate(states.last().unwrap());
+        states.push(next);
+    }
+
+    assert_eq!(
+        states[1],
+        ["1", "2", "3", "4"].iter().map(|s| s.to_string()).collect()
+    );
+    assert_eq!(states[100].len(), 400);
+    assert_eq!(states[50].len(), 200);
+
+    // ArcK 이면 다른 thread 로 보낼 수 있다
+    let last = states.pop().unwrap();
+    let t = std::thread::spawn(move || last.iter().filter(|s| s.len() == 3).count());
+    assert!(t.join().unwrap() > 0);
+}
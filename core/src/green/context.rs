@@ -0,0 +1,79 @@
+use std::arch::global_asm;
+
+/// context switching 시 저장 / 복원하는 register
+///
+/// System V AMD64 ABI 에서 함수 호출 전후로 보존되어야 하는 (callee-saved) register 만 저장한다.
+/// 나머지 register 는 switch_context 를 호출하는 쪽 (compiler) 이 이미 저장해 두므로 필요 없다.
+#[repr(C)]
+#[derive(Debug, Default)]
+pub struct Registers {
+    rbx: u64,
+    rbp: u64,
+    r12: u64,
+    r13: u64,
+    r14: u64,
+    r15: u64,
+    rsp: u64,
+}
+
+impl Registers {
+    /// 새 stack 에서 처음 switch 되면 entry 부터 실행되도록 만든다.
+    ///
+    /// switch_context 는 마지막에 ret 하므로 stack 의 맨 위에 entry 의 주소를 넣어두면 그곳으로 점프한다.
+    /// 함수 진입 시점의 rsp 는 16 byte 정렬 + 8 이어야 하므로 (call 이 return address 를 push 한 상태)
+    /// 16 byte 정렬된 위치에 entry 주소를 둔다.
+    ///
+    /// # Safety
+    /// stack_top 은 쓸 수 있는 stack 영역의 끝 (가장 높은 주소) 이어야 하고, 그 아래로 최소 32 byte 를 쓸 수 있어야 한다.
+    /// 반환된 context 로 전환하는 동안 그 stack 이 살아있어야 한다. (Stack::top 이 이 조건을 만족한다)
+    pub unsafe fn new(stack_top: *mut u8, entry: extern "C" fn() -> !) -> Self {
+        let top = (stack_top as usize & !15) - 16;
+        unsafe {
+            (top as *mut u64).write(entry as usize as u64);
+        }
+        Registers {
+            rsp: top as u64,
+            ..Registers::default()
+        }
+    }
+}
+
+global_asm!(
+    ".text",
+    ".global crested_green_switch_context",
+    "crested_green_switch_context:",
+    // 현재 register 를 rdi (from) 에 저장
+    "mov [rdi + 0x00], rbx",
+    "mov [rdi + 0x08], rbp",
+    "mov [rdi + 0x10], r12",
+    "mov [rdi + 0x18], r13",
+    "mov [rdi + 0x20], r14",
+    "mov [rdi + 0x28], r15",
+    "mov [rdi + 0x30], rsp",
+    // rsi (to) 의 register 를 복원
+    "mov rbx, [rsi + 0x00]",
+    "mov rbp, [rsi + 0x08]",
+    "mov r12, [rsi + 0x10]",
+    "mov r13, [rsi + 0x18]",
+    "mov r14, [rsi + 0x20]",
+    "mov r15, [rsi + 0x28]",
+    "mov rsp, [rsi + 0x30]",
+    // to 의 stack 에 있는 return address 로 점프
+    "ret",
+);
+
+extern "C" {
+    #[link_name = "crested_green_switch_context"]
+    fn switch_context_asm(from: *mut Registers, to: *const Registers);
+}
+
+/// 현재 context 를 from 에 저장하고 to 로 전환
+///
+/// 나중에 누군가 from 으로 전환하면 이 함수에서 return 한다.
+///
+/// # Safety
+/// to 는 Registers::new 로 만들었거나 switch_context 로 저장된 context 이어야 하고,
+/// 그 stack 이 아직 살아있어야 한다.
+pub unsafe fn switch_context(from: *mut Registers, to: *const Registers) {
+    switch_context_asm(from, to);
+}
@@ -0,0 +1,325 @@
+use std::any::Any;
+use std::cell::Cell;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::ops::ControlFlow;
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+use self::context::{switch_context, Registers};
+use self::stack::Stack;
+use crate::scope;
+
+/// context switching (x86_64 register 저장 / 복원)
+pub mod context;
+/// guard page 가 있는 stack
+pub mod stack;
+
+/// 기본 stack 크기
+pub const DEFAULT_STACK_SIZE: usize = 64 * 1024;
+
+/// ch06 6.2 협조적 green thread
+///
+/// green thread 는 OS 가 아닌 이 module 의 scheduler 가 관리하는 thread 이다.
+/// 각 green thread 는 자신의 stack 을 가지고, schedule() 을 호출할 때만 (cooperative) 다음 thread 로 전환된다.
+/// scheduler 는 실행 가능한 thread 를 queue 에 넣고 앞에서부터 순서대로 실행한다. (round-robin)
+///
+/// ch05 의 future 와 비교하면
+/// - future 는 poll 에서 return 해야 다른 task 로 넘어가지만 (stackless),
+///   green thread 는 함수 호출 도중 어디서든 schedule() 로 넘어갈 수 있다 (stackful)
+/// - 대신 thread 마다 stack 을 따로 할당해야 한다
+///
+/// scheduler 는 OS thread 마다 따로 있으며 spawn_from_main 이 실행 중인 동안만 존재한다.
+struct Scheduler {
+    main: Registers,                     // spawn_from_main 을 호출한 (green thread 가 아닌) context
+    run_queue: VecDeque<Box<Context>>,   // 맨 앞이 실행 중인 thread
+    waiting: HashMap<u64, Box<Context>>, // recv 에서 message 를 기다리는 thread
+    mailboxes: HashMap<u64, VecDeque<u64>>,
+    alive: HashSet<u64>,
+    finished: Vec<Context>, // 끝났지만 아직 그 stack 위에서 실행 중일 수 있어 해제를 미룬 thread
+    next_id: u64,
+    panic: Option<Box<dyn Any + Send>>, // green thread 에서 발생한 첫번째 panic
+}
+
+struct Context {
+    id: u64,
+    regs: Registers,
+    entry: Option<Box<dyn FnOnce()>>,
+    _stack: Stack,
+}
+
+thread_local! {
+    static SCHEDULER: Cell<*mut Scheduler> = const { Cell::new(ptr::null_mut()) };
+}
+
+// 현재 OS thread 의 scheduler 를 f 안에서만 빌려준다.
+// 빌려주는 동안 thread local 을 비워두므로, f 안에서 다시 with_scheduler 를 호출하면 같은 scheduler 에 대한
+// &mut 가 둘이 되는 대신 panic 한다. switch_context 는 f 가 return 한 뒤에 호출한다. (전환된 thread 가 같은 scheduler 를 사용)
+fn with_scheduler<R>(f: impl FnOnce(&mut Scheduler) -> R) -> R {
+    let s = SCHEDULER.with(|s| s.replace(ptr::null_mut()));
+    assert!(
+        !s.is_null(),
+        "must be called from a green thread (inside spawn_from_main) and not while the scheduler is borrowed"
+    );
+    let _restore = scope::guard(s, |s| SCHEDULER.with(|cell| cell.set(s)));
+    unsafe { f(&mut *s) }
+}
+
+// 새 green thread 는 여기서 시작한다.
+extern "C" fn entry() -> ! {
+    let f = with_scheduler(|s| s.run_queue.front_mut().unwrap().entry.take().unwrap());
+
+    // panic 이 stack 의 바닥을 넘어 unwind 하면 안 되므로 여기서 잡는다
+    if let Err(e) = panic::catch_unwind(AssertUnwindSafe(f)) {
+        with_scheduler(|s| {
+            s.panic.get_or_insert(e);
+        });
+    }
+
+    let (from, to) = with_scheduler(|s| {
+        let ctx = s.run_queue.pop_front().unwrap();
+        s.alive.remove(&ctx.id);
+        s.mailboxes.remove(&ctx.id);
+        // 저장한 register 는 다시 사용되지 않는다 (이 thread 로 돌아오지 않음)
+        s.finished.push(*ctx);
+        let from: *mut Registers = &mut s.finished.last_mut().unwrap().regs;
+        (from, s.next_regs())
+    });
+
+    unsafe { switch_context(from, to) };
+    unreachable!("finished green thread was resumed");
+}
+
+impl Scheduler {
+    // 다음에 실행할 context. 실행 가능한 thread 가 없으면 main 으로 돌아간다.
+    fn next_regs(&mut self) -> *const Registers {
+        match self.run_queue.front() {
+            Some(ctx) => &ctx.regs,
+            None => &self.main,
+        }
+    }
+
+    // 지금 실행 중인 stack 이 아니므로 해제해도 된다.
+    // (finished 에 넣은 thread 는 이미 다른 context 로 전환된 뒤에만 여기에 도달)
+    fn free_finished(&mut self) {
+        self.finished.clear();
+    }
+}
+
+/// 현재 OS thread 를 main context 로 하여 f 를 green thread 로 실행한다.
+/// f 와 f 에서 spawn 한 green thread 가 모두 끝나면 반환한다.
+///
+/// green thread 에서 panic 이 발생하면 나머지 thread 를 끝까지 실행한 뒤 여기서 다시 panic 한다.
+/// 모든 thread 가 recv 에서 대기하면 (deadlock) panic 한다.
+pub fn spawn_from_main<F: FnOnce() + 'static>(f: F, stack_size: usize) {
+    SCHEDULER.with(|s| assert!(s.get().is_null(), "spawn_from_main can not be nested"));
+
+    let mut sched = Box::new(Scheduler {
+        main: Registers::default(),
+        run_queue: VecDeque::new(),
+        waiting: HashMap::new(),
+        mailboxes: HashMap::new(),
+        alive: HashSet::new(),
+        finished: Vec::new(),
+        next_id: 1,
+        panic: None,
+    });
+    SCHEDULER.with(|s| s.set(&mut *sched));
+    // panic 하더라도 scheduler 를 해제하기 전에 thread local 을 지운다
+    let _reset = scope::guard((), |()| SCHEDULER.with(|s| s.set(ptr::null_mut())));
+
+    spawn(f, stack_size);
+    let (main, to) = with_scheduler(|s| {
+        let main: *mut Registers = &mut s.main;
+        (main, s.next_regs())
+    });
+    unsafe { switch_context(main, to) };
+
+    // 실행 가능한 thread 가 모두 없어져서 돌아옴
+    let (panic, waiting) = with_scheduler(|s| {
+        s.free_finished();
+        (s.panic.take(), s.waiting.len())
+    });
+    if let Some(e) = panic {
+        panic::resume_unwind(e);
+    }
+    if waiting > 0 {
+        panic!("deadlock: {} green threads are waiting in recv", waiting);
+    }
+}
+
+/// 새 green thread 를 만들어 실행 queue 의 뒤에 넣고 id 를 반환한다.
+/// 현재 thread 는 계속 실행되며 다음 schedule() 이후에 실행된다.
+pub fn spawn<F: FnOnce() + 'static>(f: F, stack_size: usize) -> u64 {
+    let stack = Stack::new(stack_size).expect("failed to allocate green thread stack");
+    // stack 은 Context 가 소유하므로 이 context 로 전환하는 동안 살아있다
+    let regs = unsafe { Registers::new(stack.top(), entry) };
+    with_scheduler(|s| {
+        s.free_finished();
+        let id = s.next_id;
+        s.next_id += 1;
+        s.alive.insert(id);
+        s.run_queue.push_back(Box::new(Context {
+            id,
+            regs,
+            entry: Some(Box::new(f)),
+            _stack: stack,
+        }));
+        id
+    })
+}
+
+/// 실행을 양보하고 queue 의 다음 thread 로 전환 (현재 thread 는 queue 의 맨 뒤로)
+pub fn schedule() {
+    let switch = with_scheduler(|s| {
+        s.free_finished();
+        if s.run_queue.len() <= 1 {
+            return None;
+        }
+
+        let ctx = s.run_queue.pop_front().unwrap();
+        s.run_queue.push_back(ctx);
+        let from: *mut Registers = &mut s.run_queue.back_mut().unwrap().regs;
+        Some((from, s.next_regs()))
+    });
+    if let Some((from, to)) = switch {
+        unsafe { switch_context(from, to) };
+    }
+}
+
+/// 현재 green thread 의 id
+pub fn current_id() -> u64 {
+    with_scheduler(|s| s.run_queue.front().expect("no running green thread").id)
+}
+
+/// id 의 green thread 에게 message 를 보낸다. (6.3 actor model 의 send)
+/// 받는 thread 가 recv 에서 대기 중이면 실행 queue 로 옮긴다.
+/// 받는 thread 가 이미 끝났으면 false
+pub fn send(id: u64, msg: u64) -> bool {
+    with_scheduler(|s| {
+        if !s.alive.contains(&id) {
+            return false;
+        }
+        s.mailboxes.entry(id).or_default().push_back(msg);
+        if let Some(ctx) = s.waiting.remove(&id) {
+            s.run_queue.push_back(ctx);
+        }
+        true
+    })
+}
+
+/// 자신에게 온 message 를 받는다. 없으면 올 때까지 실행 queue 에서 빠져서 대기
+pub fn recv() -> u64 {
+    loop {
+        let id = current_id();
+        let wait = with_scheduler(|s| {
+            s.free_finished();
+            if let Some(msg) = s.mailboxes.get_mut(&id).and_then(|m| m.pop_front()) {
+                return ControlFlow::Break(msg);
+            }
+
+            let ctx = s.run_queue.pop_front().unwrap();
+            let from: *mut Registers = &mut s.waiting.entry(id).or_insert(ctx).regs;
+            ControlFlow::Continue((from, s.next_regs()))
+        });
+        match wait {
+            ControlFlow::Continue((from, to)) => unsafe { switch_context(from, to) },
+            ControlFlow::Break(msg) => return msg,
+        }
+    }
+}
+
+/// round-robin : 각 thread 가 schedule() 할 때마다 다음 thread 로 돌아가며 실행된다.
+#[test]
+fn round_robin() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    let log = Rc::new(RefCell::new(Vec::new()));
+    let l = log.clone();
+    spawn_from_main(
+        move || {
+            for name in ["a", "b", "c"] {
+                let l = l.clone();
+                spawn(
+                    move || {
+                        for i in 0..3 {
+                            l.borrow_mut().push(format!("{}{}", name, i));
+                            schedule();
+                        }
+                    },
+                    DEFAULT_STACK_SIZE,
+                );
+            }
+        },
+        DEFAULT_STACK_SIZE,
+    );
+
+    assert_eq!(
+        *log.borrow(),
+        vec!["a0", "b0", "c0", "a1", "b1", "c1", "a2", "b2", "c2"]
+    );
+}
+
+/// green thread 수천개가 ring 으로 연결되어 값을 다음 thread 에 전달한다.
+#[test]
+fn ring() {
+    use std::rc::Rc;
+
+    const NUM_THREADS: u64 = 2_000;
+    const NUM_ROUNDS: u64 = 10;
+
+    let result = Rc::new(Cell::new(0));
+    let r = result.clone();
+    spawn_from_main(
+        move || {
+            // thread i 는 받은 값에 1 을 더해서 thread i + 1 에게 보낸다. 마지막은 처음 thread 에게
+            let first = current_id() + 1;
+            for i in 0..NUM_THREADS {
+                let next = if i + 1 == NUM_THREADS { first } else { first + i + 1 };
+                let r = r.clone();
+                spawn(
+                    move || {
+                        for round in 0..NUM_ROUNDS {
+                            if i == 0 && round == 0 {
+                                send(next, 1);
+                            }
+                            let v = recv();
+                            if i == 0 && round + 1 == NUM_ROUNDS {
+                                r.set(v);
+                            } else {
+                                send(next, v + 1);
+                            }
+                        }
+                    },
+                    16 * 1024,
+                );
+            }
+        },
+        DEFAULT_STACK_SIZE,
+    );
+
+    assert_eq!(result.get(), NUM_THREADS * NUM_ROUNDS);
+}
+
+#[test]
+#[should_panic(expected = "deadlock")]
+fn deadlock() {
+    spawn_from_main(
+        || {
+            recv();
+        },
+        DEFAULT_STACK_SIZE,
+    );
+}
+
+#[test]
+#[should_panic(expected = "boom")]
+fn panic_in_green_thread() {
+    spawn_from_main(
+        || {
+            spawn(|| panic!("boom"), DEFAULT_STACK_SIZE);
+            schedule();
+        },
+        DEFAULT_STACK_SIZE,
+    );
+}
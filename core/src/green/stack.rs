@@ -0,0 +1,81 @@
+use std::io;
+use std::ptr::NonNull;
+
+/// green thread 용 stack
+///
+/// mmap 으로 할당하고 가장 아래 page 를 PROT_NONE (guard page) 로 만들어 둔다.
+/// stack 은 높은 주소에서 낮은 주소로 자라므로, 넘치면 guard page 에 접근해서
+/// 다른 메모리를 조용히 덮어쓰는 대신 SIGSEGV 로 바로 죽는다.
+pub struct Stack {
+    base: NonNull<u8>, // guard page 를 포함한 영역의 시작 (가장 낮은 주소)
+    len: usize,        // guard page 를 포함한 크기
+}
+
+fn page_size() -> usize {
+    unsafe { libc::sysconf(libc::_SC_PAGESIZE) as usize }
+}
+
+impl Stack {
+    /// size 는 page 단위로 올림하고, 그 아래에 guard page 하나를 더 붙인다.
+    pub fn new(size: usize) -> io::Result<Self> {
+        let page = page_size();
+        let len = size.max(1).div_ceil(page) * page + page;
+
+        unsafe {
+            let ptr = libc::mmap(
+                std::ptr::null_mut(),
+                len,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_PRIVATE | libc::MAP_ANONYMOUS | libc::MAP_NORESERVE | libc::MAP_STACK,
+                -1,
+                0,
+            );
+            if ptr == libc::MAP_FAILED {
+                return Err(io::Error::last_os_error());
+            }
+            if libc::mprotect(ptr, page, libc::PROT_NONE) != 0 {
+                let err = io::Error::last_os_error();
+                libc::munmap(ptr, len);
+                return Err(err);
+            }
+
+            Ok(Stack {
+                base: NonNull::new_unchecked(ptr as *mut u8),
+                len,
+            })
+        }
+    }
+
+    /// stack 의 가장 높은 주소 (여기서부터 아래로 사용)
+    pub fn top(&self) -> *mut u8 {
+        unsafe { self.base.as_ptr().add(self.len) }
+    }
+
+    /// guard page 를 제외하고 사용할 수 있는 크기
+    pub fn usable_size(&self) -> usize {
+        self.len - page_size()
+    }
+}
+
+impl Drop for Stack {
+    fn drop(&mut self) {
+        unsafe {
+            libc::munmap(self.base.as_ptr() as *mut libc::c_void, self.len);
+        }
+    }
+}
+
+#[test]
+fn guard_page() {
+    let page = page_size();
+    let stack = Stack::new(page * 2 + 1).unwrap();
+    assert_eq!(stack.usable_size(), page * 3);
+
+    // guard page 위쪽은 읽고 쓸 수 있다
+    unsafe {
+        let bottom = stack.top().sub(stack.usable_size());
+        bottom.write(1);
+        stack.top().sub(1).write(2);
+        assert_eq!(bottom.read() + stack.top().sub(1).read(), 3);
+    }
+}
@@ -157,6 +157,7 @@ fn p124plus () {
     // 2. wait_timeout 이 1초로 설정된 t_child 1000 는 parent 가 오기전에 시간이 다 경과했으므로 작업을 진행한다.
     // 3. 2초가 지나면 parent 가 실행된다.
     // 4. parent 가 condvar 을 true 로 변경하였으므로, t_child 5000 은 parent 가 끝나고 바로 실행된다.  
+    // -> 여러 thread 의 완료를 기다리는 (count_down / await_timeout) 버전은 crate::sync::CountDownLatch
 }
 
 /// 지금까지 읽기, 쓰기 작업은 모두 lock 권한을 얻어야 작업이 가능하다. 
@@ -184,6 +185,7 @@ fn p126() {
         *w1 = 7;
         println!("w1 = {}", w1);
     }
+    // -> writer 우선 / upgradable read 를 지원하는 구현은 crate::sync::RwLock (작은 설정값은 crate::sync::SeqLock)
 }
 
 /// 3.8.4 Barrier synchronization
@@ -228,6 +230,7 @@ fn p127() {
     for th in v {
         th.join().unwrap();
     } 
+    // -> 재사용 가능하고 leader 가 barrier action 을 실행하는 버전은 crate::sync::Barrier (참가자가 바뀌면 crate::sync::Phaser)
 }
 
 /// 3.8.5 semaphore (Rust 에서 표준으로 제공하고 있지 않으므로 자료 구조 작성해야)
@@ -251,6 +254,7 @@ fn p128() {
     use std::thread;
 
     // 세마포어용 타입 (기존 Condvar 구조에 max 추가)
+    // -> wait/post 를 permit guard 로 묶은 버전은 crate::sync::Semaphore
     pub struct Semaphore {
         mutex: Mutex<isize>,
         cond: Condvar,
@@ -291,6 +295,7 @@ fn p128() {
     // test_code();
     #[allow(dead_code)]
     fn test_code() {
+        // -> model-check feature 로 모든 interleaving 을 검사하는 버전은 crate::sync::semaphore 의 model_p128
         use std::sync::atomic::{AtomicUsize, Ordering}; // memory ordering : https://int-i.github.io/rust/2022-01-15/memory-ordering/
         use std::sync::Arc;
     
@@ -467,6 +472,7 @@ fn p136() {
     }
 
     // bakery algorithm's type
+    // -> &self 로 공유 가능하고 참가자를 동적으로 등록하는 버전은 crate::sync::fair::BakeryLock
     struct BakeryLock {
         entering: [bool; NUM_THREADS],          // i 번째 thread 가 ticket 을 취득 중(processing)이면 entering[i] == true
         tickets: [Option<u64>; NUM_THREADS],    // i 번째 thread 의 ticket 은 ticket[i]
@@ -219,6 +219,8 @@ fn p152() {
 ///  x86-64                                               O
 ///  RISC-V(WMO)      O           O           O           O     (WMO: Weak Memory Ordering)
 ///  RISC-V(TSO)                                          O     (TSO: Total Store Ordering)
+///
+/// -> 표의 reordering 이 실제로 일어나는지는 crate::litmus 로 확인 (SB, MP, LB, IRIW)
 
 /// 하지만 사용자의 의도에 따라서 reordering 을 막고 순차적으로 처리되는 것도 필요하다. 
 /// 이를 지원하기 위해 barrier 의 memory barrier (memory fence) 개념을 사용할 수 있다.
@@ -268,6 +270,7 @@ fn p172() {
     const NUM_LOOP: usize = 100_000;
 
     // spin lock 용 type
+    // -> backoff 와 경합 통계가 추가된 버전은 crate::sync::SpinLock
     struct SpinLock<T> {
         lock: AtomicBool,       // lock 용 공유 변수
         data: UnsafeCell<T>,    // 보호 대상 데이터 -> 여러 thread 에서 mutable 접근을 허용할 것이므로 UnsafeCell type
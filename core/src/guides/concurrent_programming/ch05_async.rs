@@ -236,6 +236,54 @@ fn p186() {
     hello.as_mut().poll(&mut ctx);
 }
 
+/// -- 앞에서 예시된 비동기로 task 를 실행하는 Hello, World (polling) 함수 구현 --
+/// 자기 자신을 wake 하므로 executor 가 다시 poll 해야 끝난다. (p189, crate::runtime 의 test 에서 사용)
+pub struct Hello {
+    state: StateHello,
+}
+
+pub enum StateHello {
+    HELLO,
+    WORLD,
+    END,
+}
+
+impl Hello {
+    pub fn new() -> Self {
+        Hello {state: StateHello::HELLO,} // 초기 상태
+    }
+}
+
+impl Default for Hello {
+    fn default() -> Self {
+        Hello::new()
+    }
+}
+
+impl std::future::Future for Hello {
+    type Output = ();
+
+    fn poll(mut self: std::pin::Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> std::task::Poll<()> {
+        match (*self).state {
+            StateHello::HELLO => {
+                print!("Hello, ");
+                (*self).state = StateHello::WORLD;
+                cx.waker().wake_by_ref();   // 자신을 실행 큐에 넣음
+                std::task::Poll::Pending   
+            }
+            StateHello::WORLD => {
+                print!("World!");
+                (*self).state = StateHello::END;
+                cx.waker().wake_by_ref();   // 자신을 실행 큐에 넣음
+                std::task::Poll::Pending   
+            }
+            StateHello::END => {
+                std::task::Poll::Ready(()) // 종료
+            }
+        }
+    }
+}
+
 /// 5.2.2 scheduling
 /// 
 /// Task    : scheduling 의 대상이 되는 계산의 실행단위 (단위 작업으로써의 Process 개념과 동일?)
@@ -264,10 +312,9 @@ fn p189() {
     use futures::future::{BoxFuture, FutureExt};
     use futures::task::{waker_ref, ArcWake};
     use std::future::Future;
-    use std::pin::Pin;
     use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
     use std::sync::{Arc, Mutex};
-    use std::task::{Context, Poll};
+    use std::task::Context;
 
     // -- Task type --
     struct Task{
@@ -351,51 +398,12 @@ fn p189() {
         }
     }
 
-    // -- 앞에서 예시된 비동기로 task 를 실행하는 Hello, World (polling) 함수 구현 --
-    struct Hello {
-        state: StateHello,
-    }
-
-    enum StateHello {
-        HELLO,
-        WORLD,
-        END,
-    }
-
-    impl Hello {
-        fn new() -> Self {
-            Hello {state: StateHello::HELLO,} // 초기 상태
-        }
-    }
-
-    impl Future for Hello {
-        type Output = ();
-
-        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
-            match (*self).state {
-                StateHello::HELLO => {
-                    print!("Hello, ");
-                    (*self).state = StateHello::WORLD;
-                    cx.waker().wake_by_ref();   // 자신을 실행 큐에 넣음
-                    Poll::Pending   
-                }
-                StateHello::WORLD => {
-                    print!("World!");
-                    (*self).state = StateHello::END;
-                    cx.waker().wake_by_ref();   // 자신을 실행 큐에 넣음
-                    Poll::Pending   
-                }
-                StateHello::END => {
-                    Poll::Ready(()) // 종료
-                }
-            }
-        }
-    }
-    
     // -- fn main --
     // let executor = Executor::new();
     // executor.get_spawner().spawn(Hello::new());
     // executor.run();
+    // -> 모든 task 가 끝나면 반환하고 JoinHandle / sleep 이 추가된 버전은 crate::runtime::LocalExecutor
+    // -> 같은 get_spawner / run 으로 여러 thread 에서 실행하는 버전은 crate::runtime::Runtime
 
     // 5.3.1 Future async/await
     // Future : coroutine 으로 구현. 다만 기존 coroutine 의 의미가 '중단, 재개가능 함수' 에서
@@ -1,7 +1,7 @@
 /// ch06 멀티태스크
 ///
 /// - green thread 를 Rust 언어를 사용하여 mutitasking
-/// - 간단한 actor model 구현
+/// - 간단한 actor model 구현 (-> crate::actor)
 /// 
 /// 6.1 Mutlitask 
 /// context : register (또는 stack 정보) 등의 프로세스 상태에 관현 정보
@@ -38,6 +38,7 @@
 
 /// 이후 내용은 아직 지식이 부족해서..;; 추후에 다시 학습 후 정리.. 
 /// 6.2 협조적 green thread 구현
+/// -> 구현은 crate::green (x86_64 linux 전용, spawn_from_main / spawn / schedule / send / recv)
 ///  
 
 /// -------------------------------------------------
@@ -221,6 +221,7 @@ pub fn example_9_inner(s: &mut Box<i32>) {
     std::mem::swap(s, &mut r);
     assert_ne!(*r, 84);
     // mutable reference 를 value 로 갖는 variable 간의 swap
+    // -> Option<Box<Node>> 를 take 로 꺼내서 연결을 바꾸는 tree 의 회전은 crate::dsa::ordered::rbtree
 }
 
 /// 5. Interior Mutability
@@ -323,6 +324,7 @@ impl<'s, 'p> Iterator for StrSplit<'s, 'p> {
 
     fn next(&mut self) -> Option<Self::Item> {
         self.spliced.pop()
+        // -> 미리 Vec 에 모으지 않고 앞에서부터 lazily 나누는 (할당 없는) version 은 crate::text::StrSplit
     }
 }
 
@@ -168,6 +168,7 @@ fn example_8() {
     
     let s_default = Second::default();
     assert_eq!(0, s_default.value());
+    // -> 여러 단위의 newtype 과 단위 간 변환 / 연산 / 문자열 parsing 은 crate::units (Seconds, Milliseconds, MiB ...)
 }
 
 pub struct Second {
@@ -246,6 +247,7 @@ impl<T: Clone> HVec<T> {
         let mut result = Vec::<Box<T>>::new();
         for i in value {
             let inner = Box::new(i.clone());
+            // -> element 마다 Box 를 할당하지 않고 arena 에 이어서 놓는 version 은 crate::alloc::HVec
             result.push(inner);
         };
         result
@@ -272,6 +274,7 @@ fn example_11() {
     example_11_inner();
 }
 
+// -> closure 를 drop 때 실행하는 범용 guard (성공 / unwind 시에만 실행, dismiss) 는 crate::scope
 struct Finalizer {
     index: u8,
 }
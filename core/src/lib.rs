@@ -1,2 +1,13 @@
+pub mod alloc;
+pub mod actor;
+pub mod deadlock;
 pub mod dsa;
-pub mod guides;
\ No newline at end of file
+#[cfg(all(target_arch = "x86_64", target_os = "linux"))]
+pub mod green;
+pub mod guides;
+pub mod litmus;
+pub mod runtime;
+pub mod scope;
+pub mod sync;
+pub mod text;
+pub mod units;
\ No newline at end of file
@@ -0,0 +1,360 @@
+use super::{LitmusTest, Regs};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+fn check_store(order: Ordering) {
+    assert!(
+        matches!(
+            order,
+            Ordering::Relaxed | Ordering::Release | Ordering::SeqCst
+        ),
+        "invalid store ordering {:?}",
+        order
+    );
+}
+
+fn check_load(order: Ordering) {
+    assert!(
+        matches!(
+            order,
+            Ordering::Relaxed | Ordering::Acquire | Ordering::SeqCst
+        ),
+        "invalid load ordering {:?}",
+        order
+    );
+}
+
+/// Store buffering (SB) : W -> R reordering
+///
+/// ```text
+/// T0             T1
+/// x = 1          y = 1
+/// r0 = y         r1 = x
+/// ```
+/// r0 = r1 = 0 은 두 thread 모두 자기 쓰기보다 읽기를 먼저 한 것처럼 보이는 경우.
+/// x86-64 에서도 store buffer 때문에 나오며, 막으려면 모두 SeqCst 이어야 한다. (Release / Acquire 로는 부족)
+pub struct StoreBuffering {
+    x: AtomicU64,
+    y: AtomicU64,
+    store: Ordering,
+    load: Ordering,
+}
+
+impl StoreBuffering {
+    pub fn new(store: Ordering, load: Ordering) -> Self {
+        check_store(store);
+        check_load(load);
+        StoreBuffering {
+            x: AtomicU64::new(0),
+            y: AtomicU64::new(0),
+            store,
+            load,
+        }
+    }
+}
+
+impl LitmusTest for StoreBuffering {
+    fn name(&self) -> String {
+        format!("SB(store={:?}, load={:?})", self.store, self.load)
+    }
+
+    fn threads(&self) -> usize {
+        2
+    }
+
+    fn registers(&self) -> &'static [&'static str] {
+        &["r0", "r1"]
+    }
+
+    fn relaxed_outcome(&self) -> Vec<u64> {
+        vec![0, 0]
+    }
+
+    fn reset(&self) {
+        self.x.store(0, Ordering::Relaxed);
+        self.y.store(0, Ordering::Relaxed);
+    }
+
+    fn thread(&self, id: usize) -> Regs {
+        let (mine, other) = if id == 0 {
+            (&self.x, &self.y)
+        } else {
+            (&self.y, &self.x)
+        };
+        mine.store(1, self.store);
+        [other.load(self.load), 0]
+    }
+
+    fn outcome(&self, regs: &[Regs]) -> Vec<u64> {
+        vec![regs[0][0], regs[1][0]]
+    }
+}
+
+/// Message passing (MP) : W -> W 또는 R -> R reordering
+///
+/// ```text
+/// T0                      T1
+/// data = 1 (Relaxed)      r0 = flag
+/// flag = 1                r1 = data (Relaxed)
+/// ```
+/// r0 = 1, r1 = 0 은 flag 를 보고도 data 를 못 본 경우.
+/// flag 를 Release / Acquire 로 주고받으면 나올 수 없다. (ch04 p172 의 spinlock 이 기대는 성질)
+pub struct MessagePassing {
+    data: AtomicU64,
+    flag: AtomicU64,
+    store: Ordering,
+    load: Ordering,
+}
+
+impl MessagePassing {
+    /// store / load 는 flag 의 ordering (data 는 항상 Relaxed)
+    pub fn new(store: Ordering, load: Ordering) -> Self {
+        check_store(store);
+        check_load(load);
+        MessagePassing {
+            data: AtomicU64::new(0),
+            flag: AtomicU64::new(0),
+            store,
+            load,
+        }
+    }
+}
+
+impl LitmusTest for MessagePassing {
+    fn name(&self) -> String {
+        format!("MP(store={:?}, load={:?})", self.store, self.load)
+    }
+
+    fn threads(&self) -> usize {
+        2
+    }
+
+    fn registers(&self) -> &'static [&'static str] {
+        &["r0", "r1"]
+    }
+
+    fn relaxed_outcome(&self) -> Vec<u64> {
+        vec![1, 0]
+    }
+
+    fn reset(&self) {
+        self.data.store(0, Ordering::Relaxed);
+        self.flag.store(0, Ordering::Relaxed);
+    }
+
+    fn thread(&self, id: usize) -> Regs {
+        if id == 0 {
+            self.data.store(1, Ordering::Relaxed);
+            self.flag.store(1, self.store);
+            [0, 0]
+        } else {
+            let r0 = self.flag.load(self.load);
+            let r1 = self.data.load(Ordering::Relaxed);
+            [r0, r1]
+        }
+    }
+
+    fn outcome(&self, regs: &[Regs]) -> Vec<u64> {
+        regs[1].to_vec()
+    }
+}
+
+/// Load buffering (LB) : R -> W reordering
+///
+/// ```text
+/// T0             T1
+/// r0 = x         r1 = y
+/// y = 1          x = 1
+/// ```
+/// r0 = r1 = 1 은 두 thread 모두 읽기 전에 상대방의 쓰기를 본 경우.
+/// ch04 표에서는 x86-64 도 R -> W 가 O 이지만, x86-64 (TSO) 는 load 뒤의 store 를 앞당기지 않으므로
+/// compiler 가 순서를 바꾸지 않는 한 관찰되지 않는다. (AArch64 등에서는 Relaxed 이면 나올 수 있음)
+pub struct LoadBuffering {
+    x: AtomicU64,
+    y: AtomicU64,
+    store: Ordering,
+    load: Ordering,
+}
+
+impl LoadBuffering {
+    pub fn new(store: Ordering, load: Ordering) -> Self {
+        check_store(store);
+        check_load(load);
+        LoadBuffering {
+            x: AtomicU64::new(0),
+            y: AtomicU64::new(0),
+            store,
+            load,
+        }
+    }
+}
+
+impl LitmusTest for LoadBuffering {
+    fn name(&self) -> String {
+        format!("LB(store={:?}, load={:?})", self.store, self.load)
+    }
+
+    fn threads(&self) -> usize {
+        2
+    }
+
+    fn registers(&self) -> &'static [&'static str] {
+        &["r0", "r1"]
+    }
+
+    fn relaxed_outcome(&self) -> Vec<u64> {
+        vec![1, 1]
+    }
+
+    fn reset(&self) {
+        self.x.store(0, Ordering::Relaxed);
+        self.y.store(0, Ordering::Relaxed);
+    }
+
+    fn thread(&self, id: usize) -> Regs {
+        let (read, write) = if id == 0 {
+            (&self.x, &self.y)
+        } else {
+            (&self.y, &self.x)
+        };
+        let r = read.load(self.load);
+        write.store(1, self.store);
+        [r, 0]
+    }
+
+    fn outcome(&self, regs: &[Regs]) -> Vec<u64> {
+        vec![regs[0][0], regs[1][0]]
+    }
+}
+
+/// Independent reads of independent writes (IRIW)
+///
+/// ```text
+/// T0        T1        T2           T3
+/// x = 1     y = 1     r0 = x       r2 = y
+///                     r1 = y       r3 = x
+/// ```
+/// r0 = 1, r1 = 0, r2 = 1, r3 = 0 은 T2 는 x 가 먼저, T3 는 y 가 먼저 쓰였다고 본 경우.
+/// (모든 thread 가 같은 쓰기 순서를 보지 않음) Release / Acquire 로는 막을 수 없고 SeqCst 가 필요하다.
+/// x86-64 는 모든 core 가 같은 순서로 store 를 보므로 (multi-copy atomic) 관찰되지 않는다.
+pub struct Iriw {
+    x: AtomicU64,
+    y: AtomicU64,
+    store: Ordering,
+    load: Ordering,
+}
+
+impl Iriw {
+    pub fn new(store: Ordering, load: Ordering) -> Self {
+        check_store(store);
+        check_load(load);
+        Iriw {
+            x: AtomicU64::new(0),
+            y: AtomicU64::new(0),
+            store,
+            load,
+        }
+    }
+}
+
+impl LitmusTest for Iriw {
+    fn name(&self) -> String {
+        format!("IRIW(store={:?}, load={:?})", self.store, self.load)
+    }
+
+    fn threads(&self) -> usize {
+        4
+    }
+
+    fn registers(&self) -> &'static [&'static str] {
+        &["r0", "r1", "r2", "r3"]
+    }
+
+    fn relaxed_outcome(&self) -> Vec<u64> {
+        vec![1, 0, 1, 0]
+    }
+
+    fn reset(&self) {
+        self.x.store(0, Ordering::Relaxed);
+        self.y.store(0, Ordering::Relaxed);
+    }
+
+    fn thread(&self, id: usize) -> Regs {
+        match id {
+            0 => {
+                self.x.store(1, self.store);
+                [0, 0]
+            }
+            1 => {
+                self.y.store(1, self.store);
+                [0, 0]
+            }
+            2 => [self.x.load(self.load), self.y.load(self.load)],
+            _ => [self.y.load(self.load), self.x.load(self.load)],
+        }
+    }
+
+    fn outcome(&self, regs: &[Regs]) -> Vec<u64> {
+        vec![regs[2][0], regs[2][1], regs[3][0], regs[3][1]]
+    }
+}
+
+/// SeqCst 이면 어떤 test 도 reordering outcome 이 나오지 않고, 나온 outcome 은 모두 가능한 값이다.
+#[test]
+fn seq_cst() {
+    use super::run;
+    use Ordering::SeqCst;
+
+    const ITERATIONS: u64 = 2_000;
+
+    let h = run(&StoreBuffering::new(SeqCst, SeqCst), ITERATIONS);
+    assert_eq!(h.relaxed_count(), 0);
+    let h = run(&MessagePassing::new(SeqCst, SeqCst), ITERATIONS);
+    assert_eq!(h.relaxed_count(), 0);
+    let h = run(&LoadBuffering::new(SeqCst, SeqCst), ITERATIONS);
+    assert_eq!(h.relaxed_count(), 0);
+    let h = run(&Iriw::new(SeqCst, SeqCst), ITERATIONS);
+    assert_eq!(h.relaxed_count(), 0);
+    assert!(h.iter().all(|(o, _)| o.iter().all(|&v| v <= 1)));
+}
+
+/// Release / Acquire 로 flag 를 주고받으면 data 를 놓치지 않는다.
+#[test]
+fn message_passing() {
+    use super::run;
+
+    let h = run(
+        &MessagePassing::new(Ordering::Release, Ordering::Acquire),
+        5_000,
+    );
+    assert_eq!(h.relaxed_count(), 0);
+    assert_eq!(h.iter().map(|(_, c)| c).sum::<u64>(), 5_000);
+}
+
+#[test]
+#[should_panic(expected = "invalid store ordering")]
+fn invalid_ordering() {
+    StoreBuffering::new(Ordering::Acquire, Ordering::Acquire);
+}
+
+/// 네 test 모두 (store, load) 순서로 ordering 을 받는다
+#[test]
+fn argument_order() {
+    use Ordering::{Acquire, Release};
+
+    assert_eq!(
+        StoreBuffering::new(Release, Acquire).name(),
+        "SB(store=Release, load=Acquire)"
+    );
+    assert_eq!(
+        MessagePassing::new(Release, Acquire).name(),
+        "MP(store=Release, load=Acquire)"
+    );
+    assert_eq!(
+        LoadBuffering::new(Release, Acquire).name(),
+        "LB(store=Release, load=Acquire)"
+    );
+    assert_eq!(
+        Iriw::new(Release, Acquire).name(),
+        "IRIW(store=Release, load=Acquire)"
+    );
+}
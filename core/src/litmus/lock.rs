@@ -0,0 +1,109 @@
+use super::{LitmusTest, Regs};
+use crate::sync::fair::{Lock, RawLock};
+use crate::sync::SpinLock;
+use std::hint;
+use std::sync::Mutex;
+
+/// MutualExclusion 으로 검사할 수 있는 lock
+pub trait CriticalSection: Sync {
+    /// lock 을 잡고 f 를 실행
+    fn critical(&self, f: &mut dyn FnMut(&mut u64));
+}
+
+impl CriticalSection for SpinLock<u64> {
+    fn critical(&self, f: &mut dyn FnMut(&mut u64)) {
+        f(&mut self.lock())
+    }
+}
+
+impl<R: RawLock> CriticalSection for Lock<R, u64> {
+    fn critical(&self, f: &mut dyn FnMut(&mut u64)) {
+        f(&mut self.lock())
+    }
+}
+
+impl CriticalSection for Mutex<u64> {
+    fn critical(&self, f: &mut dyn FnMut(&mut u64)) {
+        f(&mut self.lock().unwrap())
+    }
+}
+
+/// 두 thread 가 동시에 lock 을 잡고 counter 를 (atomic 이 아닌) 읽기 -> 쓰기로 1 씩 증가
+///
+/// lock 이 임계 영역을 보호하지 못하거나 (ch04 p172 에서 CAS 대신 load + store 로 lock 을 잡는 경우 등)
+/// lock / unlock 의 Acquire / Release 가 빠져서 이전 임계 영역의 쓰기가 보이지 않으면
+/// 한 쪽의 증가가 사라져서 count = 1 이 나온다.
+pub struct MutualExclusion<L> {
+    lock: L,
+}
+
+impl<L: CriticalSection> MutualExclusion<L> {
+    /// lock 이 보호하는 값은 0 이어야 한다. (reset 마다 0 으로 되돌림)
+    pub fn new(lock: L) -> Self {
+        MutualExclusion { lock }
+    }
+}
+
+impl<L: CriticalSection> LitmusTest for MutualExclusion<L> {
+    fn name(&self) -> String {
+        format!("MutualExclusion<{}>", std::any::type_name::<L>())
+    }
+
+    fn threads(&self) -> usize {
+        2
+    }
+
+    fn registers(&self) -> &'static [&'static str] {
+        &["count"]
+    }
+
+    fn relaxed_outcome(&self) -> Vec<u64> {
+        vec![1]
+    }
+
+    fn reset(&self) {
+        self.lock.critical(&mut |v| *v = 0);
+    }
+
+    fn thread(&self, _id: usize) -> Regs {
+        self.lock.critical(&mut |v| {
+            let old = *v;
+            // 읽기와 쓰기 사이를 벌려서 다른 thread 가 끼어들 틈을 만든다
+            for _ in 0..16 {
+                hint::spin_loop();
+            }
+            *v = old + 1;
+        });
+        [0, 0]
+    }
+
+    fn outcome(&self, _regs: &[Regs]) -> Vec<u64> {
+        let mut count = 0;
+        self.lock.critical(&mut |v| count = *v);
+        vec![count]
+    }
+}
+
+#[test]
+fn spin_lock() {
+    use super::run;
+    use crate::sync::fair::{McsLock, TicketLock};
+
+    const ITERATIONS: u64 = 5_000;
+
+    let h = run(&MutualExclusion::new(SpinLock::new(0)), ITERATIONS);
+    assert_eq!(h.count(&[2]), ITERATIONS);
+
+    let h = run(
+        &MutualExclusion::new(Lock::<TicketLock, u64>::new(0)),
+        ITERATIONS,
+    );
+    assert_eq!(h.count(&[2]), ITERATIONS);
+    let h = run(
+        &MutualExclusion::new(Lock::<McsLock, u64>::new(0)),
+        ITERATIONS,
+    );
+    assert_eq!(h.count(&[2]), ITERATIONS);
+    let h = run(&MutualExclusion::new(Mutex::new(0)), ITERATIONS);
+    assert_eq!(h.count(&[2]), ITERATIONS);
+}
@@ -0,0 +1,242 @@
+use crate::sync::queue::CachePadded;
+use crate::sync::spinlock::Backoff;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::thread;
+
+/// memory ordering litmus test
+///
+/// ch04 4.7 의 reordering 표 (x86-64 는 W -> R 만 reordering) 를 실제로 확인하기 위한 도구.
+/// 여러 thread 가 공유 변수에 읽기 / 쓰기를 한 번씩 하는 짧은 program 을 수만 번 동시에 실행하고,
+/// 각 thread 가 읽은 값 (register) 의 조합 (outcome) 별로 몇 번 나왔는지 Histogram 으로 센다.
+/// 순서대로 (sequentially consistent) 실행했다면 나올 수 없는 outcome 이 나오면 reordering 이 일어난 것이다.
+/// - classic : store buffering (SB), message passing (MP), load buffering (LB), IRIW
+/// - lock    : SpinLock 등 lock 이 임계 영역을 제대로 보호하는지 (lost update 가 없는지)
+///
+/// 관찰되지 않았다고 해서 허용되지 않는 것은 아니다. (CPU / compiler / 실행 횟수에 따라 다름)
+/// 전체 결과는 cargo test --release litmus::report -- --ignored --nocapture
+pub mod classic;
+pub mod lock;
+
+pub use classic::{Iriw, LoadBuffering, MessagePassing, StoreBuffering};
+pub use lock::{CriticalSection, MutualExclusion};
+
+/// 한 thread 가 읽은 값 (최대 2개)
+pub type Regs = [u64; 2];
+
+/// litmus test 하나
+///
+/// run 은 매 시행마다 모든 thread 를 동시에 출발시켜 thread(id) 를 실행하고,
+/// 모든 thread 가 끝나면 outcome 으로 결과를 기록한 뒤 reset 으로 공유 변수를 초기화한다.
+pub trait LitmusTest: Sync {
+    /// 결과 출력용 이름 (ordering 포함)
+    fn name(&self) -> String;
+
+    fn threads(&self) -> usize;
+
+    /// outcome 의 각 값의 이름 (r0, r1 ..)
+    fn registers(&self) -> &'static [&'static str];
+
+    /// 순서대로 실행했다면 나올 수 없는 (reordering 이 일어나야 나오는) outcome
+    fn relaxed_outcome(&self) -> Vec<u64>;
+
+    /// 공유 변수를 초기값으로
+    fn reset(&self);
+
+    /// thread id 의 program 을 실행하고 읽은 값을 반환
+    fn thread(&self, id: usize) -> Regs;
+
+    /// 모든 thread 가 끝난 뒤, 각 thread 의 Regs 로 outcome 을 만든다. (registers 순서)
+    fn outcome(&self, regs: &[Regs]) -> Vec<u64>;
+}
+
+/// test 를 iterations 번 실행하고 outcome 별 횟수를 센다.
+pub fn run<T: LitmusTest>(test: &T, iterations: u64) -> Histogram {
+    let n = test.threads();
+    assert!(n > 0);
+    let barrier = SpinBarrier::new(n);
+    // 다른 thread 의 Regs 와 같은 cache line 에 있으면 실행 시점이 어긋나므로 분리
+    let slots: Vec<_> = (0..n)
+        .map(|_| CachePadded::new([AtomicU64::new(0), AtomicU64::new(0)]))
+        .collect();
+
+    test.reset();
+    let counts = thread::scope(|s| {
+        let mut handles = Vec::new();
+        for id in 0..n {
+            let barrier = &barrier;
+            let slots = &slots;
+            handles.push(s.spawn(move || {
+                let mut counts = BTreeMap::new();
+                let mut sense = false;
+                for _ in 0..iterations {
+                    // 모든 thread 가 같은 시점에 출발
+                    barrier.wait(&mut sense);
+                    let regs = test.thread(id);
+                    slots[id][0].store(regs[0], Ordering::Relaxed);
+                    slots[id][1].store(regs[1], Ordering::Relaxed);
+                    barrier.wait(&mut sense);
+
+                    // 다른 thread 는 다음 시행의 첫 barrier 에서 기다리므로 thread 0 혼자 기록 / 초기화
+                    if id == 0 {
+                        let regs: Vec<Regs> = slots
+                            .iter()
+                            .map(|r| [r[0].load(Ordering::Relaxed), r[1].load(Ordering::Relaxed)])
+                            .collect();
+                        *counts.entry(test.outcome(&regs)).or_insert(0) += 1;
+                        test.reset();
+                    }
+                }
+                counts
+            }));
+        }
+        let mut handles = handles.into_iter();
+        let counts = handles.next().unwrap().join().unwrap();
+        for h in handles {
+            h.join().unwrap();
+        }
+        counts
+    });
+
+    Histogram {
+        name: test.name(),
+        registers: test.registers(),
+        relaxed: test.relaxed_outcome(),
+        counts,
+        iterations,
+    }
+}
+
+/// outcome 별 관찰 횟수
+#[derive(Debug, Clone)]
+pub struct Histogram {
+    name: String,
+    registers: &'static [&'static str],
+    relaxed: Vec<u64>,
+    counts: BTreeMap<Vec<u64>, u64>,
+    iterations: u64,
+}
+
+impl Histogram {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn iterations(&self) -> u64 {
+        self.iterations
+    }
+
+    pub fn count(&self, outcome: &[u64]) -> u64 {
+        self.counts.get(outcome).copied().unwrap_or(0)
+    }
+
+    /// reordering 이 일어난 횟수
+    pub fn relaxed_count(&self) -> u64 {
+        self.count(&self.relaxed)
+    }
+
+    /// 관찰된 outcome 과 횟수 (outcome 순으로 정렬)
+    pub fn iter(&self) -> impl Iterator<Item = (&[u64], u64)> + '_ {
+        self.counts.iter().map(|(k, &v)| (k.as_slice(), v))
+    }
+}
+
+/// 이름과 시행 횟수, outcome 별 횟수 / 비율 / 막대. reordering outcome 에는 * 표시
+impl fmt::Display for Histogram {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const WIDTH: u64 = 40;
+
+        writeln!(f, "{} ({} iterations)", self.name, self.iterations)?;
+        for (outcome, &count) in &self.counts {
+            let regs: Vec<String> = self
+                .registers
+                .iter()
+                .zip(outcome)
+                .map(|(r, v)| format!("{}={}", r, v))
+                .collect();
+            let mark = if *outcome == self.relaxed { '*' } else { ' ' };
+            let bar = (count * WIDTH).div_ceil(self.iterations.max(1)) as usize;
+            writeln!(
+                f,
+                "  {} {} {:>10} {:>7.3}% {}",
+                mark,
+                regs.join(" "),
+                count,
+                count as f64 * 100.0 / self.iterations.max(1) as f64,
+                "#".repeat(bar)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// 매 시행마다 두 번씩 쓰는 barrier 이므로 Condvar 대신 spin (sense reversing)
+/// 오래 기다리면 Backoff 가 yield 하므로 core 보다 thread 가 많아도 진행한다.
+struct SpinBarrier {
+    count: AtomicUsize,
+    sense: AtomicBool,
+    n: usize,
+}
+
+impl SpinBarrier {
+    fn new(n: usize) -> Self {
+        SpinBarrier {
+            count: AtomicUsize::new(0),
+            sense: AtomicBool::new(false),
+            n,
+        }
+    }
+
+    // 세대마다 sense 가 뒤집히므로 count 를 초기화하는 사이에 다음 세대의 thread 가 섞이지 않는다
+    fn wait(&self, local: &mut bool) {
+        *local = !*local;
+        if self.count.fetch_add(1, Ordering::AcqRel) + 1 == self.n {
+            self.count.store(0, Ordering::Relaxed);
+            self.sense.store(*local, Ordering::Release);
+        } else {
+            let mut backoff = Backoff::new();
+            while self.sense.load(Ordering::Acquire) != *local {
+                backoff.snooze();
+            }
+        }
+    }
+}
+
+/// 모든 조합의 결과를 출력한다.
+/// cargo test --release litmus::report -- --ignored --nocapture
+#[test]
+#[ignore]
+fn report() {
+    use Ordering::*;
+
+    const ITERATIONS: u64 = 100_000;
+
+    for (store, load) in [(Relaxed, Relaxed), (Release, Acquire), (SeqCst, SeqCst)] {
+        println!("{}", run(&StoreBuffering::new(store, load), ITERATIONS));
+        println!("{}", run(&MessagePassing::new(store, load), ITERATIONS));
+        println!("{}", run(&LoadBuffering::new(store, load), ITERATIONS));
+        println!("{}", run(&Iriw::new(store, load), ITERATIONS));
+    }
+    println!(
+        "{}",
+        run(
+            &MutualExclusion::new(crate::sync::SpinLock::new(0)),
+            ITERATIONS
+        )
+    );
+}
+
+#[test]
+fn histogram() {
+    let test = StoreBuffering::new(Ordering::SeqCst, Ordering::SeqCst);
+    let h = run(&test, 1000);
+    assert_eq!(h.iterations(), 1000);
+    assert_eq!(h.iter().map(|(_, c)| c).sum::<u64>(), 1000);
+    // SeqCst 이면 store buffering 의 (0, 0) 은 나올 수 없다
+    assert_eq!(h.relaxed_count(), 0);
+
+    let s = h.to_string();
+    assert!(s.starts_with("SB(store=SeqCst, load=SeqCst) (1000 iterations)"));
+    assert!(!s.contains('*'));
+}
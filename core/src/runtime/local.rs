@@ -0,0 +1,463 @@
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::thread::{self, Thread};
+use std::time::{Duration, Instant};
+
+use futures::future::{FutureExt, LocalBoxFuture};
+use futures::task::{waker_ref, ArcWake};
+
+use super::timer::{TimerKey, TimerWheel};
+
+/// 단일 thread executor (ch05 p189 의 Executor 정리)
+///
+/// p189 의 Executor 는
+/// - run() 이 channel 을 영원히 기다리므로 모든 task 가 끝나도 반환하지 않았고
+/// - spawn 한 task 의 결과를 받을 방법 (JoinHandle) 이 없었으며
+/// - task 가 Send + 'static 이어야 했다.
+///
+/// 여기서는 task 를 executor 안의 map 에 두고, waker 는 실행 큐에 task id 만 넣은 뒤 executor thread 를 unpark 한다.
+/// 실행할 task 가 없으면 가장 이른 timer 까지 park 하고, block_on / run 이 끝나는 조건을 명확히 정했다.
+/// - block_on(fut) : fut 이 완료되면 (남은 task 가 있어도) 결과를 반환
+/// - run()         : spawn 한 task 가 모두 끝나면 반환
+///
+/// task 는 executor thread 에서만 poll 되므로 Send 가 아니어도 된다. (Rc, RefCell 등을 사용 가능)
+pub struct LocalExecutor {
+    inner: Rc<Inner>,
+}
+
+struct Inner {
+    tasks: RefCell<HashMap<usize, Task>>,
+    next_id: Cell<usize>,
+    queue: Arc<Mutex<VecDeque<usize>>>, // 실행 가능한 task id (다른 thread 에서 wake 할 수 있으므로 Mutex)
+    timers: Rc<RefCell<TimerWheel>>,
+    thread: Thread,
+}
+
+struct Task {
+    future: LocalBoxFuture<'static, ()>,
+    waker: Arc<TaskWaker>,
+}
+
+// p189 의 Task 에 해당하지만 future 는 executor 가 가지고 있고, 여기는 깨울 때 필요한 정보만
+struct TaskWaker {
+    id: usize,
+    scheduled: AtomicBool, // 이미 실행 큐에 들어있으면 다시 넣지 않는다
+    queue: Arc<Mutex<VecDeque<usize>>>,
+    thread: Thread,
+}
+
+impl ArcWake for TaskWaker {
+    fn wake_by_ref(arc_self: &Arc<Self>) {
+        if !arc_self.scheduled.swap(true, Ordering::AcqRel) {
+            arc_self.queue.lock().unwrap().push_back(arc_self.id);
+            arc_self.thread.unpark();
+        }
+    }
+}
+
+// block_on 에 넘긴 future 의 id
+const MAIN: usize = usize::MAX;
+
+thread_local! {
+    // 현재 thread 에서 실행 중인 executor (spawn / sleep 에서 사용)
+    static CURRENT: RefCell<Option<Rc<Inner>>> = const { RefCell::new(None) };
+}
+
+// block_on / run 이 끝나면 (panic 포함) 이전 executor 로 되돌린다
+struct Enter {
+    prev: Option<Rc<Inner>>,
+}
+
+impl Enter {
+    fn new(inner: &Rc<Inner>) -> Self {
+        let prev = CURRENT.with(|c| c.borrow_mut().replace(inner.clone()));
+        Enter { prev }
+    }
+}
+
+impl Drop for Enter {
+    fn drop(&mut self) {
+        CURRENT.with(|c| *c.borrow_mut() = self.prev.take());
+    }
+}
+
+impl Inner {
+    fn new_waker(&self, id: usize) -> Arc<TaskWaker> {
+        Arc::new(TaskWaker {
+            id,
+            scheduled: AtomicBool::new(false),
+            queue: self.queue.clone(),
+            thread: self.thread.clone(),
+        })
+    }
+
+    fn spawn<F>(&self, future: F) -> JoinHandle<F::Output>
+    where
+        F: Future + 'static,
+        F::Output: 'static,
+    {
+        let state = Rc::new(RefCell::new(JoinState {
+            result: None,
+            waker: None,
+        }));
+        let s = state.clone();
+        let future = async move {
+            let output = future.await;
+            let waker = {
+                let mut s = s.borrow_mut();
+                s.result = Some(output);
+                s.waker.take()
+            };
+            if let Some(w) = waker {
+                w.wake();
+            }
+        };
+
+        let id = self.next_id.get();
+        self.next_id.set(id + 1);
+        let waker = self.new_waker(id);
+        self.tasks.borrow_mut().insert(
+            id,
+            Task {
+                future: future.boxed_local(),
+                waker: waker.clone(),
+            },
+        );
+        ArcWake::wake_by_ref(&waker);
+
+        JoinHandle { state }
+    }
+
+    // 만료된 timer 를 깨운다
+    fn fire_timers(&self) {
+        let expired = self.timers.borrow_mut().advance(Instant::now());
+        for w in expired {
+            w.wake();
+        }
+    }
+
+    fn pop(&self) -> Option<usize> {
+        self.queue.lock().unwrap().pop_front()
+    }
+
+    // task 하나를 poll. 끝났으면 map 에서 제거
+    fn poll_task(&self, id: usize) {
+        // poll 도중에 spawn 할 수 있도록 (tasks 를 borrow_mut) map 에서 꺼낸 상태로 poll
+        let mut task = match self.tasks.borrow_mut().remove(&id) {
+            Some(task) => task,
+            None => return, // 이미 끝난 task 의 wake
+        };
+        task.waker.scheduled.store(false, Ordering::Release);
+
+        let waker = waker_ref(&task.waker);
+        let mut cx = Context::from_waker(&waker);
+        if task.future.as_mut().poll(&mut cx).is_pending() {
+            self.tasks.borrow_mut().insert(id, task);
+        }
+    }
+
+    // 할 일이 없으면 가장 이른 timer 까지 (timer 가 없으면 wake 될 때까지) 대기
+    fn park(&self) {
+        if !self.queue.lock().unwrap().is_empty() {
+            return;
+        }
+        let deadline = self.timers.borrow().next_deadline();
+        match deadline {
+            Some(deadline) => {
+                let now = Instant::now();
+                if deadline > now {
+                    thread::park_timeout(deadline - now);
+                }
+            }
+            None => thread::park(),
+        }
+    }
+}
+
+impl LocalExecutor {
+    pub fn new() -> Self {
+        LocalExecutor {
+            inner: Rc::new(Inner {
+                tasks: RefCell::new(HashMap::new()),
+                next_id: Cell::new(0),
+                queue: Arc::new(Mutex::new(VecDeque::new())),
+                timers: Rc::new(RefCell::new(TimerWheel::new())),
+                thread: thread::current(),
+            }),
+        }
+    }
+
+    /// task 를 실행 큐에 넣는다. 실제 실행은 block_on / run 안에서 이루어진다.
+    pub fn spawn<F>(&self, future: F) -> JoinHandle<F::Output>
+    where
+        F: Future + 'static,
+        F::Output: 'static,
+    {
+        self.inner.spawn(future)
+    }
+
+    /// 아직 끝나지 않은 task 수 (block_on 의 future 는 제외)
+    pub fn len(&self) -> usize {
+        self.inner.tasks.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// future 가 완료될 때까지 spawn 한 task 와 함께 실행하고 결과를 반환
+    pub fn block_on<F: Future>(&self, future: F) -> F::Output {
+        self.assert_thread();
+        let _enter = Enter::new(&self.inner);
+        let mut future = std::pin::pin!(future);
+        let main = self.inner.new_waker(MAIN);
+        ArcWake::wake_by_ref(&main);
+
+        loop {
+            self.inner.fire_timers();
+            while let Some(id) = self.inner.pop() {
+                if id != MAIN {
+                    self.inner.poll_task(id);
+                    continue;
+                }
+                main.scheduled.store(false, Ordering::Release);
+                let waker = waker_ref(&main);
+                let mut cx = Context::from_waker(&waker);
+                if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                    return output;
+                }
+            }
+            self.inner.park();
+        }
+    }
+
+    /// spawn 한 task 가 모두 끝날 때까지 실행
+    pub fn run(&self) {
+        self.assert_thread();
+        let _enter = Enter::new(&self.inner);
+
+        while !self.is_empty() {
+            self.inner.fire_timers();
+            while let Some(id) = self.inner.pop() {
+                self.inner.poll_task(id);
+            }
+            if self.is_empty() {
+                break;
+            }
+            self.inner.park();
+        }
+    }
+
+    // 다른 thread 에서 park 하면 waker 의 unpark 가 닿지 않는다
+    fn assert_thread(&self) {
+        assert_eq!(
+            thread::current().id(),
+            self.inner.thread.id(),
+            "LocalExecutor must be driven on the thread that created it"
+        );
+    }
+}
+
+impl Default for LocalExecutor {
+    fn default() -> Self {
+        LocalExecutor::new()
+    }
+}
+
+fn with_current<R>(f: impl FnOnce(&Rc<Inner>) -> R) -> R {
+    CURRENT.with(|c| {
+        let c = c.borrow();
+        let inner = c
+            .as_ref()
+            .expect("must be called from within LocalExecutor::block_on or LocalExecutor::run");
+        f(inner)
+    })
+}
+
+/// 실행 중인 executor 에 task 를 추가 (task 안에서 다른 task 를 spawn 할 때)
+pub fn spawn<F>(future: F) -> JoinHandle<F::Output>
+where
+    F: Future + 'static,
+    F::Output: 'static,
+{
+    with_current(|inner| inner.spawn(future))
+}
+
+struct JoinState<T> {
+    result: Option<T>,
+    waker: Option<Waker>, // 결과를 기다리는 쪽
+}
+
+/// spawn 한 task 의 결과를 기다리는 future
+///
+/// drop 해도 task 는 취소되지 않고 계속 실행된다. (결과만 버려짐)
+pub struct JoinHandle<T> {
+    state: Rc<RefCell<JoinState<T>>>,
+}
+
+impl<T> JoinHandle<T> {
+    pub fn is_finished(&self) -> bool {
+        self.state.borrow().result.is_some()
+    }
+}
+
+impl<T> Future for JoinHandle<T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        let mut state = self.state.borrow_mut();
+        match state.result.take() {
+            Some(output) => Poll::Ready(output),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// duration 이 지날 때까지 대기하는 future
+pub fn sleep(duration: Duration) -> Sleep {
+    Sleep {
+        deadline: Instant::now() + duration,
+        timer: None,
+    }
+}
+
+/// sleep() 이 반환하는 future. 처음 poll 될 때 실행 중인 executor 의 timer wheel 에 등록된다.
+pub struct Sleep {
+    deadline: Instant,
+    timer: Option<(Rc<RefCell<TimerWheel>>, TimerKey)>,
+}
+
+impl Sleep {
+    pub fn deadline(&self) -> Instant {
+        self.deadline
+    }
+}
+
+impl Future for Sleep {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if Instant::now() >= self.deadline {
+            if let Some((timers, key)) = self.timer.take() {
+                timers.borrow_mut().remove(key);
+            }
+            return Poll::Ready(());
+        }
+
+        // 다른 task 로 옮겨졌을 수도 있으므로 매번 최신 waker 로 다시 등록
+        let timers = match self.timer.take() {
+            Some((timers, key)) => {
+                timers.borrow_mut().remove(key);
+                timers
+            }
+            None => with_current(|inner| inner.timers.clone()),
+        };
+        let key = timers.borrow_mut().insert(self.deadline, cx.waker().clone());
+        self.timer = Some((timers, key));
+        Poll::Pending
+    }
+}
+
+impl Drop for Sleep {
+    fn drop(&mut self) {
+        if let Some((timers, key)) = self.timer.take() {
+            timers.borrow_mut().remove(key);
+        }
+    }
+}
+
+/// ch05 p189 의 Hello 를 block_on 으로 실행 : 자신을 wake 한 뒤 Pending 을 반환해도 끝까지 실행된다.
+#[test]
+fn p189() {
+    use crate::guides::concurrent_programming::ch05_async::Hello;
+
+    let executor = LocalExecutor::new();
+    executor.block_on(Hello::new());
+
+    // spawn 한 Hello 들의 결과를 JoinHandle 로 받는다
+    let total = executor.block_on(async {
+        let handles: Vec<_> = (0..10)
+            .map(|i| {
+                spawn(async move {
+                    Hello::new().await;
+                    i
+                })
+            })
+            .collect();
+
+        let mut total = 0;
+        for h in handles {
+            total += h.await;
+        }
+        total
+    });
+    assert_eq!(total, 45);
+    assert!(executor.is_empty());
+}
+
+/// run 은 task 안에서 spawn 한 task 까지 모두 끝나면 반환한다.
+#[test]
+fn run_until_idle() {
+    let executor = LocalExecutor::new();
+    let count = Rc::new(Cell::new(0));
+
+    for _ in 0..4 {
+        let count = count.clone();
+        executor.spawn(async move {
+            for _ in 0..4 {
+                let count = count.clone();
+                spawn(async move { count.set(count.get() + 1) });
+            }
+        });
+    }
+    executor.run();
+
+    assert_eq!(count.get(), 16);
+    assert!(executor.is_empty());
+}
+
+/// timer 는 등록 순서가 아니라 deadline 순서로 깨어난다.
+#[test]
+fn sleep_order() {
+    let executor = LocalExecutor::new();
+    let order = Rc::new(RefCell::new(Vec::new()));
+    let start = Instant::now();
+
+    for ms in [30, 10, 20] {
+        let order = order.clone();
+        executor.spawn(async move {
+            sleep(Duration::from_millis(ms)).await;
+            order.borrow_mut().push(ms);
+        });
+    }
+    executor.run();
+
+    assert_eq!(*order.borrow(), vec![10, 20, 30]);
+    assert!(start.elapsed() >= Duration::from_millis(30));
+}
+
+/// 다른 thread 에서 wake 해도 park 중인 executor 가 깨어난다.
+#[test]
+fn wake_from_other_thread() {
+    use crate::sync::async_channel;
+
+    let executor = LocalExecutor::new();
+    let (tx, rx) = async_channel::channel(1);
+
+    let th = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(10));
+        tx.try_send(42).unwrap();
+    });
+
+    assert_eq!(executor.block_on(rx.recv()), Ok(42));
+    th.join().unwrap();
+}
@@ -0,0 +1,11 @@
+/// 비동기 runtime (ch05 의 Executor / Spawner 정리)
+///
+/// 단일 thread executor
+pub mod local;
+/// work stealing multi thread runtime
+pub mod multi;
+/// sleep 을 위한 timer wheel
+mod timer;
+
+pub use local::{sleep, spawn, JoinHandle, LocalExecutor, Sleep};
+pub use multi::{Runtime, Spawner, TaskLocalKey};
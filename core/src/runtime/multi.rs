@@ -0,0 +1,870 @@
+use std::any::Any;
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::thread::{self, Thread};
+use std::time::Duration;
+
+use futures::future::{BoxFuture, FutureExt};
+use futures::task::{waker_ref, ArcWake};
+
+/// work stealing multi thread runtime (ch05 p189 의 Executor / Spawner 를 여러 thread 로)
+///
+/// p189 는 channel 하나를 실행 큐로 사용하므로 thread 를 늘리면 모든 thread 가 같은 queue 에서 경합한다.
+/// 여기서는 worker 마다 자신의 deque 를 두고
+/// - worker 안에서 wake / spawn 된 task 는 자신의 deque 에 넣고 앞에서부터 꺼내 실행
+/// - runtime 밖 (block_on, 다른 thread) 에서 들어온 task 는 공용 injector queue 에 넣음
+/// - 자신의 deque 와 injector 가 비면 다른 worker 의 deque 뒤쪽 절반을 훔쳐온다 (work stealing)
+///
+/// 할 일이 없는 worker 는 Condvar 로 잠들고, task 가 queue 에 들어올 때 하나씩 깨운다.
+/// blocking 작업 (파일 IO, 무거운 계산) 은 worker 를 막지 않도록 spawn_blocking 으로 별도 pool 에서 실행한다.
+///
+/// p189 와 같이 get_spawner().spawn(..) 후 run() 으로 사용할 수 있다. (run 은 모든 task 가 끝나면 반환)
+pub struct Runtime {
+    shared: Arc<Shared>,
+    workers: Vec<thread::JoinHandle<()>>,
+}
+
+struct Shared {
+    injector: Mutex<VecDeque<Arc<Task>>>,
+    queues: Vec<Mutex<VecDeque<Arc<Task>>>>, // worker 별 deque
+    sleep: Mutex<u64>,                        // task 가 들어올 때마다 증가 (잠들기 전후로 비교해서 놓친 wake 를 감지)
+    wakeup: Condvar,
+    active: Mutex<usize>, // 끝나지 않은 task 수
+    all_done: Condvar,
+    shutdown: AtomicBool,
+    blocking: Arc<BlockingPool>,
+}
+
+struct Task {
+    future: Mutex<Option<BoxFuture<'static, ()>>>, // poll 하는 동안과 끝난 뒤에는 None
+    state: AtomicU8,
+    locals: Mutex<HashMap<usize, Box<dyn Any + Send>>>,
+    shared: Arc<Shared>,
+}
+
+// Task::state
+const IDLE: u8 = 0; // wake 를 기다리는 중
+const SCHEDULED: u8 = 1; // 실행 큐에 들어있음 (다시 넣지 않는다)
+const RUNNING: u8 = 2; // worker 가 poll 하는 중
+const NOTIFIED: u8 = 3; // poll 하는 중에 wake 됨 (poll 이 끝나면 poll 한 worker 가 다시 큐에 넣는다)
+const DONE: u8 = 4; // 끝났거나 runtime 종료로 취소됨
+
+impl Task {
+    // runtime 종료 후 : future 를 drop 해서 future 가 가진 waker (Arc<Task>) 와의 순환 참조도 끊는다
+    fn cancel(&self) {
+        self.state.store(DONE, Ordering::Release);
+        let future = self.future.lock().unwrap().take();
+        drop(future);
+        self.locals.lock().unwrap().clear();
+    }
+}
+
+impl ArcWake for Task {
+    fn wake_by_ref(arc_self: &Arc<Self>) {
+        let mut state = arc_self.state.load(Ordering::Acquire);
+        loop {
+            let next = match state {
+                IDLE => SCHEDULED,
+                RUNNING => NOTIFIED,
+                _ => return,
+            };
+            match arc_self
+                .state
+                .compare_exchange_weak(state, next, Ordering::AcqRel, Ordering::Acquire)
+            {
+                Ok(_) if next == SCHEDULED => return arc_self.shared.schedule(arc_self.clone()),
+                Ok(_) => return,
+                Err(s) => state = s,
+            }
+        }
+    }
+}
+
+thread_local! {
+    // 현재 thread 가 속한 runtime 과 worker 번호 (block_on 중인 thread 는 None)
+    static CURRENT: RefCell<Option<(Arc<Shared>, Option<usize>)>> = const { RefCell::new(None) };
+    // 지금 poll 하고 있는 task (task local 에서 사용)
+    static CURRENT_TASK: RefCell<Option<Arc<Task>>> = const { RefCell::new(None) };
+}
+
+// block_on 이 끝나면 (panic 포함) 이전 runtime 으로 되돌린다
+struct Enter {
+    prev: Option<(Arc<Shared>, Option<usize>)>,
+}
+
+impl Enter {
+    fn new(shared: &Arc<Shared>, worker: Option<usize>) -> Self {
+        let prev = CURRENT.with(|c| c.borrow_mut().replace((shared.clone(), worker)));
+        Enter { prev }
+    }
+}
+
+impl Drop for Enter {
+    fn drop(&mut self) {
+        CURRENT.with(|c| *c.borrow_mut() = self.prev.take());
+    }
+}
+
+impl Shared {
+    // 이 runtime 의 worker thread 이면 worker 번호
+    fn current_worker(self: &Arc<Self>) -> Option<usize> {
+        CURRENT.with(|c| match &*c.borrow() {
+            Some((shared, worker)) if Arc::ptr_eq(shared, self) => *worker,
+            _ => None,
+        })
+    }
+
+    fn schedule(self: &Arc<Self>, task: Arc<Task>) {
+        let mut queue = match self.current_worker() {
+            Some(idx) => self.queues[idx].lock().unwrap(),
+            None => self.injector.lock().unwrap(),
+        };
+        // Runtime 이 drop 된 뒤의 wake : queue 에 넣으면 task 와 Shared 가 서로를 가리켜서 해제되지 않는다.
+        // (queue 의 lock 을 잡고 확인하므로 drop 에서 queue 를 비운 뒤에 들어오는 task 는 없다)
+        if self.shutdown.load(Ordering::Acquire) {
+            drop(queue);
+            task.cancel();
+            return;
+        }
+        queue.push_back(task);
+        drop(queue);
+        *self.sleep.lock().unwrap() += 1;
+        self.wakeup.notify_one();
+    }
+
+    fn spawn<F>(self: &Arc<Self>, future: F) -> JoinHandle<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        let (handle, mut complete) = JoinHandle::new();
+        // task 안의 panic 으로 worker 가 죽지 않도록 잡아서 JoinHandle 로 전달
+        let future = async move {
+            let result = AssertUnwindSafe(future).catch_unwind().await;
+            complete.finish(result);
+        };
+
+        *self.active.lock().unwrap() += 1;
+        let task = Arc::new(Task {
+            future: Mutex::new(Some(future.boxed())),
+            state: AtomicU8::new(IDLE),
+            locals: Mutex::new(HashMap::new()),
+            shared: self.clone(),
+        });
+        ArcWake::wake_by_ref(&task);
+
+        handle
+    }
+
+    fn find_task(&self, idx: usize) -> Option<Arc<Task>> {
+        if let Some(task) = self.queues[idx].lock().unwrap().pop_front() {
+            return Some(task);
+        }
+        if let Some(task) = self.injector.lock().unwrap().pop_front() {
+            return Some(task);
+        }
+        self.steal(idx)
+    }
+
+    // 다른 worker 의 deque 뒤쪽 절반을 가져온다. 두 deque 의 lock 을 동시에 잡지 않는다.
+    fn steal(&self, idx: usize) -> Option<Arc<Task>> {
+        let n = self.queues.len();
+        for i in 1..n {
+            let victim = (idx + i) % n;
+            let mut stolen = {
+                let mut q = self.queues[victim].lock().unwrap();
+                let len = q.len();
+                if len == 0 {
+                    continue;
+                }
+                q.split_off(len - len.div_ceil(2))
+            };
+            let task = stolen.pop_front();
+            self.queues[idx].lock().unwrap().extend(stolen);
+            return task;
+        }
+        None
+    }
+
+    fn run_task(&self, task: Arc<Task>) {
+        // queue 에는 SCHEDULED 인 task 만 한번씩 들어있다
+        if task
+            .state
+            .compare_exchange(SCHEDULED, RUNNING, Ordering::AcqRel, Ordering::Acquire)
+            .is_err()
+        {
+            return;
+        }
+        // poll 하는 동안 lock 을 잡고 있지 않도록 꺼낸다
+        let Some(mut future) = task.future.lock().unwrap().take() else {
+            return;
+        };
+
+        CURRENT_TASK.with(|t| *t.borrow_mut() = Some(task.clone()));
+        let waker = waker_ref(&task);
+        let mut cx = Context::from_waker(&waker);
+        let poll = future.as_mut().poll(&mut cx);
+        CURRENT_TASK.with(|t| *t.borrow_mut() = None);
+
+        if poll.is_ready() {
+            task.state.store(DONE, Ordering::Release);
+            drop(future);
+            task.locals.lock().unwrap().clear();
+            let mut active = self.active.lock().unwrap();
+            *active -= 1;
+            if *active == 0 {
+                self.all_done.notify_all();
+            }
+            return;
+        }
+
+        // RUNNING 인 동안에는 다른 worker 가 꺼내지 않으므로, 상태를 바꾸기 전에 되돌려 놓는다
+        *task.future.lock().unwrap() = Some(future);
+        if task
+            .state
+            .compare_exchange(RUNNING, IDLE, Ordering::AcqRel, Ordering::Acquire)
+            .is_err()
+        {
+            // poll 중에 wake 되었으므로 (NOTIFIED) 이제 queue 에 넣는다
+            task.state.store(SCHEDULED, Ordering::Release);
+            task.shared.schedule(task.clone());
+        }
+    }
+
+    fn worker_loop(self: Arc<Self>, idx: usize) {
+        let _enter = Enter::new(&self, Some(idx));
+        loop {
+            // 찾기 전에 epoch 를 읽어두면, 찾는 도중 들어온 task 는 epoch 가 바뀌어서 잠들지 않는다
+            let epoch = *self.sleep.lock().unwrap();
+            if self.shutdown.load(Ordering::Acquire) {
+                return;
+            }
+            if let Some(task) = self.find_task(idx) {
+                self.run_task(task);
+                continue;
+            }
+
+            let mut e = self.sleep.lock().unwrap();
+            while *e == epoch && !self.shutdown.load(Ordering::Acquire) {
+                e = self.wakeup.wait(e).unwrap();
+            }
+        }
+    }
+}
+
+impl Runtime {
+    /// worker thread 수를 받는다. blocking pool 은 최대 DEFAULT_BLOCKING_THREADS 개
+    pub fn new(workers: usize) -> Self {
+        Runtime::with_blocking_threads(workers, DEFAULT_BLOCKING_THREADS)
+    }
+
+    pub fn with_blocking_threads(workers: usize, blocking_threads: usize) -> Self {
+        assert!(workers > 0 && blocking_threads > 0);
+        let shared = Arc::new(Shared {
+            injector: Mutex::new(VecDeque::new()),
+            queues: (0..workers).map(|_| Mutex::new(VecDeque::new())).collect(),
+            sleep: Mutex::new(0),
+            wakeup: Condvar::new(),
+            active: Mutex::new(0),
+            all_done: Condvar::new(),
+            shutdown: AtomicBool::new(false),
+            blocking: Arc::new(BlockingPool::new(blocking_threads)),
+        });
+
+        let workers = (0..workers)
+            .map(|idx| {
+                let shared = shared.clone();
+                thread::Builder::new()
+                    .name(format!("runtime-worker-{}", idx))
+                    .spawn(move || shared.worker_loop(idx))
+                    .unwrap()
+            })
+            .collect();
+
+        Runtime { shared, workers }
+    }
+
+    pub fn workers(&self) -> usize {
+        self.workers.len()
+    }
+
+    /// p189 의 get_spawner : runtime 밖의 thread 에서도 task 를 추가할 수 있다.
+    pub fn get_spawner(&self) -> Spawner {
+        Spawner {
+            shared: self.shared.clone(),
+        }
+    }
+
+    pub fn spawn<F>(&self, future: F) -> JoinHandle<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        self.shared.spawn(future)
+    }
+
+    pub fn spawn_blocking<F, T>(&self, f: F) -> JoinHandle<T>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        self.shared.blocking.spawn(f)
+    }
+
+    /// 현재 thread 에서 future 를 완료될 때까지 실행 (spawn 한 task 는 worker 가 실행)
+    pub fn block_on<F: Future>(&self, future: F) -> F::Output {
+        let _enter = Enter::new(&self.shared, None);
+        let mut future = std::pin::pin!(future);
+        let waker = Arc::new(ThreadWaker(thread::current()));
+        let waker = waker_ref(&waker);
+        let mut cx = Context::from_waker(&waker);
+
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+            thread::park();
+        }
+    }
+
+    /// p189 의 run : spawn 한 task 가 모두 끝날 때까지 대기
+    pub fn run(&self) {
+        let mut active = self.shared.active.lock().unwrap();
+        while *active > 0 {
+            active = self.shared.all_done.wait(active).unwrap();
+        }
+    }
+}
+
+impl Default for Runtime {
+    /// cpu 수 만큼의 worker
+    fn default() -> Self {
+        Runtime::new(thread::available_parallelism().map_or(1, |n| n.get()))
+    }
+}
+
+impl Drop for Runtime {
+    /// 끝나지 않은 task 는 실행하지 않고 버린다. (끝까지 실행하려면 먼저 run())
+    fn drop(&mut self) {
+        self.shared.shutdown.store(true, Ordering::Release);
+        *self.shared.sleep.lock().unwrap() += 1;
+        self.shared.wakeup.notify_all();
+        for w in self.workers.drain(..) {
+            let _ = w.join();
+        }
+
+        // 큐에 남은 task 가 Arc<Shared> 를 가지고 있으므로 비워서 순환 참조를 끊는다.
+        // 이후에 wake 되는 task 는 schedule 에서 취소된다
+        let mut tasks: Vec<_> = self.shared.injector.lock().unwrap().drain(..).collect();
+        for q in &self.shared.queues {
+            tasks.extend(q.lock().unwrap().drain(..));
+        }
+        for task in tasks {
+            task.cancel();
+        }
+
+        self.shared.blocking.shutdown();
+    }
+}
+
+struct ThreadWaker(Thread);
+
+impl ArcWake for ThreadWaker {
+    fn wake_by_ref(arc_self: &Arc<Self>) {
+        arc_self.0.unpark();
+    }
+}
+
+/// task 를 추가하는 handle (p189 의 Spawner)
+#[derive(Clone)]
+pub struct Spawner {
+    shared: Arc<Shared>,
+}
+
+impl Spawner {
+    pub fn spawn<F>(&self, future: F) -> JoinHandle<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        self.shared.spawn(future)
+    }
+
+    pub fn spawn_blocking<F, T>(&self, f: F) -> JoinHandle<T>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        self.shared.blocking.spawn(f)
+    }
+}
+
+fn current() -> Arc<Shared> {
+    CURRENT.with(|c| {
+        c.borrow()
+            .as_ref()
+            .map(|(shared, _)| shared.clone())
+            .expect("must be called from within a Runtime")
+    })
+}
+
+// task 나 block_on 안이면 그 runtime 의 Spawner
+pub(crate) fn try_current() -> Option<Spawner> {
+    CURRENT.with(|c| {
+        c.borrow().as_ref().map(|(shared, _)| Spawner {
+            shared: shared.clone(),
+        })
+    })
+}
+
+/// 실행 중인 runtime 에 task 를 추가 (task 나 block_on 안에서)
+pub fn spawn<F>(future: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    current().spawn(future)
+}
+
+/// 실행 중인 runtime 의 blocking pool 에서 f 를 실행
+pub fn spawn_blocking<F, T>(f: F) -> JoinHandle<T>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    current().blocking.spawn(f)
+}
+
+/// spawn / spawn_blocking 한 작업의 결과를 기다리는 future
+///
+/// 작업이 panic 하면 await 한 쪽에서 같은 panic 이 다시 발생한다.
+/// drop 해도 작업은 취소되지 않는다.
+pub struct JoinHandle<T> {
+    state: Arc<Mutex<JoinState<T>>>,
+}
+
+struct JoinState<T> {
+    result: Option<thread::Result<T>>,
+    waker: Option<Waker>,
+}
+
+// 작업 쪽이 가지고 있다가 결과를 넣는다. 결과를 넣지 못하고 drop 되면 (runtime 종료로 취소) panic 으로 완료
+struct Complete<T> {
+    state: Option<Arc<Mutex<JoinState<T>>>>,
+}
+
+impl<T> JoinHandle<T> {
+    fn new() -> (JoinHandle<T>, Complete<T>) {
+        let state = Arc::new(Mutex::new(JoinState {
+            result: None,
+            waker: None,
+        }));
+        let complete = Complete {
+            state: Some(state.clone()),
+        };
+        (JoinHandle { state }, complete)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.state.lock().unwrap().result.is_some()
+    }
+}
+
+impl<T> Complete<T> {
+    fn finish(&mut self, result: thread::Result<T>) {
+        if let Some(state) = self.state.take() {
+            let waker = {
+                let mut s = state.lock().unwrap();
+                s.result = Some(result);
+                s.waker.take()
+            };
+            if let Some(w) = waker {
+                w.wake();
+            }
+        }
+    }
+}
+
+impl<T> Drop for Complete<T> {
+    fn drop(&mut self) {
+        self.finish(Err(Box::new("task was cancelled")));
+    }
+}
+
+impl<T> Future for JoinHandle<T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        let mut state = self.state.lock().unwrap();
+        match state.result.take() {
+            Some(Ok(output)) => Poll::Ready(output),
+            Some(Err(e)) => {
+                drop(state);
+                panic::resume_unwind(e)
+            }
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+const DEFAULT_BLOCKING_THREADS: usize = 16;
+// 이 시간 동안 일이 없으면 blocking thread 를 종료
+const BLOCKING_KEEP_ALIVE: Duration = Duration::from_secs(10);
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// spawn_blocking 용 thread pool
+///
+/// 필요할 때 thread 를 max 개까지 늘리고, 한동안 일이 없으면 줄인다.
+struct BlockingPool {
+    state: Mutex<BlockingState>,
+    cond: Condvar,
+    max: usize,
+}
+
+struct BlockingState {
+    jobs: VecDeque<Job>,
+    threads: usize,
+    idle: usize,
+    shutdown: bool,
+}
+
+impl BlockingPool {
+    fn new(max: usize) -> Self {
+        BlockingPool {
+            state: Mutex::new(BlockingState {
+                jobs: VecDeque::new(),
+                threads: 0,
+                idle: 0,
+                shutdown: false,
+            }),
+            cond: Condvar::new(),
+            max,
+        }
+    }
+
+    fn spawn<F, T>(self: &Arc<Self>, f: F) -> JoinHandle<T>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let (handle, mut complete) = JoinHandle::new();
+        let job = Box::new(move || {
+            let result = panic::catch_unwind(AssertUnwindSafe(f));
+            complete.finish(result);
+        });
+
+        let mut state = self.state.lock().unwrap();
+        assert!(!state.shutdown, "runtime is shut down");
+        state.jobs.push_back(job);
+        if state.idle > 0 {
+            self.cond.notify_one();
+        } else if state.threads < self.max {
+            state.threads += 1;
+            let pool = self.clone();
+            thread::Builder::new()
+                .name("runtime-blocking".to_string())
+                .spawn(move || pool.thread_loop())
+                .unwrap();
+        }
+        handle
+    }
+
+    fn thread_loop(&self) {
+        let mut state = self.state.lock().unwrap();
+        loop {
+            if let Some(job) = state.jobs.pop_front() {
+                drop(state);
+                job();
+                state = self.state.lock().unwrap();
+                continue;
+            }
+            if state.shutdown {
+                break;
+            }
+
+            state.idle += 1;
+            let (s, timeout) = self.cond.wait_timeout(state, BLOCKING_KEEP_ALIVE).unwrap();
+            state = s;
+            state.idle -= 1;
+            if timeout.timed_out() && state.jobs.is_empty() {
+                break;
+            }
+        }
+        state.threads -= 1;
+        self.cond.notify_all();
+    }
+
+    // 남은 작업을 끝내고 모든 thread 가 종료될 때까지 대기
+    fn shutdown(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.shutdown = true;
+        self.cond.notify_all();
+        while state.threads > 0 {
+            state = self.cond.wait(state).unwrap();
+        }
+    }
+}
+
+/// task 마다 따로 가지는 변수 (thread_local! 의 task 버전)
+///
+/// task 는 poll 될 때마다 다른 worker thread 에서 실행될 수 있으므로 thread_local 로는 task 의 상태를 유지할 수 없다.
+/// 값은 task 가 처음 접근할 때 init 으로 만들어지고 task 가 끝나면 drop 된다.
+/// task_local! macro 로 선언한다.
+pub struct TaskLocalKey<T: 'static> {
+    init: fn() -> T,
+}
+
+impl<T: Send + 'static> TaskLocalKey<T> {
+    #[doc(hidden)]
+    pub const fn new(init: fn() -> T) -> Self {
+        TaskLocalKey { init }
+    }
+
+    /// task 밖에서 (block_on 의 future 포함) 호출하면 panic
+    pub fn with<R>(&'static self, f: impl FnOnce(&T) -> R) -> R {
+        let task = CURRENT_TASK
+            .with(|t| t.borrow().clone())
+            .expect("task local accessed outside of a Runtime task");
+        let key = self as *const Self as usize;
+
+        // init 안에서 다른 task local 에 접근할 수 있으므로 lock 을 잡지 않은 상태에서 만든다
+        if !task.locals.lock().unwrap().contains_key(&key) {
+            let value = Box::new((self.init)());
+            task.locals.lock().unwrap().entry(key).or_insert(value);
+        }
+        let ptr = {
+            let locals = task.locals.lock().unwrap();
+            locals[&key].downcast_ref::<T>().unwrap() as *const T
+        };
+        // 값은 Box 안에 있고 task 가 끝날 때까지 map 에서 제거되지 않으므로 주소가 유지된다.
+        // 같은 task 는 한번에 한 thread 에서만 poll 된다.
+        f(unsafe { &*ptr })
+    }
+}
+
+/// task local 변수 선언
+///
+/// ```ignore
+/// task_local! {
+///     static COUNTER: Cell<usize> = Cell::new(0);
+/// }
+/// COUNTER.with(|c| c.set(c.get() + 1));
+/// ```
+#[macro_export]
+macro_rules! task_local {
+    ($(#[$attr:meta])* $vis:vis static $name:ident: $t:ty = $init:expr;) => {
+        $(#[$attr])*
+        $vis static $name: $crate::runtime::multi::TaskLocalKey<$t> = {
+            fn __init() -> $t {
+                $init
+            }
+            $crate::runtime::multi::TaskLocalKey::new(__init)
+        };
+    };
+}
+
+/// p189 의 main 을 그대로 : get_spawner().spawn(Hello) 후 run()
+#[test]
+fn p189() {
+    use crate::guides::concurrent_programming::ch05_async::Hello;
+
+    let executor = Runtime::new(4);
+    executor.get_spawner().spawn(Hello::new());
+    executor.run();
+}
+
+/// task 안에서 spawn 한 task 들이 여러 worker 에 나뉘어 실행된다.
+#[test]
+fn work_stealing() {
+    use std::collections::HashSet;
+
+    const NUM_TASKS: usize = 1_000;
+
+    let rt = Runtime::new(4);
+    let threads = Arc::new(Mutex::new(HashSet::new()));
+    let th = threads.clone();
+
+    // 하나의 worker 에서 모든 task 를 spawn 하므로, 다른 worker 는 훔쳐와야만 실행할 수 있다
+    let total = rt.block_on(rt.spawn(async move {
+        let handles: Vec<_> = (0..NUM_TASKS)
+            .map(|i| {
+                let th = th.clone();
+                spawn(async move {
+                    thread::sleep(Duration::from_micros(100));
+                    th.lock().unwrap().insert(thread::current().id());
+                    i
+                })
+            })
+            .collect();
+
+        let mut total = 0;
+        for h in handles {
+            total += h.await;
+        }
+        total
+    }));
+
+    assert_eq!(total, NUM_TASKS * (NUM_TASKS - 1) / 2);
+    assert!(threads.lock().unwrap().len() > 1);
+}
+
+/// blocking 작업이 worker 를 막지 않는다. worker 1개로도 blocking 작업 대기 중에 다른 task 가 실행된다.
+#[test]
+fn blocking() {
+    let rt = Runtime::new(1);
+    let (tx, rx) = std::sync::mpsc::channel();
+
+    let result = rt.block_on(async move {
+        // 아래 task 가 보낼 때까지 thread 를 막는다 (worker 에서 실행되었다면 deadlock)
+        let blocking = spawn_blocking(move || rx.recv().unwrap() * 2);
+        spawn(async move { tx.send(21).unwrap() }).await;
+        blocking.await
+    });
+    assert_eq!(result, 42);
+}
+
+#[test]
+#[should_panic(expected = "boom")]
+fn panic_propagates() {
+    let rt = Runtime::new(2);
+    rt.block_on(rt.spawn(async { panic!("boom") }));
+}
+
+// 자신을 wake 하고 한번 Pending 을 반환 (p189 의 Hello 와 같은 방식)
+#[cfg(test)]
+async fn yield_now() {
+    let mut yielded = false;
+    futures::future::poll_fn(|cx| {
+        if yielded {
+            return Poll::Ready(());
+        }
+        yielded = true;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    })
+    .await
+}
+
+/// 같은 task 는 worker 가 바뀌어도 같은 값을, 다른 task 는 다른 값을 본다.
+#[test]
+fn task_local() {
+    use std::cell::Cell;
+
+    crate::task_local! {
+        static COUNTER: Cell<usize> = Cell::new(0);
+    }
+
+    let rt = Runtime::new(4);
+    let handles: Vec<_> = (0..8)
+        .map(|i| {
+            rt.spawn(async move {
+                for _ in 0..i {
+                    COUNTER.with(|c| c.set(c.get() + 1));
+                    // 다시 실행 큐에 들어가서 다른 worker 가 poll 할 수도 있다
+                    yield_now().await;
+                }
+                COUNTER.with(|c| c.get())
+            })
+        })
+        .collect();
+
+    for (i, h) in handles.into_iter().enumerate() {
+        assert_eq!(rt.block_on(h), i);
+    }
+}
+
+/// 계속 자신을 wake 하는 task 들 : poll 중의 wake 는 poll 이 끝난 뒤에 다시 queue 에 들어간다
+#[test]
+fn self_wake() {
+    use std::sync::atomic::AtomicUsize;
+
+    const NUM_TASKS: usize = 64;
+    const NUM_YIELDS: usize = 1_000;
+
+    let rt = Runtime::new(4);
+    let polls = Arc::new(AtomicUsize::new(0));
+    for _ in 0..NUM_TASKS {
+        let polls = polls.clone();
+        rt.spawn(async move {
+            for _ in 0..NUM_YIELDS {
+                polls.fetch_add(1, Ordering::Relaxed);
+                yield_now().await;
+            }
+        });
+    }
+    rt.run();
+    assert_eq!(polls.load(Ordering::Relaxed), NUM_TASKS * NUM_YIELDS);
+}
+
+/// Runtime 을 drop 한 뒤에 wake 된 task 도 future 와 함께 해제된다
+#[test]
+fn no_leak_after_shutdown() {
+    use crate::alloc::DropTracker;
+
+    let tracker = DropTracker::new();
+    let waker = Arc::new(Mutex::new(None::<Waker>));
+    {
+        let rt = Runtime::new(2);
+        for _ in 0..4 {
+            let value = tracker.track(());
+            let waker = waker.clone();
+            // 끝나지 않고 waker 만 밖에 남긴다
+            rt.spawn(futures::future::poll_fn(move |cx| {
+                let _ = &value;
+                *waker.lock().unwrap() = Some(cx.waker().clone());
+                Poll::<()>::Pending
+            }));
+        }
+        // 하나는 queue 에 남아있는 상태로 drop 될 수도 있다
+        rt.spawn(yield_now());
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    let w = waker.lock().unwrap().take().unwrap();
+    w.wake();
+    assert_eq!(tracker.alive(), 0);
+}
+
+/// tokio runtime 과 비교 : task spawn + join 처리량
+/// cargo test --release bench_spawn -- --ignored --nocapture
+#[test]
+#[ignore]
+fn bench_spawn() {
+    use std::time::Instant;
+
+    const NUM_TASKS: usize = 100_000;
+    let workers = thread::available_parallelism().map_or(1, |n| n.get());
+
+    let rt = Runtime::new(workers);
+    let start = Instant::now();
+    rt.block_on(async {
+        let handles: Vec<_> = (0..NUM_TASKS).map(|i| spawn(async move { i })).collect();
+        for h in handles {
+            h.await;
+        }
+    });
+    println!("runtime::multi : {:?}", start.elapsed());
+
+    let tokio = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(workers)
+        .build()
+        .unwrap();
+    let start = Instant::now();
+    tokio.block_on(async {
+        let handles: Vec<_> = (0..NUM_TASKS).map(|i| tokio::spawn(async move { i })).collect();
+        for h in handles {
+            h.await.unwrap();
+        }
+    });
+    println!("tokio          : {:?}", start.elapsed());
+}
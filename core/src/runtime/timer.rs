@@ -0,0 +1,149 @@
+use std::task::Waker;
+use std::time::{Duration, Instant};
+
+/// hashed timing wheel
+///
+/// 시간을 tick 단위로 나누고, 만료 tick 을 slot 수로 나눈 나머지 slot 에 timer 를 넣는다.
+/// advance 는 지난번 이후 지나간 tick 의 slot 만 확인하므로 timer 수가 많아도 비용이 일정하다.
+/// (한 바퀴 이상 뒤의 timer 는 같은 slot 에 있지만 deadline_tick 이 아직 오지 않았으므로 남겨둔다)
+pub(crate) struct TimerWheel {
+    start: Instant,
+    tick: Duration,
+    slots: Vec<Vec<Entry>>,
+    current: u64, // 아직 처리하지 않은 가장 이른 tick
+    next_id: u64,
+}
+
+struct Entry {
+    id: u64,
+    deadline_tick: u64,
+    waker: Waker,
+}
+
+/// 등록한 timer 를 취소할 때 사용
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct TimerKey {
+    id: u64,
+    deadline_tick: u64,
+}
+
+const DEFAULT_TICK: Duration = Duration::from_millis(1);
+const DEFAULT_SLOTS: usize = 256;
+
+impl TimerWheel {
+    pub(crate) fn new() -> Self {
+        TimerWheel::with_tick(DEFAULT_TICK, DEFAULT_SLOTS)
+    }
+
+    pub(crate) fn with_tick(tick: Duration, slots: usize) -> Self {
+        assert!(!tick.is_zero() && slots > 0);
+        TimerWheel {
+            start: Instant::now(),
+            tick,
+            slots: (0..slots).map(|_| Vec::new()).collect(),
+            current: 0,
+            next_id: 0,
+        }
+    }
+
+    // deadline 이 속한 tick 보다 일찍 깨우지 않도록 올림
+    fn tick_of(&self, deadline: Instant) -> u64 {
+        let elapsed = deadline.saturating_duration_since(self.start).as_nanos();
+        let tick = self.tick.as_nanos();
+        elapsed.div_ceil(tick) as u64
+    }
+
+    fn instant_of(&self, tick: u64) -> Instant {
+        self.start + Duration::from_nanos((self.tick.as_nanos() * tick as u128) as u64)
+    }
+
+    fn slot(&self, tick: u64) -> usize {
+        (tick % self.slots.len() as u64) as usize
+    }
+
+    pub(crate) fn insert(&mut self, deadline: Instant, waker: Waker) -> TimerKey {
+        // 이미 지나간 tick 이면 다음 advance 에서 바로 만료되도록
+        let deadline_tick = self.tick_of(deadline).max(self.current);
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let slot = self.slot(deadline_tick);
+        self.slots[slot].push(Entry { id, deadline_tick, waker });
+        TimerKey { id, deadline_tick }
+    }
+
+    /// 아직 만료되지 않은 timer 를 취소. 이미 만료되었으면 false
+    pub(crate) fn remove(&mut self, key: TimerKey) -> bool {
+        let slot = self.slot(key.deadline_tick);
+        let entries = &mut self.slots[slot];
+        match entries.iter().position(|e| e.id == key.id) {
+            Some(pos) => {
+                entries.swap_remove(pos);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// now 까지 만료된 timer 의 waker 를 꺼낸다.
+    /// (wake 하는 도중 다른 timer 가 등록될 수 있으므로 여기서 직접 깨우지 않는다)
+    pub(crate) fn advance(&mut self, now: Instant) -> Vec<Waker> {
+        let now_tick = now.saturating_duration_since(self.start).as_nanos() / self.tick.as_nanos();
+        let now_tick = now_tick as u64;
+        let mut expired = Vec::new();
+        if now_tick < self.current {
+            return expired;
+        }
+
+        // 한 바퀴 이상 지났으면 모든 slot 을 한번씩만 확인하면 된다
+        let n = (now_tick - self.current + 1).min(self.slots.len() as u64);
+        for t in self.current..self.current + n {
+            let slot = self.slot(t);
+            let entries = &mut self.slots[slot];
+            let mut i = 0;
+            while i < entries.len() {
+                if entries[i].deadline_tick <= now_tick {
+                    expired.push(entries.swap_remove(i).waker);
+                } else {
+                    i += 1;
+                }
+            }
+        }
+        self.current = now_tick + 1;
+        expired
+    }
+
+    /// 가장 이른 timer 의 만료 시각 (park_timeout 에 사용)
+    pub(crate) fn next_deadline(&self) -> Option<Instant> {
+        self.slots
+            .iter()
+            .flatten()
+            .map(|e| e.deadline_tick)
+            .min()
+            .map(|tick| self.instant_of(tick))
+    }
+}
+
+#[test]
+fn wheel() {
+    use futures::task::noop_waker;
+
+    // tick 1ms, slot 4개 : 2ms 와 6ms 는 같은 slot 에 들어간다
+    let mut wheel = TimerWheel::with_tick(Duration::from_millis(1), 4);
+    let start = wheel.start;
+
+    let k2 = wheel.insert(start + Duration::from_millis(2), noop_waker());
+    wheel.insert(start + Duration::from_millis(6), noop_waker());
+    let k9 = wheel.insert(start + Duration::from_millis(9), noop_waker());
+    assert_eq!(wheel.next_deadline(), Some(start + Duration::from_millis(2)));
+
+    assert_eq!(wheel.advance(start + Duration::from_millis(1)).len(), 0);
+    assert_eq!(wheel.advance(start + Duration::from_millis(3)).len(), 1);
+    assert!(!wheel.remove(k2));
+    assert_eq!(wheel.next_deadline(), Some(start + Duration::from_millis(6)));
+
+    assert!(wheel.remove(k9));
+    // 한 바퀴 이상 건너뛰어도 남은 timer 를 놓치지 않는다
+    assert_eq!(wheel.advance(start + Duration::from_millis(20)).len(), 1);
+    assert_eq!(wheel.next_deadline(), None);
+}
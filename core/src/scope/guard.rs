@@ -0,0 +1,225 @@
+use std::fmt;
+use std::marker::PhantomData;
+use std::mem::ManuallyDrop;
+use std::ops::{Deref, DerefMut};
+use std::ptr;
+use std::thread;
+
+/// guard 가 drop 될 때 closure 를 실행할지 정하는 전략
+pub trait Strategy {
+    fn should_run() -> bool;
+}
+
+/// 정상 종료 (early return 포함) 와 panic 모두에서 실행 (`finally`)
+#[derive(Debug)]
+pub enum Always {}
+
+/// 정상 종료에서만 실행 (commit)
+#[derive(Debug)]
+pub enum OnSuccess {}
+
+/// panic 으로 unwind 할 때만 실행 (rollback)
+#[derive(Debug)]
+pub enum OnUnwind {}
+
+impl Strategy for Always {
+    fn should_run() -> bool {
+        true
+    }
+}
+
+impl Strategy for OnSuccess {
+    fn should_run() -> bool {
+        !thread::panicking()
+    }
+}
+
+impl Strategy for OnUnwind {
+    fn should_run() -> bool {
+        thread::panicking()
+    }
+}
+
+/// 값을 소유하고, drop 될 때 그 값을 closure 에 넘겨 실행하는 guard
+///
+/// - Deref / DerefMut 로 안의 값을 그대로 쓸 수 있다.
+/// - `dismiss` 로 closure 를 실행하지 않고 값을 돌려받는다 (성공했으니 해제하지 않는 경우).
+/// - closure 가 unwind 중에 다시 panic 하면 process 가 abort 되므로, S = Always / OnUnwind 의 closure 는 panic 하지 않아야 한다.
+pub struct ScopeGuard<T, F: FnOnce(T), S: Strategy = Always> {
+    value: ManuallyDrop<T>,
+    f: ManuallyDrop<F>,
+    strategy: PhantomData<fn() -> S>,
+}
+
+impl<T, F: FnOnce(T)> ScopeGuard<T, F> {
+    pub fn new(value: T, f: F) -> Self {
+        ScopeGuard::with_strategy(value, f)
+    }
+}
+
+impl<T, F: FnOnce(T), S: Strategy> ScopeGuard<T, F, S> {
+    pub fn with_strategy(value: T, f: F) -> Self {
+        ScopeGuard {
+            value: ManuallyDrop::new(value),
+            f: ManuallyDrop::new(f),
+            strategy: PhantomData,
+        }
+    }
+
+    /// closure 를 실행하지 않고 (drop 만 하고) 값을 꺼낸다
+    pub fn dismiss(self) -> T {
+        let mut guard = ManuallyDrop::new(self);
+        // guard 의 Drop 은 실행되지 않으므로 두 field 를 한번씩만 꺼낸다
+        unsafe {
+            drop(ManuallyDrop::take(&mut guard.f));
+            ptr::read(&*guard.value)
+        }
+    }
+}
+
+impl<T, F: FnOnce(T), S: Strategy> Drop for ScopeGuard<T, F, S> {
+    fn drop(&mut self) {
+        // drop 은 한번만 호출되므로 여기서 꺼내는 것이 마지막 사용
+        let (value, f) = unsafe {
+            (
+                ManuallyDrop::take(&mut self.value),
+                ManuallyDrop::take(&mut self.f),
+            )
+        };
+        if S::should_run() {
+            f(value);
+        }
+    }
+}
+
+impl<T, F: FnOnce(T), S: Strategy> Deref for ScopeGuard<T, F, S> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T, F: FnOnce(T), S: Strategy> DerefMut for ScopeGuard<T, F, S> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+impl<T: fmt::Debug, F: FnOnce(T), S: Strategy> fmt::Debug for ScopeGuard<T, F, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ScopeGuard")
+            .field("value", &*self.value)
+            .finish()
+    }
+}
+
+/// 항상 실행하는 guard
+pub fn guard<T, F: FnOnce(T)>(value: T, f: F) -> ScopeGuard<T, F, Always> {
+    ScopeGuard::with_strategy(value, f)
+}
+
+/// 정상 종료에서만 실행하는 guard
+pub fn guard_on_success<T, F: FnOnce(T)>(value: T, f: F) -> ScopeGuard<T, F, OnSuccess> {
+    ScopeGuard::with_strategy(value, f)
+}
+
+/// panic 으로 unwind 할 때만 실행하는 guard
+pub fn guard_on_unwind<T, F: FnOnce(T)>(value: T, f: F) -> ScopeGuard<T, F, OnUnwind> {
+    ScopeGuard::with_strategy(value, f)
+}
+
+/// scope 를 벗어날 때 (panic 포함) 실행할 code 를 등록한다. 여러개면 등록의 역순으로 실행.
+///
+/// ```ignore
+/// let file = File::create(path)?;
+/// defer! { let _ = fs::remove_file(path); }
+/// ```
+#[macro_export]
+macro_rules! defer {
+    ($($body:tt)*) => {
+        let _guard = $crate::scope::guard((), |()| { $($body)* });
+    };
+}
+
+/// panic 으로 scope 를 벗어날 때만 실행 (rollback)
+#[macro_export]
+macro_rules! defer_on_unwind {
+    ($($body:tt)*) => {
+        let _guard = $crate::scope::guard_on_unwind((), |()| { $($body)* });
+    };
+}
+
+/// 정상적으로 scope 를 벗어날 때만 실행
+#[macro_export]
+macro_rules! defer_on_success {
+    ($($body:tt)*) => {
+        let _guard = $crate::scope::guard_on_success((), |()| { $($body)* });
+    };
+}
+
+/// 등록의 역순으로, early return 에서도 실행된다
+#[test]
+fn defer_order() {
+    use std::cell::RefCell;
+
+    let log = RefCell::new(Vec::new());
+    let run = |early: bool| {
+        defer! { log.borrow_mut().push("first"); }
+        defer! { log.borrow_mut().push("second"); }
+        if early {
+            return;
+        }
+        log.borrow_mut().push("body");
+    };
+    run(false);
+    assert_eq!(*log.borrow(), ["body", "second", "first"]);
+    log.borrow_mut().clear();
+    run(true);
+    assert_eq!(*log.borrow(), ["second", "first"]);
+}
+
+/// 값을 소유하는 guard 와 dismiss
+#[test]
+fn dismiss() {
+    let tracker = crate::alloc::DropTracker::new();
+    let released = std::cell::Cell::new(0);
+
+    {
+        let mut v = guard(vec![1, 2], |v| released.set(v.len()));
+        v.push(3);
+    }
+    assert_eq!(released.get(), 3);
+
+    let v = guard(tracker.track(vec![1]), |_| released.set(0));
+    let v = v.dismiss();
+    assert_eq!(released.get(), 3);
+    assert_eq!(tracker.alive(), 1);
+    drop(v);
+    assert_eq!(tracker.alive(), 0);
+}
+
+/// 성공 / unwind 에 따라 commit 과 rollback 중 하나만 실행
+#[test]
+fn strategies() {
+    use std::panic::{self, AssertUnwindSafe};
+    use std::sync::Mutex;
+
+    let log = Mutex::new(Vec::new());
+    let transaction = |fail: bool| {
+        let _commit = guard_on_success((), |()| log.lock().unwrap().push("commit"));
+        let _rollback = guard_on_unwind((), |()| log.lock().unwrap().push("rollback"));
+        let _close = guard((), |()| log.lock().unwrap().push("close"));
+        if fail {
+            panic!("transaction failed");
+        }
+    };
+
+    transaction(false);
+    assert_eq!(*log.lock().unwrap(), ["close", "commit"]);
+    log.lock().unwrap().clear();
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| transaction(true)));
+    assert!(result.is_err());
+    assert_eq!(*log.lock().unwrap(), ["close", "rollback"]);
+}
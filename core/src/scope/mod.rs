@@ -0,0 +1,6 @@
+/// drop 될 때 closure 를 실행하는 ScopeGuard 와 defer! (guides 의 idioms1 Finalizer 를 일반화)
+pub mod guard;
+
+pub use guard::{
+    guard, guard_on_success, guard_on_unwind, Always, OnSuccess, OnUnwind, ScopeGuard, Strategy,
+};
@@ -0,0 +1,295 @@
+use std::collections::VecDeque;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+use futures::Stream;
+
+pub use super::channel::{RecvError, SendError, TryRecvError, TrySendError};
+
+/// sync::channel 의 async 버전
+///
+/// 길이 제한 / disconnect 규칙은 blocking channel 과 같지만, 대기할 때 Condvar 로 thread 를 재우지 않고
+/// 자신의 Waker 를 등록한 뒤 Poll::Pending 을 반환한다. (ch05 p189 의 wake_by_ref 와 같은 흐름)
+/// 상대편이 queue 를 변경하면 등록된 Waker 를 깨워서 executor 가 다시 poll 하도록 한다.
+pub fn channel<T>(max: usize) -> (Sender<T>, Receiver<T>) {
+    assert!(max > 0);
+    let chan = Arc::new(Channel {
+        state: Mutex::new(State {
+            buf: VecDeque::with_capacity(max),
+            senders: 1,
+            receivers: 1,
+            send_wakers: Vec::new(),
+            recv_wakers: Vec::new(),
+        }),
+        max,
+    });
+    let tx = Sender { chan: chan.clone() };
+    let rx = Receiver { chan };
+
+    (tx, rx)
+}
+
+struct Channel<T> {
+    state: Mutex<State<T>>,
+    max: usize,
+}
+
+struct State<T> {
+    buf: VecDeque<T>,
+    senders: usize,
+    receivers: usize,
+    send_wakers: Vec<Waker>, // queue 가 가득 차서 대기 중인 send future
+    recv_wakers: Vec<Waker>, // queue 가 비어서 대기 중인 recv future
+}
+
+// 같은 task 가 여러번 poll 되어도 waker 가 중복해서 쌓이지 않도록
+fn register(wakers: &mut Vec<Waker>, waker: &Waker) {
+    if !wakers.iter().any(|w| w.will_wake(waker)) {
+        wakers.push(waker.clone());
+    }
+}
+
+// 깨운 future 가 그 사이 drop 되었을 수도 있으므로 하나만이 아니라 모두 깨운다.
+fn wake_all(wakers: &mut Vec<Waker>) {
+    for w in wakers.drain(..) {
+        w.wake();
+    }
+}
+
+pub struct Sender<T> {
+    chan: Arc<Channel<T>>,
+}
+
+impl<T> Sender<T> {
+    /// queue 가 가득 차 있으면 빌 때까지 task 를 대기 (thread 는 block 되지 않음)
+    pub fn send(&self, data: T) -> Send<'_, T> {
+        Send { tx: self, data: Some(data) }
+    }
+
+    pub fn try_send(&self, data: T) -> Result<(), TrySendError<T>> {
+        let mut state = self.chan.state.lock().unwrap();
+        if state.receivers == 0 {
+            return Err(TrySendError::Disconnected(data));
+        }
+        if state.buf.len() >= self.chan.max {
+            return Err(TrySendError::Full(data));
+        }
+        state.buf.push_back(data);
+        wake_all(&mut state.recv_wakers);
+        Ok(())
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        self.chan.state.lock().unwrap().senders += 1;
+        Sender { chan: self.chan.clone() }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let mut state = self.chan.state.lock().unwrap();
+        state.senders -= 1;
+        if state.senders == 0 {
+            wake_all(&mut state.recv_wakers);
+        }
+    }
+}
+
+/// Sender::send 가 반환하는 Future
+pub struct Send<'a, T> {
+    tx: &'a Sender<T>,
+    data: Option<T>,
+}
+
+// data 를 pin 된 상태로 다루지 않으므로 (꺼내서 queue 로 move 할 뿐) Unpin
+impl<'a, T> Unpin for Send<'a, T> {}
+
+impl<'a, T> Future for Send<'a, T> {
+    type Output = Result<(), SendError<T>>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let data = self.data.take().expect("Send polled after completion");
+        let mut state = self.tx.chan.state.lock().unwrap();
+        if state.receivers == 0 {
+            return Poll::Ready(Err(SendError(data)));
+        }
+        if state.buf.len() < self.tx.chan.max {
+            state.buf.push_back(data);
+            wake_all(&mut state.recv_wakers);
+            return Poll::Ready(Ok(()));
+        }
+
+        register(&mut state.send_wakers, cx.waker());
+        drop(state);
+        self.data = Some(data);
+        Poll::Pending
+    }
+}
+
+pub struct Receiver<T> {
+    chan: Arc<Channel<T>>,
+}
+
+impl<T> Receiver<T> {
+    /// queue 가 비어 있으면 task 를 대기. sender 가 모두 사라지고 queue 도 비었으면 Disconnected
+    pub fn recv(&self) -> Recv<'_, T> {
+        Recv { rx: self }
+    }
+
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        let mut state = self.chan.state.lock().unwrap();
+        if let Some(data) = state.buf.pop_front() {
+            wake_all(&mut state.send_wakers);
+            return Ok(data);
+        }
+        if state.senders == 0 {
+            Err(TryRecvError::Disconnected)
+        } else {
+            Err(TryRecvError::Empty)
+        }
+    }
+
+    /// Recv future 와 Stream 이 같이 사용하는 poll 함수
+    pub fn poll_recv(&self, cx: &mut Context<'_>) -> Poll<Result<T, RecvError>> {
+        let mut state = self.chan.state.lock().unwrap();
+        if let Some(data) = state.buf.pop_front() {
+            wake_all(&mut state.send_wakers);
+            return Poll::Ready(Ok(data));
+        }
+        if state.senders == 0 {
+            return Poll::Ready(Err(RecvError));
+        }
+
+        register(&mut state.recv_wakers, cx.waker());
+        Poll::Pending
+    }
+
+    pub fn len(&self) -> usize {
+        self.chan.state.lock().unwrap().buf.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T> Clone for Receiver<T> {
+    fn clone(&self) -> Self {
+        self.chan.state.lock().unwrap().receivers += 1;
+        Receiver { chan: self.chan.clone() }
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        let mut state = self.chan.state.lock().unwrap();
+        state.receivers -= 1;
+        if state.receivers == 0 {
+            wake_all(&mut state.send_wakers);
+            // 더 이상 받을 수 없으므로 남은 data 를 바로 drop (lock 밖에서)
+            let buf = std::mem::take(&mut state.buf);
+            drop(state);
+            drop(buf);
+        }
+    }
+}
+
+/// Receiver::recv 가 반환하는 Future
+pub struct Recv<'a, T> {
+    rx: &'a Receiver<T>,
+}
+
+impl<'a, T> Future for Recv<'a, T> {
+    type Output = Result<T, RecvError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.rx.poll_recv(cx)
+    }
+}
+
+/// futures::StreamExt 와 같이 사용할 수 있도록 Stream 구현
+/// disconnect 되면 None 으로 stream 이 끝난다.
+impl<T> Stream for Receiver<T> {
+    type Item = T;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        self.poll_recv(cx).map(|r| r.ok())
+    }
+}
+
+/// 작은 queue 로 task 8개가 보내고 2개가 받는다.
+/// sender task 가 모두 끝나면 receiver 의 recv().await 가 Disconnected 로 끝나야 한다.
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn mpmc_tasks() {
+    const NUM_LOOP: usize = 1_000;
+    const NUM_TASKS: usize = 8;
+
+    let (tx, rx) = channel(4);
+
+    let mut receivers = Vec::new();
+    for _ in 0..2 {
+        let rx0 = rx.clone();
+        receivers.push(tokio::spawn(async move {
+            let mut cnt = 0;
+            while rx0.recv().await.is_ok() {
+                cnt += 1;
+            }
+            cnt
+        }));
+    }
+    drop(rx);
+
+    for i in 0..NUM_TASKS {
+        let tx0 = tx.clone();
+        tokio::spawn(async move {
+            for j in 0..NUM_LOOP {
+                tx0.send((i, j)).await.unwrap();
+            }
+        });
+    }
+    drop(tx);
+
+    let mut total = 0;
+    for r in receivers {
+        total += r.await.unwrap();
+    }
+    assert_eq!(total, NUM_TASKS * NUM_LOOP);
+}
+
+#[tokio::test]
+async fn stream() {
+    use futures::StreamExt;
+
+    let (tx, rx) = channel(2);
+    tokio::spawn(async move {
+        for i in 0..10 {
+            tx.send(i).await.unwrap();
+        }
+    });
+
+    let v: Vec<i32> = rx.map(|n| n * 2).collect().await;
+    assert_eq!(v, (0..10).map(|n| n * 2).collect::<Vec<_>>());
+}
+
+#[tokio::test]
+async fn disconnect() {
+    let (tx, rx) = channel::<u32>(1);
+    tx.send(1).await.unwrap();
+    assert!(matches!(tx.try_send(2), Err(TrySendError::Full(2))));
+
+    // queue 가 가득 찬 상태에서 대기 중인 send 는 receiver 가 사라지면 깨어나서 data 를 돌려받아야 한다.
+    let t = tokio::spawn(async move { tx.send(2).await });
+    tokio::task::yield_now().await;
+    drop(rx);
+    assert_eq!(t.await.unwrap().unwrap_err().0, 2);
+
+    let (tx, rx) = channel::<u32>(1);
+    tx.send(1).await.unwrap();
+    drop(tx);
+    assert_eq!(rx.recv().await, Ok(1));
+    assert_eq!(rx.recv().await, Err(RecvError));
+}
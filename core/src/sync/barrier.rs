@@ -0,0 +1,209 @@
+use crate::scope;
+use std::sync::{Condvar, Mutex};
+
+/// 재사용 가능한 (cyclic) barrier (ch03 p127 의 std::sync::Barrier 를 직접 구현)
+///
+/// parties 개의 thread 가 wait 에 도착하면 모두 풀어주고, 다음 세대 (generation) 로 넘어가 다시 사용할 수 있다.
+/// 마지막에 도착한 thread 가 leader 가 되어, 다른 thread 를 풀어주기 전에 barrier action 을 실행한다.
+/// (p127 의 영상처리 예라면 모든 thread 가 frame 처리를 끝낸 뒤 결과를 모아 출력하는 작업)
+pub struct Barrier {
+    state: Mutex<State>,
+    cond: Condvar,
+    parties: usize,
+    action: Option<Box<dyn Fn() + Send + Sync>>,
+}
+
+struct State {
+    count: usize,      // 이번 세대에 도착한 thread 수
+    generation: usize, // 모두 도착할 때마다 증가
+    released: usize,   // action 까지 끝나서 풀어준 세대 수 (generation 보다 늦을 수 있다)
+}
+
+/// wait 의 결과
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BarrierWaitResult {
+    leader: bool,
+    generation: usize,
+}
+
+impl BarrierWaitResult {
+    /// 마지막에 도착해서 barrier action 을 실행한 thread 이면 true (세대마다 하나)
+    pub fn is_leader(&self) -> bool {
+        self.leader
+    }
+
+    /// 통과한 세대 (0 부터)
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+}
+
+impl Barrier {
+    pub fn new(parties: usize) -> Self {
+        assert!(parties > 0);
+        Barrier {
+            state: Mutex::new(State {
+                count: 0,
+                generation: 0,
+                released: 0,
+            }),
+            cond: Condvar::new(),
+            parties,
+            action: None,
+        }
+    }
+
+    /// 모두 도착할 때마다 leader 가 action 을 실행한다.
+    pub fn with_action<F>(parties: usize, action: F) -> Self
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        Barrier {
+            action: Some(Box::new(action)),
+            ..Barrier::new(parties)
+        }
+    }
+
+    pub fn parties(&self) -> usize {
+        self.parties
+    }
+
+    pub fn wait(&self) -> BarrierWaitResult {
+        let mut state = self.state.lock().unwrap();
+        let generation = state.generation;
+        state.count += 1;
+
+        if state.count < self.parties {
+            // 다음 세대의 thread 와 섞이지 않도록 count 가 아니라 이 세대가 풀렸는지로 판단
+            while state.released <= generation {
+                state = self.cond.wait(state).unwrap();
+            }
+            return BarrierWaitResult {
+                leader: false,
+                generation,
+            };
+        }
+
+        // leader : lock 을 놓기 전에 다음 세대로 넘겨야, action 중에 도착한 thread 가 이 세대의 leader 가 되지 않는다
+        state.count = 0;
+        state.generation += 1;
+        drop(state);
+
+        // action 이 panic 해도 다른 thread 는 풀어주도록 drop 에서 이 세대를 풀어준다
+        let _release = scope::guard(self, |barrier| {
+            let mut state = barrier.state.lock().unwrap_or_else(|e| e.into_inner());
+            state.released = state.released.max(generation + 1);
+            barrier.cond.notify_all();
+        });
+        if let Some(action) = &self.action {
+            action();
+        }
+        BarrierWaitResult {
+            leader: true,
+            generation,
+        }
+    }
+}
+
+/// p127 과 같은 구성 : 12 개의 thread 를 3 개씩 barrier 로 통과시키고, 세대마다 leader 는 하나
+#[test]
+fn p127() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+
+    let actions = Arc::new(AtomicUsize::new(0));
+    let a = actions.clone();
+    let barrier = Arc::new(Barrier::with_action(3, move || {
+        a.fetch_add(1, Ordering::Relaxed);
+    }));
+    let leaders = Arc::new(AtomicUsize::new(0));
+
+    let mut v = Vec::new();
+    for _ in 0..12 {
+        let b = barrier.clone();
+        let l = leaders.clone();
+        let th = thread::spawn(move || {
+            if b.wait().is_leader() {
+                l.fetch_add(1, Ordering::Relaxed);
+            }
+        });
+        v.push(th);
+    }
+
+    for th in v {
+        th.join().unwrap();
+    }
+    assert_eq!(leaders.load(Ordering::Relaxed), 4);
+    assert_eq!(actions.load(Ordering::Relaxed), 4);
+}
+
+/// 같은 thread 들이 barrier 를 반복해서 사용 (cyclic) : 세대마다 모든 thread 의 작업이 끝난 뒤에 다음 세대로
+#[test]
+fn cyclic() {
+    use std::sync::Arc;
+    use std::thread;
+
+    const NUM_THREADS: usize = 4;
+    const NUM_ROUNDS: usize = 100;
+
+    // 세대마다 leader 가 모든 thread 의 결과가 모였는지 확인
+    let results = Arc::new(Mutex::new(Vec::new()));
+    let r = results.clone();
+    let barrier = Arc::new(Barrier::with_action(NUM_THREADS, move || {
+        let mut r = r.lock().unwrap();
+        assert_eq!(r.len(), NUM_THREADS);
+        r.clear();
+    }));
+
+    let mut v = Vec::new();
+    for i in 0..NUM_THREADS {
+        let b = barrier.clone();
+        let results = results.clone();
+        let th = thread::spawn(move || {
+            for round in 0..NUM_ROUNDS {
+                results.lock().unwrap().push(i);
+                assert_eq!(b.wait().generation(), round);
+            }
+        });
+        v.push(th);
+    }
+
+    for th in v {
+        th.join().unwrap();
+    }
+}
+
+/// parties 보다 많은 thread 가 동시에 도착해도 세대마다 정확히 parties 개씩 통과한다
+#[test]
+fn more_threads_than_parties() {
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    const PARTIES: usize = 3;
+    const NUM_THREADS: usize = 30;
+
+    for _ in 0..20 {
+        // action 을 느리게 해서 leader 가 action 을 실행하는 동안 다음 세대의 thread 가 도착하도록
+        let barrier = Arc::new(Barrier::with_action(PARTIES, || {
+            thread::sleep(Duration::from_micros(100))
+        }));
+        let v: Vec<_> = (0..NUM_THREADS)
+            .map(|_| {
+                let b = barrier.clone();
+                thread::spawn(move || b.wait())
+            })
+            .collect();
+
+        let mut released = [0; NUM_THREADS / PARTIES];
+        let mut leaders = [0; NUM_THREADS / PARTIES];
+        for th in v {
+            let result = th.join().unwrap();
+            released[result.generation()] += 1;
+            leaders[result.generation()] += usize::from(result.is_leader());
+        }
+        assert_eq!(released, [PARTIES; NUM_THREADS / PARTIES]);
+        assert_eq!(leaders, [1; NUM_THREADS / PARTIES]);
+    }
+}
@@ -0,0 +1,483 @@
+use crate::sync::facade::{Condvar, Mutex};
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// 길이가 유한한 MPMC channel (ch03 p128 의 channel 을 정리)
+///
+/// ch03 의 channel 은 Sender 가 모두 drop 되어도 recv() 가 계속 대기했다.
+/// 여기서는 Sender / Receiver 의 수를 같이 세어서 worker pool 을 깔끔하게 종료할 수 있도록 한다.
+/// - Sender 가 모두 사라지면 queue 를 비운 뒤 recv() 가 RecvError
+/// - Receiver 가 모두 사라지면 send() 가 SendError(data) (보내려던 data 를 돌려줌)
+///
+/// ch03 은 Semaphore 로 queue 길이를 제한했지만, semaphore 에서 대기하는 sender 는
+/// 상대방이 사라져도 깨울 방법이 없으므로 queue 와 같은 Mutex 안에서 길이를 보고
+/// not_full / not_empty 두개의 Condvar 로 대기한다.
+pub fn channel<T>(max: usize) -> (Sender<T>, Receiver<T>) {
+    assert!(max > 0);
+    let chan = Arc::new(Channel {
+        state: Mutex::new(State {
+            buf: VecDeque::with_capacity(max),
+            senders: 1,
+            receivers: 1,
+        }),
+        not_empty: Condvar::new(),
+        not_full: Condvar::new(),
+        max,
+    });
+    let tx = Sender { chan: chan.clone() };
+    let rx = Receiver { chan };
+
+    (tx, rx)
+}
+
+struct Channel<T> {
+    state: Mutex<State<T>>,
+    not_empty: Condvar, // queue 를 읽는 thread 가 대기
+    not_full: Condvar,  // queue 에 쓰는 thread 가 대기
+    max: usize,
+}
+
+struct State<T> {
+    buf: VecDeque<T>,
+    senders: usize,
+    receivers: usize,
+}
+
+pub struct Sender<T> {
+    chan: Arc<Channel<T>>,
+}
+
+impl<T> Sender<T> {
+    /// queue 가 가득 차 있으면 빌 때까지 대기
+    pub fn send(&self, data: T) -> Result<(), SendError<T>> {
+        let mut state = self.chan.state.lock().unwrap();
+        loop {
+            if state.receivers == 0 {
+                return Err(SendError(data));
+            }
+            if state.buf.len() < self.chan.max {
+                state.buf.push_back(data);
+                self.chan.not_empty.notify_one();
+                return Ok(());
+            }
+            state = self.chan.not_full.wait(state).unwrap();
+        }
+    }
+
+    /// 대기하지 않고 바로 시도
+    pub fn try_send(&self, data: T) -> Result<(), TrySendError<T>> {
+        let mut state = self.chan.state.lock().unwrap();
+        if state.receivers == 0 {
+            return Err(TrySendError::Disconnected(data));
+        }
+        if state.buf.len() >= self.chan.max {
+            return Err(TrySendError::Full(data));
+        }
+        state.buf.push_back(data);
+        self.chan.not_empty.notify_one();
+        Ok(())
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        self.chan.state.lock().unwrap().senders += 1;
+        Sender { chan: self.chan.clone() }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let mut state = self.chan.state.lock().unwrap();
+        state.senders -= 1;
+        if state.senders == 0 {
+            // 대기 중인 receiver 들이 disconnect 를 확인할 수 있도록 모두 깨움
+            self.chan.not_empty.notify_all();
+        }
+    }
+}
+
+pub struct Receiver<T> {
+    chan: Arc<Channel<T>>,
+}
+
+impl<T> Receiver<T> {
+    /// queue 가 비어 있으면 대기. sender 가 모두 사라지고 queue 도 비었으면 RecvError
+    pub fn recv(&self) -> Result<T, RecvError> {
+        let mut state = self.chan.state.lock().unwrap();
+        loop {
+            if let Some(data) = state.buf.pop_front() {
+                self.chan.not_full.notify_one();
+                return Ok(data);
+            }
+            if state.senders == 0 {
+                return Err(RecvError);
+            }
+            state = self.chan.not_empty.wait(state).unwrap();
+        }
+    }
+
+    /// 대기하지 않고 바로 시도. 비어 있으면 Empty
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        let mut state = self.chan.state.lock().unwrap();
+        if let Some(data) = state.buf.pop_front() {
+            self.chan.not_full.notify_one();
+            return Ok(data);
+        }
+        if state.senders == 0 {
+            Err(TryRecvError::Disconnected)
+        } else {
+            Err(TryRecvError::Empty)
+        }
+    }
+
+    /// 최대 timeout 만큼만 대기. 시간이 지나면 Timeout
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        let deadline = Instant::now() + timeout;
+        let mut state = self.chan.state.lock().unwrap();
+        loop {
+            if let Some(data) = state.buf.pop_front() {
+                self.chan.not_full.notify_one();
+                return Ok(data);
+            }
+            if state.senders == 0 {
+                return Err(RecvTimeoutError::Disconnected);
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(RecvTimeoutError::Timeout);
+            }
+            state = self.chan.not_empty.wait_timeout(state, deadline - now).unwrap().0;
+        }
+    }
+
+    /// sender 가 모두 사라질 때까지 recv() 를 반복하는 iterator
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter { rx: self }
+    }
+
+    /// 현재 queue 에 쌓여있는 것만 꺼내는 iterator (대기하지 않음)
+    pub fn try_iter(&self) -> TryIter<'_, T> {
+        TryIter { rx: self }
+    }
+
+    pub fn len(&self) -> usize {
+        self.chan.state.lock().unwrap().buf.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Receiver 도 clone 가능 (MPMC). 하나의 data 는 receiver 중 하나만 받는다.
+impl<T> Clone for Receiver<T> {
+    fn clone(&self) -> Self {
+        self.chan.state.lock().unwrap().receivers += 1;
+        Receiver { chan: self.chan.clone() }
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        let mut state = self.chan.state.lock().unwrap();
+        state.receivers -= 1;
+        if state.receivers == 0 {
+            self.chan.not_full.notify_all();
+            // 더 이상 받을 수 없으므로 남은 data 를 바로 drop (lock 밖에서)
+            let buf = std::mem::take(&mut state.buf);
+            drop(state);
+            drop(buf);
+        }
+    }
+}
+
+pub struct Iter<'a, T> {
+    rx: &'a Receiver<T>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.rx.recv().ok()
+    }
+}
+
+pub struct TryIter<'a, T> {
+    rx: &'a Receiver<T>,
+}
+
+impl<'a, T> Iterator for TryIter<'a, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.rx.try_recv().ok()
+    }
+}
+
+pub struct IntoIter<T> {
+    rx: Receiver<T>,
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.rx.recv().ok()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Receiver<T> {
+    type Item = T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<T> IntoIterator for Receiver<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter { rx: self }
+    }
+}
+
+/// receiver 가 모두 사라져 보내지 못한 data 를 돌려준다.
+pub struct SendError<T>(pub T);
+
+impl<T> fmt::Display for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "sending on a disconnected channel")
+    }
+}
+
+// T 가 Debug 가 아니어도 unwrap() 할 수 있도록 직접 구현
+impl<T> fmt::Debug for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SendError {{ .. }}")
+    }
+}
+
+impl<T> Error for SendError<T> {}
+
+pub enum TrySendError<T> {
+    Full(T),
+    Disconnected(T),
+}
+
+impl<T> TrySendError<T> {
+    pub fn into_inner(self) -> T {
+        match self {
+            TrySendError::Full(data) | TrySendError::Disconnected(data) => data,
+        }
+    }
+}
+
+impl<T> fmt::Display for TrySendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrySendError::Full(_) => write!(f, "sending on a full channel"),
+            TrySendError::Disconnected(_) => write!(f, "sending on a disconnected channel"),
+        }
+    }
+}
+
+impl<T> fmt::Debug for TrySendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrySendError::Full(_) => write!(f, "Full(..)"),
+            TrySendError::Disconnected(_) => write!(f, "Disconnected(..)"),
+        }
+    }
+}
+
+impl<T> Error for TrySendError<T> {}
+
+/// recv 의 에러. sender 가 모두 사라졌고 queue 도 비어 있을 때만 실패한다.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecvError;
+
+impl fmt::Display for RecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "receiving on a disconnected channel")
+    }
+}
+
+impl Error for RecvError {}
+
+/// try_recv 의 에러
+/// - Empty        : queue 가 비어 있음 (sender 는 남아 있음)
+/// - Disconnected : sender 가 모두 사라졌고 queue 도 비어 있음
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TryRecvError {
+    Empty,
+    Disconnected,
+}
+
+impl fmt::Display for TryRecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TryRecvError::Empty => write!(f, "receiving on an empty channel"),
+            TryRecvError::Disconnected => write!(f, "receiving on a disconnected channel"),
+        }
+    }
+}
+
+impl Error for TryRecvError {}
+
+impl From<RecvError> for TryRecvError {
+    fn from(_: RecvError) -> Self {
+        TryRecvError::Disconnected
+    }
+}
+
+/// recv_timeout 의 에러
+/// - Timeout      : 시간 안에 data 가 오지 않음
+/// - Disconnected : sender 가 모두 사라졌고 queue 도 비어 있음
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecvTimeoutError {
+    Timeout,
+    Disconnected,
+}
+
+impl fmt::Display for RecvTimeoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecvTimeoutError::Timeout => write!(f, "timed out waiting on channel"),
+            RecvTimeoutError::Disconnected => write!(f, "receiving on a disconnected channel"),
+        }
+    }
+}
+
+impl Error for RecvTimeoutError {}
+
+impl From<RecvError> for RecvTimeoutError {
+    fn from(_: RecvError) -> Self {
+        RecvTimeoutError::Disconnected
+    }
+}
+
+/// ch03 p128 의 exe() 와 같은 구성 (sender 8개) 에 receiver 를 2개로 늘리고,
+/// sender 가 모두 끝나면 receiver 의 for 문이 스스로 끝나는지 확인
+#[test]
+fn p128_mpmc() {
+    use std::thread;
+
+    const NUM_LOOP: usize = 10_000;
+    const NUM_THREADS: usize = 8;
+
+    let (tx, rx) = channel(4);
+    let mut receivers = Vec::new();
+
+    for _ in 0..2 {
+        let rx0 = rx.clone();
+        let t = thread::spawn(move || {
+            let mut cnt = 0;
+            for _ in rx0.iter() {
+                cnt += 1;
+            }
+            cnt
+        });
+        receivers.push(t);
+    }
+    drop(rx);
+
+    let mut senders = Vec::new();
+    for i in 0..NUM_THREADS {
+        let tx0 = tx.clone();
+        let t = thread::spawn(move || {
+            for j in 0..NUM_LOOP {
+                tx0.send((i, j)).unwrap();
+            }
+        });
+        senders.push(t);
+    }
+    drop(tx);
+
+    for t in senders {
+        t.join().unwrap();
+    }
+    let total: usize = receivers.into_iter().map(|t| t.join().unwrap()).sum();
+    assert_eq!(total, NUM_THREADS * NUM_LOOP);
+}
+
+/// 용량 1 의 channel 에서 sender 2개가 막히고 깨어나는 모든 순서를 model checker 로 검사
+/// (notify 를 놓치면 deadlock 으로, 값을 잃어버리면 assert 로 실패)
+#[cfg(feature = "model-check")]
+#[test]
+fn model_p128_mpmc() {
+    use crate::sync::model::{self, thread};
+
+    model::check(|| {
+        let (tx, rx) = channel(1);
+        let senders: Vec<_> = (0..2)
+            .map(|i| {
+                let tx0 = tx.clone();
+                thread::spawn(move || tx0.send(i).unwrap())
+            })
+            .collect();
+        drop(tx);
+
+        let mut got: Vec<_> = rx.iter().collect();
+        got.sort();
+        assert_eq!(got, vec![0, 1]);
+
+        for t in senders {
+            t.join().unwrap();
+        }
+    });
+}
+
+#[test]
+fn disconnect() {
+    let (tx, rx) = channel::<u32>(2);
+    tx.send(1).unwrap();
+    drop(tx);
+
+    // 남아있는 data 는 받을 수 있고, 그 이후에 Disconnected
+    assert_eq!(rx.recv(), Ok(1));
+    assert_eq!(rx.recv(), Err(RecvError));
+
+    let (tx, rx) = channel::<u32>(2);
+    drop(rx);
+    assert_eq!(tx.send(7).unwrap_err().0, 7);
+}
+
+#[test]
+fn disconnect_wakes_blocked_sender() {
+    use std::thread;
+
+    let (tx, rx) = channel::<u32>(1);
+    tx.send(0).unwrap();
+
+    // queue 가 가득 찬 상태에서 대기 중인 sender 는 receiver 가 사라지면 깨어나야 한다.
+    let t = thread::spawn(move || tx.send(1));
+    thread::sleep(Duration::from_millis(20));
+    drop(rx);
+
+    assert_eq!(t.join().unwrap().unwrap_err().0, 1);
+}
+
+#[test]
+fn try_and_timeout() {
+    let (tx, rx) = channel::<u32>(1);
+
+    assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+    assert_eq!(rx.recv_timeout(Duration::from_millis(10)), Err(RecvTimeoutError::Timeout));
+
+    tx.try_send(1).unwrap();
+    assert!(matches!(tx.try_send(2), Err(TrySendError::Full(2))));
+    assert_eq!(rx.recv_timeout(Duration::from_millis(10)), Ok(1));
+
+    tx.send(3).unwrap();
+    drop(tx);
+    assert_eq!(rx.try_iter().collect::<Vec<_>>(), vec![3]);
+    assert_eq!(rx.try_recv(), Err(TryRecvError::Disconnected));
+}
@@ -0,0 +1,9 @@
+#[cfg(not(feature = "model-check"))]
+pub(crate) use std::sync::{atomic, Condvar, Mutex};
+#[cfg(not(feature = "model-check"))]
+pub(crate) use std::{hint, thread};
+
+#[cfg(feature = "model-check")]
+pub(crate) use super::model::sync::{Condvar, Mutex};
+#[cfg(feature = "model-check")]
+pub(crate) use super::model::{atomic, hint, thread};
@@ -0,0 +1,209 @@
+use crate::sync::facade::atomic::{AtomicBool, AtomicU64, Ordering};
+
+use super::RawLock;
+use crate::scope;
+use crate::sync::spinlock::Backoff;
+
+/// Lamport's bakery lock (ch03 p136 수정판)
+///
+/// p136 의 BakeryLock 은
+/// - lock(&mut self, idx) 라서 static mut 전역 변수 + unsafe 로만 공유할 수 있었고
+/// - thread 번호 idx 를 호출하는 쪽이 0..NUM_THREADS 로 직접 정해야 했으며
+/// - 일반 변수를 volatile + fence 로 읽고 썼기 때문에 (data race) 정의되지 않은 동작이었다.
+///
+/// 여기서는 entering / ticket 을 SeqCst atomic 으로 두고 (bakery algorithm 은 순차 일관성을 전제로 한다),
+/// lock 을 요청할 때 비어있는 참가자 slot 을 하나 차지했다가 unlock 때 반납한다.
+/// 따라서 thread 번호를 미리 정할 필요가 없고, slot 수보다 많은 thread 가 오면 slot 이 빌 때까지 기다린다.
+pub struct BakeryLock {
+    slots: Box<[Slot]>,
+}
+
+#[derive(Default)]
+struct Slot {
+    in_use: AtomicBool,   // 이 slot 을 차지한 참가자가 있는지
+    entering: AtomicBool, // p136 의 entering[i] : ticket 을 취득 중
+    ticket: AtomicU64,    // p136 의 tickets[i] : 0 이면 None
+}
+
+/// 참가자 수 기본값
+const DEFAULT_PARTICIPANTS: usize = 64;
+
+impl BakeryLock {
+    /// 동시에 lock 을 경쟁할 수 있는 최대 참가자 수를 받는다.
+    /// (lock 대기 시 모든 slot 을 확인하므로 필요한 만큼만 잡는 것이 좋다)
+    pub fn new(participants: usize) -> Self {
+        assert!(participants > 0);
+        BakeryLock {
+            slots: (0..participants).map(|_| Slot::default()).collect(),
+        }
+    }
+
+    pub fn participants(&self) -> usize {
+        self.slots.len()
+    }
+
+    // 참가자 등록 : 비어있는 slot 을 하나 차지
+    fn register(&self) -> usize {
+        let mut backoff = Backoff::new();
+        loop {
+            if let Some(idx) = self.try_register() {
+                return idx;
+            }
+            backoff.snooze();
+        }
+    }
+
+    fn try_register(&self) -> Option<usize> {
+        self.slots.iter().position(|s| {
+            !s.in_use.load(Ordering::Relaxed)
+                && s
+                    .in_use
+                    .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+                    .is_ok()
+        })
+    }
+
+    // 참가자 해제 : ticket 을 지운 뒤 slot 을 반납
+    fn deregister(&self, idx: usize) {
+        self.slots[idx].ticket.store(0, Ordering::SeqCst);
+        self.slots[idx].in_use.store(false, Ordering::Release);
+    }
+
+    // p136 의 ticket 취득 처리 : 현재 배포된 ticket 의 최대값 + 1
+    fn take_ticket(&self, idx: usize) -> u64 {
+        let me = &self.slots[idx];
+        me.entering.store(true, Ordering::SeqCst);
+        let max = self
+            .slots
+            .iter()
+            .map(|s| s.ticket.load(Ordering::SeqCst))
+            .max()
+            .unwrap_or(0);
+        let ticket = max + 1;
+        me.ticket.store(ticket, Ordering::SeqCst);
+        me.entering.store(false, Ordering::SeqCst);
+        ticket
+    }
+
+    // (ticket, 참가자 번호) 순으로 나보다 앞선 참가자인가?
+    fn is_ahead(other: (u64, usize), me: (u64, usize)) -> bool {
+        other.0 != 0 && other < me
+    }
+}
+
+impl Default for BakeryLock {
+    fn default() -> Self {
+        BakeryLock::new(DEFAULT_PARTICIPANTS)
+    }
+}
+
+unsafe impl RawLock for BakeryLock {
+    /// 차지한 참가자 slot 번호
+    type Token = usize;
+
+    fn lock(&self) -> usize {
+        let idx = self.register();
+        let ticket = self.take_ticket(idx);
+
+        // p136 의 wait 처리
+        let mut backoff = Backoff::new();
+        for (i, other) in self.slots.iter().enumerate() {
+            if i == idx {
+                continue;
+            }
+            // thread i 가 ticket 을 취득 중이면 대기
+            while other.entering.load(Ordering::SeqCst) {
+                backoff.snooze();
+            }
+            // thread i 가 나보다 작은 ticket (같으면 작은 번호) 을 가지고 있으면 끝날 때까지 대기
+            while Self::is_ahead((other.ticket.load(Ordering::SeqCst), i), (ticket, idx)) {
+                backoff.snooze();
+            }
+        }
+        idx
+    }
+
+    fn try_lock(&self) -> Option<usize> {
+        // 포기하고 return 하면 (take_ticket 중의 panic 포함) slot 을 반납
+        let idx = scope::guard(self.try_register()?, |idx| self.deregister(idx));
+        let ticket = self.take_ticket(*idx);
+
+        // 한명이라도 ticket 을 취득 중이거나 앞에 있으면 포기
+        let blocked = self.slots.iter().enumerate().any(|(i, other)| {
+            i != *idx
+                && (other.entering.load(Ordering::SeqCst)
+                    || Self::is_ahead((other.ticket.load(Ordering::SeqCst), i), (ticket, *idx)))
+        });
+        // lock 을 얻었으면 slot 은 unlock 에서 반납한다
+        (!blocked).then(|| idx.dismiss())
+    }
+
+    unsafe fn unlock(&self, idx: usize) {
+        // p136 의 LockGuard::drop (tickets[idx] = None)
+        self.deregister(idx);
+    }
+}
+
+/// p136 과 같은 구성 (thread 4개, 100회) 을 static mut 없이 실행
+#[test]
+fn p136() {
+    use super::BakeryMutex;
+    use std::sync::Arc;
+    use std::thread;
+
+    const NUM_THREADS: usize = 4;
+    const NUM_LOOP: usize = 100;
+
+    let lock = Arc::new(BakeryMutex::with_raw(BakeryLock::new(NUM_THREADS), 0));
+    let mut v = Vec::new();
+
+    for _ in 0..NUM_THREADS {
+        let lock0 = lock.clone();
+        let th = thread::spawn(move || {
+            for _ in 0..NUM_LOOP {
+                *lock0.lock() += 1;
+            }
+        });
+        v.push(th);
+    }
+
+    for th in v {
+        th.join().unwrap();
+    }
+
+    assert_eq!(*lock.lock(), NUM_LOOP * NUM_THREADS);
+}
+
+/// p136 을 model checker 로 : 번호표를 고르는 도중 (choosing) 에 끼어드는 schedule 까지 검사
+#[cfg(feature = "model-check")]
+#[test]
+fn model_p136() {
+    use super::BakeryMutex;
+    use crate::sync::model::atomic::AtomicUsize;
+    use crate::sync::model::{self, thread};
+    use std::sync::Arc;
+
+    const NUM_THREADS: usize = 2;
+
+    model::check(|| {
+        let lock = Arc::new(BakeryMutex::with_raw(BakeryLock::new(NUM_THREADS), 0));
+        let inside = Arc::new(AtomicUsize::new(0));
+
+        let v: Vec<_> = (0..NUM_THREADS)
+            .map(|_| {
+                let (lock, inside) = (lock.clone(), inside.clone());
+                thread::spawn(move || {
+                    let mut data = lock.lock();
+                    assert_eq!(inside.fetch_add(1, Ordering::SeqCst), 0);
+                    *data += 1;
+                    inside.fetch_sub(1, Ordering::SeqCst);
+                })
+            })
+            .collect();
+        for t in v {
+            t.join().unwrap();
+        }
+
+        assert_eq!(*lock.lock(), NUM_THREADS);
+    });
+}
@@ -0,0 +1,121 @@
+use std::ptr::{self, NonNull};
+use crate::sync::facade::atomic::{AtomicBool, AtomicPtr, Ordering};
+
+use super::RawLock;
+use crate::sync::spinlock::Backoff;
+
+/// MCS queue lock (Mellor-Crummey & Scott)
+///
+/// 대기하는 thread 마다 자기 node 를 만들어 tail 에 연결하고, 앞 node 가 unlock 하면서
+/// 자기 node 의 locked 를 false 로 바꿔줄 때까지 "자기 node 만" spin 한다.
+/// SpinLock / TicketLock 은 모든 대기자가 같은 변수를 읽으므로 unlock 때마다 cache line 이 전부 무효화되지만
+/// MCS 는 다음 대기자 한명의 cache line 만 바뀐다.
+///
+///    tail ─────────────────────────┐
+///                                  v
+///   [node A] --next--> [node B] --next--> [node C]
+///   (lock 보유)        (A 가 깨움)         (B 가 깨움)
+pub struct McsLock {
+    tail: AtomicPtr<Node>,
+}
+
+struct Node {
+    locked: AtomicBool,
+    next: AtomicPtr<Node>,
+}
+
+/// lock 을 얻을 때 만든 node. unlock 까지 주소가 바뀌면 안되므로 heap 에 둔다.
+pub struct McsToken {
+    node: NonNull<Node>,
+}
+
+impl McsLock {
+    pub const fn new() -> Self {
+        McsLock {
+            tail: AtomicPtr::new(ptr::null_mut()),
+        }
+    }
+
+    fn new_node() -> *mut Node {
+        Box::into_raw(Box::new(Node {
+            locked: AtomicBool::new(true),
+            next: AtomicPtr::new(ptr::null_mut()),
+        }))
+    }
+}
+
+impl Default for McsLock {
+    fn default() -> Self {
+        McsLock::new()
+    }
+}
+
+unsafe impl RawLock for McsLock {
+    type Token = McsToken;
+
+    fn lock(&self) -> McsToken {
+        let node = Self::new_node();
+
+        // 자신을 queue 의 마지막으로 등록하고 이전 마지막 node 를 받는다.
+        let prev = self.tail.swap(node, Ordering::AcqRel);
+        if !prev.is_null() {
+            // 앞 node 에 자신을 연결하고 앞 node 가 깨워줄 때까지 대기
+            unsafe {
+                (*prev).next.store(node, Ordering::Release);
+                let mut backoff = Backoff::new();
+                while (*node).locked.load(Ordering::Acquire) {
+                    backoff.snooze();
+                }
+            }
+        }
+
+        McsToken {
+            node: unsafe { NonNull::new_unchecked(node) },
+        }
+    }
+
+    fn try_lock(&self) -> Option<McsToken> {
+        let node = Self::new_node();
+        match self
+            .tail
+            .compare_exchange(ptr::null_mut(), node, Ordering::AcqRel, Ordering::Relaxed)
+        {
+            Ok(_) => Some(McsToken {
+                node: unsafe { NonNull::new_unchecked(node) },
+            }),
+            Err(_) => {
+                drop(unsafe { Box::from_raw(node) });
+                None
+            }
+        }
+    }
+
+    unsafe fn unlock(&self, token: McsToken) {
+        let node = token.node.as_ptr();
+
+        let mut next = (*node).next.load(Ordering::Acquire);
+        if next.is_null() {
+            // 뒤에 아무도 없으면 tail 을 비우고 종료
+            if self
+                .tail
+                .compare_exchange(node, ptr::null_mut(), Ordering::Release, Ordering::Relaxed)
+                .is_ok()
+            {
+                drop(Box::from_raw(node));
+                return;
+            }
+            // tail 이 바뀌었다 == 누군가 swap 은 했지만 아직 next 를 연결하지 못함 -> 연결될 때까지 대기
+            let mut backoff = Backoff::new();
+            loop {
+                next = (*node).next.load(Ordering::Acquire);
+                if !next.is_null() {
+                    break;
+                }
+                backoff.snooze();
+            }
+        }
+
+        (*next).locked.store(false, Ordering::Release);
+        drop(Box::from_raw(node));
+    }
+}
@@ -0,0 +1,201 @@
+use std::cell::UnsafeCell;
+use std::marker::PhantomData;
+use std::mem::ManuallyDrop;
+use std::ops::{Deref, DerefMut};
+
+/// 공정한 (FIFO 순서로 획득하는) lock 모음
+///
+/// SpinLock 은 풀리는 순간 먼저 CAS 에 성공한 thread 가 가져가므로 특정 thread 가 계속 밀릴 수 있다 (4.2 starvation).
+/// 여기 lock 들은 도착한 순서대로 lock 을 넘겨준다.
+/// - TicketLock : 번호표 발급 / 현재 번호 두개의 counter
+/// - McsLock    : 대기자마다 자기 node 를 spin 하는 queue lock (cache line 경합이 적음)
+/// - BakeryLock : ch03 p136 의 Lamport bakery algorithm 을 &self + 동적 참가자 등록으로 수정
+///
+/// 모두 RawLock 을 구현하므로 Lock<R, T> 의 R 만 바꿔서 benchmark 할 수 있다.
+pub mod bakery;
+pub mod mcs;
+pub mod ticket;
+
+pub use bakery::BakeryLock;
+pub use mcs::McsLock;
+pub use ticket::TicketLock;
+
+/// data 없이 lock / unlock 만 제공하는 lock
+///
+/// lock 이 반환한 Token 을 unlock 에 그대로 돌려줘야 한다.
+/// (MCS 는 대기 node, Bakery 는 참가자 번호처럼 lock 마다 unlock 에 필요한 정보가 다르다)
+///
+/// # Safety
+/// 구현체는 lock ~ unlock 사이에 다른 thread 가 lock 을 얻지 못하도록 보장해야 한다.
+pub unsafe trait RawLock: Send + Sync {
+    type Token;
+
+    fn lock(&self) -> Self::Token;
+
+    fn try_lock(&self) -> Option<Self::Token>;
+
+    /// # Safety
+    /// 같은 lock 의 lock / try_lock 에서 받은 token 을 한번만 넘겨야 한다.
+    unsafe fn unlock(&self, token: Self::Token);
+}
+
+/// RawLock 으로 data 를 보호하는 generic lock (Mutex 와 같은 사용법)
+pub struct Lock<R: RawLock, T> {
+    raw: R,
+    data: UnsafeCell<T>,
+}
+
+unsafe impl<R: RawLock, T: Send> Sync for Lock<R, T> {}
+unsafe impl<R: RawLock, T: Send> Send for Lock<R, T> {}
+
+pub type TicketMutex<T> = Lock<TicketLock, T>;
+pub type McsMutex<T> = Lock<McsLock, T>;
+pub type BakeryMutex<T> = Lock<BakeryLock, T>;
+
+impl<R: RawLock + Default, T> Lock<R, T> {
+    pub fn new(v: T) -> Self {
+        Lock::with_raw(R::default(), v)
+    }
+}
+
+impl<R: RawLock, T> Lock<R, T> {
+    /// 설정이 필요한 raw lock (ex. BakeryLock::new(참가자 수)) 을 직접 넘길 때
+    pub fn with_raw(raw: R, v: T) -> Self {
+        Lock {
+            raw,
+            data: UnsafeCell::new(v),
+        }
+    }
+
+    pub fn lock(&self) -> LockGuard<'_, R, T> {
+        let token = self.raw.lock();
+        LockGuard {
+            lock: self,
+            token: ManuallyDrop::new(token),
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn try_lock(&self) -> Option<LockGuard<'_, R, T>> {
+        self.raw.try_lock().map(|token| LockGuard {
+            lock: self,
+            token: ManuallyDrop::new(token),
+            _marker: PhantomData,
+        })
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        self.data.get_mut()
+    }
+
+    pub fn into_inner(self) -> T {
+        self.data.into_inner()
+    }
+}
+
+impl<R: RawLock + Default, T: Default> Default for Lock<R, T> {
+    fn default() -> Self {
+        Lock::new(T::default())
+    }
+}
+
+pub struct LockGuard<'a, R: RawLock, T> {
+    lock: &'a Lock<R, T>,
+    token: ManuallyDrop<R::Token>,
+    // SpinLockGuard 와 같이 자동 Send / Sync 를 막는다 (&Lock<R, T> 는 T: Send 이면 Sync)
+    _marker: PhantomData<*const ()>,
+}
+
+// guard 를 공유하면 &T 를 공유하는 것이므로 T: Sync 일 때만 (token 은 guard 밖으로 보이지 않는다)
+unsafe impl<'a, R: RawLock, T: Sync> Sync for LockGuard<'a, R, T> {}
+
+impl<'a, R: RawLock, T> Drop for LockGuard<'a, R, T> {
+    fn drop(&mut self) {
+        // token 은 여기서 한번만 꺼내서 넘긴다
+        unsafe {
+            let token = ManuallyDrop::take(&mut self.token);
+            self.lock.raw.unlock(token);
+        }
+    }
+}
+
+impl<'a, R: RawLock, T> Deref for LockGuard<'a, R, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<'a, R: RawLock, T> DerefMut for LockGuard<'a, R, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { &mut *self.lock.data.get() }
+    }
+}
+
+/// ch04 p172 과 같이 여러 thread 에서 counter 를 증가시켜서 mutual exclusion 확인
+/// counter 는 atomic 이 아닌 (u64, u64) 를 일부러 두번에 나눠 써서 중간 상태가 보이면 실패하도록
+#[cfg(test)]
+fn stress<R: RawLock + 'static>(lock: Lock<R, (u64, u64)>) {
+    use std::sync::Arc;
+    use std::thread;
+
+    const NUM_THREADS: usize = 4;
+    const NUM_LOOP: usize = 10_000;
+
+    let lock = Arc::new(lock);
+    let mut v = Vec::new();
+    for _ in 0..NUM_THREADS {
+        let lock0 = lock.clone();
+        let t = thread::spawn(move || {
+            for _ in 0..NUM_LOOP {
+                let mut data = lock0.lock();
+                assert_eq!(data.0, data.1);
+                data.0 += 1;
+                data.1 += 1;
+            }
+        });
+        v.push(t);
+    }
+    for t in v {
+        t.join().unwrap();
+    }
+
+    let data = *lock.lock();
+    assert_eq!(data, ((NUM_THREADS * NUM_LOOP) as u64, (NUM_THREADS * NUM_LOOP) as u64));
+}
+
+#[test]
+fn ticket_lock() {
+    stress(TicketMutex::new((0, 0)));
+}
+
+#[test]
+fn mcs_lock() {
+    stress(McsMutex::new((0, 0)));
+}
+
+#[test]
+fn bakery_lock() {
+    // 참가자 수보다 thread 가 많아도 slot 이 빌 때까지 기다렸다가 참가한다.
+    stress(BakeryMutex::with_raw(BakeryLock::new(2), (0, 0)));
+    stress(BakeryMutex::new((0, 0)));
+}
+
+#[test]
+fn try_lock() {
+    fn check<R: RawLock + Default>() {
+        let lock: Lock<R, u32> = Lock::new(0);
+        {
+            let mut g = lock.try_lock().unwrap();
+            *g += 1;
+            assert!(lock.try_lock().is_none());
+        }
+        *lock.try_lock().unwrap() += 1;
+        assert_eq!(lock.into_inner(), 2);
+    }
+
+    check::<TicketLock>();
+    check::<McsLock>();
+    check::<BakeryLock>();
+}
@@ -0,0 +1,51 @@
+use crate::sync::facade::atomic::{AtomicUsize, Ordering};
+
+use super::RawLock;
+use crate::sync::spinlock::Backoff;
+
+/// Ticket lock
+///
+/// 은행 번호표처럼 lock 을 요청할 때 next_ticket 에서 번호를 받고,
+/// now_serving 이 자기 번호가 될 때까지 기다린다. unlock 은 now_serving 을 1 증가.
+/// bakery algorithm 과 같은 발상이지만 atomic fetch_add 가 있으므로 번호 발급이 한번에 끝난다.
+#[derive(Default)]
+pub struct TicketLock {
+    next_ticket: AtomicUsize,
+    now_serving: AtomicUsize,
+}
+
+impl TicketLock {
+    pub const fn new() -> Self {
+        TicketLock {
+            next_ticket: AtomicUsize::new(0),
+            now_serving: AtomicUsize::new(0),
+        }
+    }
+}
+
+unsafe impl RawLock for TicketLock {
+    type Token = ();
+
+    fn lock(&self) {
+        let ticket = self.next_ticket.fetch_add(1, Ordering::Relaxed);
+        let mut backoff = Backoff::new();
+        while self.now_serving.load(Ordering::Acquire) != ticket {
+            backoff.snooze();
+        }
+    }
+
+    fn try_lock(&self) -> Option<()> {
+        // 대기자가 없을 때 (next == serving) 만 번호를 받는다.
+        let serving = self.now_serving.load(Ordering::Relaxed);
+        self.next_ticket
+            .compare_exchange(serving, serving.wrapping_add(1), Ordering::Acquire, Ordering::Relaxed)
+            .ok()
+            .map(|_| ())
+    }
+
+    unsafe fn unlock(&self, _token: ()) {
+        // now_serving 은 lock 을 가진 thread 만 쓰므로 load + store 로 충분
+        let next = self.now_serving.load(Ordering::Relaxed).wrapping_add(1);
+        self.now_serving.store(next, Ordering::Release);
+    }
+}
@@ -0,0 +1,114 @@
+use std::sync::{Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+/// count 가 0 이 될 때까지 기다리는 latch (ch03 p124 의 (Mutex<bool>, Condvar) 를 일반화)
+///
+/// p124 는 부모 thread 가 한번 notify 하면 자식 thread 가 진행했다.
+/// CountDownLatch 는 반대로 여러 thread 가 count_down 해서 0 이 되면 기다리던 thread 가 모두 진행한다.
+/// (ex. worker 들의 초기화가 모두 끝날 때까지 main 이 기다림)
+/// Barrier 와 달리 한번 0 이 되면 다시 사용할 수 없고, count_down 하는 쪽은 기다리지 않는다.
+pub struct CountDownLatch {
+    count: Mutex<usize>,
+    cond: Condvar,
+}
+
+impl CountDownLatch {
+    pub fn new(count: usize) -> Self {
+        CountDownLatch {
+            count: Mutex::new(count),
+            cond: Condvar::new(),
+        }
+    }
+
+    /// count 를 1 줄이고 0 이 되면 기다리는 thread 를 모두 깨운다. (이미 0 이면 아무것도 하지 않음)
+    pub fn count_down(&self) {
+        let mut count = self.count.lock().unwrap();
+        if *count == 0 {
+            return;
+        }
+        *count -= 1;
+        if *count == 0 {
+            self.cond.notify_all();
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        *self.count.lock().unwrap()
+    }
+
+    /// count 가 0 이 될 때까지 대기
+    pub fn wait(&self) {
+        let mut count = self.count.lock().unwrap();
+        while *count > 0 {
+            count = self.cond.wait(count).unwrap();
+        }
+    }
+
+    /// 최대 timeout 만큼 대기. 시간 안에 0 이 되면 true
+    pub fn await_timeout(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        let mut count = self.count.lock().unwrap();
+        while *count > 0 {
+            let now = Instant::now();
+            if now >= deadline {
+                return false;
+            }
+            count = self.cond.wait_timeout(count, deadline - now).unwrap().0;
+        }
+        true
+    }
+}
+
+/// p124 와 같이 thread 를 spawn 하고 main 이 모든 thread 의 준비를 기다린다.
+#[test]
+fn p124() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+
+    const NUM_THREADS: usize = 8;
+
+    let latch = Arc::new(CountDownLatch::new(NUM_THREADS));
+    let ready = Arc::new(AtomicUsize::new(0));
+
+    let mut v = Vec::new();
+    for _ in 0..NUM_THREADS {
+        let latch = latch.clone();
+        let ready = ready.clone();
+        let th = thread::spawn(move || {
+            ready.fetch_add(1, Ordering::Relaxed);
+            latch.count_down();
+        });
+        v.push(th);
+    }
+
+    latch.wait();
+    assert_eq!(latch.count(), 0);
+    assert_eq!(ready.load(Ordering::Relaxed), NUM_THREADS);
+
+    for th in v {
+        th.join().unwrap();
+    }
+}
+
+#[test]
+fn await_timeout() {
+    use std::sync::Arc;
+    use std::thread;
+
+    let latch = Arc::new(CountDownLatch::new(2));
+    latch.count_down();
+    assert!(!latch.await_timeout(Duration::from_millis(10)));
+
+    let l = latch.clone();
+    let th = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(10));
+        l.count_down();
+    });
+    assert!(latch.await_timeout(Duration::from_secs(10)));
+    th.join().unwrap();
+
+    // 0 이 된 뒤에는 바로 통과
+    latch.count_down();
+    assert!(latch.await_timeout(Duration::ZERO));
+}
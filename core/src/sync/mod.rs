@@ -0,0 +1,38 @@
+/// 동기 처리 primitive 모음
+/// guides/concurrent_programming 에서 test 함수 안에만 있던 구현들을 crate 밖에서도 쓸 수 있도록 정리.
+///
+/// 3.8.5 semaphore
+pub mod semaphore;
+/// semaphore 예제의 channel -> disconnect 를 감지하는 MPMC channel
+pub mod channel;
+/// channel 의 async 버전 (Condvar 대신 Waker 로 대기)
+pub mod async_channel;
+/// 4.7 spinlock (backoff + 경합 통계)
+pub mod spinlock;
+/// 3.9 bakery algorithm 과 같은 공정한 lock (ticket, MCS, bakery)
+pub mod fair;
+/// 3.8.3 RwLock (writer 우선, upgradable read)
+pub mod rwlock;
+/// 드물게 쓰는 작은 Copy 값을 lock 없이 읽는 sequence lock
+pub mod seqlock;
+/// 3.8.4 barrier (재사용 가능, leader 가 barrier action 실행)
+pub mod barrier;
+/// 3.8.2 condvar 로 만든 count down latch
+pub mod latch;
+/// 참가자를 동적으로 등록/해제하는 barrier
+pub mod phaser;
+/// idioms2 false_sharing 을 적용한 (CachePadded) lock-free SPSC / MPSC queue
+pub mod queue;
+/// 모든 thread interleaving 을 탐색하는 model checker (std 와 같은 API 의 atomic / Mutex / Condvar / thread)
+pub mod model;
+/// primitive 들이 std 대신 사용하는 atomic / Mutex / Condvar / thread / hint
+/// model-check feature 를 켜면 model 의 instrumented 버전으로 바뀐다.
+pub(crate) mod facade;
+
+pub use barrier::{Barrier, BarrierWaitResult};
+pub use latch::CountDownLatch;
+pub use phaser::Phaser;
+pub use rwlock::{RwLock, RwLockReadGuard, RwLockUpgradableGuard, RwLockWriteGuard};
+pub use semaphore::{Semaphore, SemaphorePermit};
+pub use seqlock::SeqLock;
+pub use spinlock::{SpinLock, SpinLockGuard, SpinLockStats};
@@ -0,0 +1,248 @@
+use super::rt;
+use std::fmt;
+use std::sync::atomic as std_atomic;
+
+pub use std::sync::atomic::Ordering;
+
+/// 모든 연산 앞이 switch point 인 atomic (std::sync::atomic 과 같은 API)
+///
+/// 한 번에 한 thread 만 실행하므로 값은 항상 SeqCst 처럼 보인다.
+/// (interleaving 만 탐색하고 Relaxed 등의 reordering 은 다루지 않는다. 그쪽은 crate::litmus)
+/// compare_exchange_weak 도 가짜 실패 (spurious failure) 는 하지 않는다.
+macro_rules! atomic_int {
+    ($name:ident, $int:ty) => {
+        #[derive(Default)]
+        pub struct $name {
+            v: std_atomic::$name,
+        }
+
+        impl $name {
+            pub const fn new(v: $int) -> Self {
+                $name {
+                    v: std_atomic::$name::new(v),
+                }
+            }
+
+            pub fn load(&self, order: Ordering) -> $int {
+                rt::switch_point();
+                self.v.load(order)
+            }
+
+            pub fn store(&self, val: $int, order: Ordering) {
+                rt::switch_point();
+                self.v.store(val, order)
+            }
+
+            pub fn swap(&self, val: $int, order: Ordering) -> $int {
+                rt::switch_point();
+                self.v.swap(val, order)
+            }
+
+            pub fn compare_exchange(
+                &self,
+                current: $int,
+                new: $int,
+                success: Ordering,
+                failure: Ordering,
+            ) -> Result<$int, $int> {
+                rt::switch_point();
+                self.v.compare_exchange(current, new, success, failure)
+            }
+
+            pub fn compare_exchange_weak(
+                &self,
+                current: $int,
+                new: $int,
+                success: Ordering,
+                failure: Ordering,
+            ) -> Result<$int, $int> {
+                self.compare_exchange(current, new, success, failure)
+            }
+
+            pub fn fetch_add(&self, val: $int, order: Ordering) -> $int {
+                rt::switch_point();
+                self.v.fetch_add(val, order)
+            }
+
+            pub fn fetch_sub(&self, val: $int, order: Ordering) -> $int {
+                rt::switch_point();
+                self.v.fetch_sub(val, order)
+            }
+
+            pub fn fetch_max(&self, val: $int, order: Ordering) -> $int {
+                rt::switch_point();
+                self.v.fetch_max(val, order)
+            }
+
+            pub fn fetch_min(&self, val: $int, order: Ordering) -> $int {
+                rt::switch_point();
+                self.v.fetch_min(val, order)
+            }
+
+            pub fn get_mut(&mut self) -> &mut $int {
+                self.v.get_mut()
+            }
+
+            pub fn into_inner(self) -> $int {
+                self.v.into_inner()
+            }
+        }
+
+        impl fmt::Debug for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                fmt::Debug::fmt(&self.v, f)
+            }
+        }
+    };
+}
+
+atomic_int!(AtomicUsize, usize);
+atomic_int!(AtomicIsize, isize);
+atomic_int!(AtomicU32, u32);
+atomic_int!(AtomicU64, u64);
+
+#[derive(Default)]
+pub struct AtomicBool {
+    v: std_atomic::AtomicBool,
+}
+
+impl AtomicBool {
+    pub const fn new(v: bool) -> Self {
+        AtomicBool {
+            v: std_atomic::AtomicBool::new(v),
+        }
+    }
+
+    pub fn load(&self, order: Ordering) -> bool {
+        rt::switch_point();
+        self.v.load(order)
+    }
+
+    pub fn store(&self, val: bool, order: Ordering) {
+        rt::switch_point();
+        self.v.store(val, order)
+    }
+
+    pub fn swap(&self, val: bool, order: Ordering) -> bool {
+        rt::switch_point();
+        self.v.swap(val, order)
+    }
+
+    pub fn compare_exchange(
+        &self,
+        current: bool,
+        new: bool,
+        success: Ordering,
+        failure: Ordering,
+    ) -> Result<bool, bool> {
+        rt::switch_point();
+        self.v.compare_exchange(current, new, success, failure)
+    }
+
+    pub fn compare_exchange_weak(
+        &self,
+        current: bool,
+        new: bool,
+        success: Ordering,
+        failure: Ordering,
+    ) -> Result<bool, bool> {
+        self.compare_exchange(current, new, success, failure)
+    }
+
+    pub fn fetch_or(&self, val: bool, order: Ordering) -> bool {
+        rt::switch_point();
+        self.v.fetch_or(val, order)
+    }
+
+    pub fn fetch_and(&self, val: bool, order: Ordering) -> bool {
+        rt::switch_point();
+        self.v.fetch_and(val, order)
+    }
+
+    pub fn get_mut(&mut self) -> &mut bool {
+        self.v.get_mut()
+    }
+
+    pub fn into_inner(self) -> bool {
+        self.v.into_inner()
+    }
+}
+
+impl fmt::Debug for AtomicBool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.v, f)
+    }
+}
+
+pub struct AtomicPtr<T> {
+    v: std_atomic::AtomicPtr<T>,
+}
+
+impl<T> AtomicPtr<T> {
+    pub const fn new(p: *mut T) -> Self {
+        AtomicPtr {
+            v: std_atomic::AtomicPtr::new(p),
+        }
+    }
+
+    pub fn load(&self, order: Ordering) -> *mut T {
+        rt::switch_point();
+        self.v.load(order)
+    }
+
+    pub fn store(&self, p: *mut T, order: Ordering) {
+        rt::switch_point();
+        self.v.store(p, order)
+    }
+
+    pub fn swap(&self, p: *mut T, order: Ordering) -> *mut T {
+        rt::switch_point();
+        self.v.swap(p, order)
+    }
+
+    pub fn compare_exchange(
+        &self,
+        current: *mut T,
+        new: *mut T,
+        success: Ordering,
+        failure: Ordering,
+    ) -> Result<*mut T, *mut T> {
+        rt::switch_point();
+        self.v.compare_exchange(current, new, success, failure)
+    }
+
+    pub fn compare_exchange_weak(
+        &self,
+        current: *mut T,
+        new: *mut T,
+        success: Ordering,
+        failure: Ordering,
+    ) -> Result<*mut T, *mut T> {
+        self.compare_exchange(current, new, success, failure)
+    }
+
+    pub fn get_mut(&mut self) -> &mut *mut T {
+        self.v.get_mut()
+    }
+
+    pub fn into_inner(self) -> *mut T {
+        self.v.into_inner()
+    }
+}
+
+impl<T> Default for AtomicPtr<T> {
+    fn default() -> Self {
+        AtomicPtr::new(std::ptr::null_mut())
+    }
+}
+
+impl<T> fmt::Debug for AtomicPtr<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.v, f)
+    }
+}
+
+pub fn fence(order: Ordering) {
+    rt::switch_point();
+    std_atomic::fence(order)
+}
@@ -0,0 +1,329 @@
+use std::fmt::Write;
+use std::sync::Arc;
+
+/// 가능한 모든 thread interleaving 을 실행해보는 작은 model checker (loom / CHESS 방식)
+///
+/// 실제 thread 를 많이 돌리는 test 는 문제가 되는 interleaving 이 우연히 나와야만 bug 를 찾는다.
+/// check 는 test 를 여러 번 다시 실행하면서, switch point (atomic 연산, lock, notify, spawn, join ..) 마다
+/// 어느 thread 를 실행할지를 하나씩 바꿔가며 (depth first) 모든 schedule 을 탐색한다.
+/// - assert 실패 / panic, deadlock, step 제한 초과 (livelock) 를 찾으면 그 schedule 을 출력하고 panic
+/// - 출력된 replay 로 같은 schedule 을 다시 실행할 수 있다
+///
+/// 실행 중인 thread 를 도중에 빼앗는 선점 (preemption) 횟수를 제한해서 (기본 2) 탐색 공간을 줄인다.
+/// 대부분의 동시성 bug 는 적은 선점으로 재현된다. (Musuvathi & Qadeer, CHESS)
+///
+/// atomic / sync / thread / hint 는 std 와 같은 API 의 instrumented 버전이다.
+/// model-check feature 를 켜면 crate::sync 의 primitive 들이 (sync::facade 를 통해) 이 type 들을 사용하므로
+/// Semaphore, SpinLock, BakeryLock, channel 등을 그대로 검사할 수 있다.
+/// model 밖에서 사용하면 std 와 똑같이 동작한다.
+///
+/// 한계
+/// - 한 번에 한 thread 만 실행하므로 memory reordering 은 탐색하지 않는다. (crate::litmus 참고)
+/// - Condvar 의 spurious wakeup, compare_exchange_weak 의 spurious failure 는 만들지 않는다.
+pub mod atomic;
+mod rt;
+pub mod sync;
+pub mod thread;
+
+/// spin loop hint : model 안에서는 yield_now 와 같다.
+pub mod hint {
+    pub fn spin_loop() {
+        match super::rt::current() {
+            Some((exec, me)) => exec.yield_now(me),
+            None => std::hint::spin_loop(),
+        }
+    }
+}
+
+/// 탐색 설정
+#[derive(Debug, Clone)]
+pub struct Builder {
+    preemption_bound: Option<usize>,
+    max_steps: usize,
+    replay: Option<Vec<usize>>,
+}
+
+impl Builder {
+    pub fn new() -> Self {
+        Builder {
+            preemption_bound: Some(2),
+            max_steps: 10_000,
+            replay: None,
+        }
+    }
+
+    /// 한 실행에서 허용하는 선점 횟수. None 이면 제한 없음 (모든 interleaving)
+    pub fn preemption_bound(mut self, bound: Option<usize>) -> Self {
+        self.preemption_bound = bound;
+        self
+    }
+
+    /// 한 실행의 switch point 수 제한. 넘으면 livelock 으로 보고 실패
+    pub fn max_steps(mut self, max_steps: usize) -> Self {
+        self.max_steps = max_steps;
+        self
+    }
+
+    /// 실패 report 의 replay 로 그 schedule 하나만 실행
+    pub fn replay(mut self, schedule: &[usize]) -> Self {
+        self.replay = Some(schedule.to_vec());
+        self
+    }
+
+    /// 모든 schedule 을 실행하고 실행 횟수를 반환. 실패한 schedule 이 있으면 report 와 함께 panic
+    pub fn check<F>(&self, f: F) -> usize
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        let f: Arc<dyn Fn() + Send + Sync> = Arc::new(f);
+        let config = rt::Config {
+            preemption_bound: self.preemption_bound,
+            max_steps: self.max_steps,
+        };
+        let replay = self.replay.clone().unwrap_or_default();
+
+        let mut path = Vec::new();
+        let mut executions = 0;
+        loop {
+            executions += 1;
+            let outcome = rt::Execution::run(f.clone(), path, replay.clone(), config);
+            if let Some(failure) = outcome.failure {
+                panic!(
+                    "{}",
+                    report(executions, &failure, &outcome.trace, &outcome.path)
+                );
+            }
+            if self.replay.is_some() {
+                return executions;
+            }
+
+            // 마지막 branch 부터 아직 고르지 않은 선택지가 있는 곳을 찾아 다음 선택으로
+            path = outcome.path;
+            while let Some(last) = path.last_mut() {
+                if last.index + 1 < last.choices.len() {
+                    last.index += 1;
+                    break;
+                }
+                path.pop();
+            }
+            if path.is_empty() {
+                return executions;
+            }
+        }
+    }
+}
+
+impl Default for Builder {
+    fn default() -> Self {
+        Builder::new()
+    }
+}
+
+/// 기본 설정으로 모든 schedule 을 검사
+pub fn check<F>(f: F) -> usize
+where
+    F: Fn() + Send + Sync + 'static,
+{
+    Builder::new().check(f)
+}
+
+/// 실패 원인, thread 실행 순서 (연속된 같은 thread 는 묶어서), replay 용 branch 선택
+fn report(executions: usize, failure: &str, trace: &[usize], path: &[rt::Branch]) -> String {
+    let mut schedule = String::new();
+    let mut i = 0;
+    while i < trace.len() {
+        let t = trace[i];
+        let n = trace[i..].iter().take_while(|&&x| x == t).count();
+        if !schedule.is_empty() {
+            schedule.push_str(" -> ");
+        }
+        write!(schedule, "t{}", t).unwrap();
+        if n > 1 {
+            write!(schedule, "x{}", n).unwrap();
+        }
+        i += n;
+    }
+    let replay: Vec<usize> = path.iter().map(|b| b.chosen()).collect();
+
+    format!(
+        "model check failed at execution {}: {}\n  schedule : {}\n  replay   : Builder::new().replay(&{:?})",
+        executions, failure, schedule, replay
+    )
+}
+
+#[cfg(test)]
+fn failure_of(f: impl FnOnce() + std::panic::UnwindSafe) -> String {
+    let payload = std::panic::catch_unwind(f).expect_err("model check should fail");
+    match payload.downcast::<String>() {
+        Ok(s) => *s,
+        Err(_) => String::new(),
+    }
+}
+
+/// load + store 로 증가시키면 두 thread 의 증가가 겹쳐서 사라지는 schedule 이 있다.
+#[test]
+fn lost_update() {
+    use self::atomic::{AtomicUsize, Ordering};
+
+    fn racy() {
+        let n = Arc::new(AtomicUsize::new(0));
+        let handles: Vec<_> = (0..2)
+            .map(|_| {
+                let n = n.clone();
+                thread::spawn(move || {
+                    let v = n.load(Ordering::SeqCst);
+                    n.store(v + 1, Ordering::SeqCst);
+                })
+            })
+            .collect();
+        for h in handles {
+            h.join().unwrap();
+        }
+        assert_eq!(n.load(Ordering::SeqCst), 2, "lost update");
+    }
+
+    let msg = failure_of(|| {
+        check(racy);
+    });
+    assert!(msg.contains("lost update"), "{}", msg);
+    assert!(msg.contains("schedule"), "{}", msg);
+
+    // report 의 replay 로 같은 schedule 을 다시 실행하면 같은 실패
+    let replay = &msg[msg.find("replay(&[").unwrap() + 9..];
+    let replay: Vec<usize> = replay[..replay.find(']').unwrap()]
+        .split(", ")
+        .filter(|s| !s.is_empty())
+        .map(|s| s.parse().unwrap())
+        .collect();
+    let again = failure_of(|| {
+        Builder::new().replay(&replay).check(racy);
+    });
+    assert!(again.contains("at execution 1: thread"), "{}", again);
+
+    // fetch_add 이면 어떤 schedule 에서도 2
+    let executions = check(|| {
+        let n = Arc::new(AtomicUsize::new(0));
+        let n2 = n.clone();
+        let h = thread::spawn(move || {
+            n2.fetch_add(1, Ordering::SeqCst);
+        });
+        n.fetch_add(1, Ordering::SeqCst);
+        h.join().unwrap();
+        assert_eq!(n.load(Ordering::SeqCst), 2);
+    });
+    assert!(executions > 1);
+}
+
+/// ch04 p144 의 식사하는 철학자 : 서로 반대 순서로 lock 을 잡으면 deadlock 이 나는 schedule 을 찾는다.
+#[test]
+fn deadlock() {
+    use self::sync::Mutex;
+
+    let msg = failure_of(|| {
+        check(|| {
+            let a = Arc::new(Mutex::new(()));
+            let b = Arc::new(Mutex::new(()));
+            let (a2, b2) = (a.clone(), b.clone());
+            let philosopher = thread::spawn(move || {
+                let _b = b2.lock().unwrap();
+                let _a = a2.lock().unwrap();
+            });
+            {
+                let _a = a.lock().unwrap();
+                let _b = b.lock().unwrap();
+            }
+            philosopher.join().unwrap();
+        });
+    });
+    assert!(msg.contains("deadlock"), "{}", msg);
+}
+
+/// ch03 p124 의 (Mutex<bool>, Condvar) 는 어떤 순서로 실행되어도 끝난다.
+/// flag 를 확인하지 않고 바로 wait 하면, 부모가 먼저 notify 한 경우 자식이 영원히 기다린다. (lost wakeup)
+#[test]
+fn condvar() {
+    use self::sync::{Condvar, Mutex};
+
+    fn parent_child(check_flag: bool) {
+        let pair = Arc::new((Mutex::new(false), Condvar::new()));
+        let pair2 = pair.clone();
+        let child = thread::spawn(move || {
+            let (lock, cvar) = &*pair2;
+            let mut started = lock.lock().unwrap();
+            if check_flag {
+                while !*started {
+                    started = cvar.wait(started).unwrap();
+                }
+            } else {
+                let _ = cvar.wait(started).unwrap();
+            }
+        });
+        let (lock, cvar) = &*pair;
+        *lock.lock().unwrap() = true;
+        cvar.notify_all();
+        child.join().unwrap();
+    }
+
+    check(|| parent_child(true));
+    let msg = failure_of(|| {
+        check(|| parent_child(false));
+    });
+    assert!(msg.contains("deadlock"), "{}", msg);
+}
+
+/// 모두 기다리는 상태가 되면 wait_timeout 은 timeout 으로 깨어난다.
+#[test]
+fn wait_timeout() {
+    use self::sync::{Condvar, Mutex};
+    use std::time::Duration;
+
+    check(|| {
+        let lock = Mutex::new(());
+        let cvar = Condvar::new();
+        let (_g, r) = cvar
+            .wait_timeout(lock.lock().unwrap(), Duration::from_secs(1))
+            .unwrap();
+        assert!(r.timed_out());
+    });
+}
+
+/// 선점 제한을 늘리면 탐색하는 schedule 이 늘어난다.
+#[test]
+fn preemption_bound() {
+    use self::atomic::{AtomicUsize, Ordering};
+
+    fn body() {
+        let n = Arc::new(AtomicUsize::new(0));
+        let n2 = n.clone();
+        let h = thread::spawn(move || {
+            for _ in 0..3 {
+                n2.fetch_add(1, Ordering::SeqCst);
+            }
+        });
+        for _ in 0..3 {
+            n.fetch_add(1, Ordering::SeqCst);
+        }
+        h.join().unwrap();
+    }
+
+    let none = Builder::new().preemption_bound(Some(0)).check(body);
+    let two = Builder::new().preemption_bound(Some(2)).check(body);
+    let all = Builder::new().preemption_bound(None).check(body);
+    assert!(none < two && two < all, "{} {} {}", none, two, all);
+}
+
+/// 끝나지 않는 spin 은 step 제한으로 실패한다.
+#[test]
+fn livelock() {
+    use self::atomic::{AtomicBool, Ordering};
+
+    let msg = failure_of(|| {
+        Builder::new().max_steps(100).check(|| {
+            let flag = AtomicBool::new(false);
+            while !flag.load(Ordering::Acquire) {
+                thread::yield_now();
+            }
+        });
+    });
+    assert!(msg.contains("step limit"), "{}", msg);
+}
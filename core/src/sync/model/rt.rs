@@ -0,0 +1,436 @@
+use std::any::Any;
+use std::cell::RefCell;
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread;
+
+/// thread 가 기다리는 대상
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Resource {
+    Lock(usize),   // Mutex 주소
+    Notify(usize), // Condvar 주소
+    Join(usize),   // thread id
+}
+
+impl fmt::Display for Resource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Resource::Lock(addr) => write!(f, "mutex {:#x}", addr),
+            Resource::Notify(addr) => write!(f, "condvar {:#x}", addr),
+            Resource::Join(id) => write!(f, "join of thread {}", id),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Runnable,
+    Blocked {
+        on: Resource,
+        timed: bool,
+        since: u64,
+    },
+    Finished,
+}
+
+struct ThreadInfo {
+    status: Status,
+    yielded: bool,   // yield_now 직후 : 다른 thread 가 있으면 그쪽을 먼저 실행
+    timed_out: bool, // wait_timeout 이 notify 가 아닌 timeout 으로 깨어남
+}
+
+/// 하나의 switch point 에서 고를 수 있었던 thread 들과 고른 것
+#[derive(Debug, Clone)]
+pub(crate) struct Branch {
+    pub(crate) choices: Vec<usize>,
+    pub(crate) index: usize,
+}
+
+impl Branch {
+    pub(crate) fn chosen(&self) -> usize {
+        self.choices[self.index]
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Config {
+    pub(crate) preemption_bound: Option<usize>,
+    pub(crate) max_steps: usize,
+}
+
+/// 실행이 실패해서 다른 thread 들을 풀어줄 때 쓰는 panic payload (panic hook 을 거치지 않음)
+struct Abort;
+
+/// 한 번의 실행 (execution)
+///
+/// model thread 는 각각 OS thread 이지만 active 인 thread 하나만 실행하고 나머지는 Condvar 에서 기다린다. (baton)
+/// 실행 중인 thread 는 switch point (atomic 연산, lock, spawn ..) 마다 다음에 실행할 thread 를 고르고 baton 을 넘긴다.
+/// 고를 수 있는 thread 가 둘 이상이면 branch 로 기록하고, 이미 기록된 branch (재실행) 이면 기록된 선택을 따른다.
+pub(crate) struct Execution {
+    state: Mutex<State>,
+    cond: Condvar,
+    config: Config,
+}
+
+struct State {
+    threads: Vec<ThreadInfo>,
+    active: Option<usize>,
+    path: Vec<Branch>,
+    replay: Vec<usize>,
+    pos: usize,
+    preemptions: usize,
+    trace: Vec<usize>,
+    steps: usize,
+    seq: u64,
+    failure: Option<String>,
+    done: bool,
+    handles: Vec<thread::JoinHandle<()>>,
+}
+
+/// 실행 결과
+pub(crate) struct Outcome {
+    pub(crate) path: Vec<Branch>,
+    pub(crate) trace: Vec<usize>,
+    pub(crate) failure: Option<String>,
+}
+
+thread_local! {
+    static CURRENT: RefCell<Option<(Arc<Execution>, usize)>> = const { RefCell::new(None) };
+}
+
+/// model thread 안이면 실행과 thread id
+/// panic 으로 unwinding 중이면 (drop 등) scheduling 하지 않고 std 와 같이 동작한다.
+pub(crate) fn current() -> Option<(Arc<Execution>, usize)> {
+    if thread::panicking() {
+        return None;
+    }
+    CURRENT.with(|c| c.borrow().clone())
+}
+
+/// model thread 안이면 다른 thread 로 전환될 수 있는 지점
+pub(crate) fn switch_point() {
+    if let Some((exec, id)) = current() {
+        exec.switch(id);
+    }
+}
+
+impl Execution {
+    /// path 의 선택을 따라 f 를 thread 0 으로 실행하고 끝날 때까지 기다린다.
+    pub(crate) fn run(
+        f: Arc<dyn Fn() + Send + Sync>,
+        path: Vec<Branch>,
+        replay: Vec<usize>,
+        config: Config,
+    ) -> Outcome {
+        let exec = Arc::new(Execution {
+            state: Mutex::new(State {
+                threads: Vec::new(),
+                active: None,
+                path,
+                replay,
+                pos: 0,
+                preemptions: 0,
+                trace: Vec::new(),
+                steps: 0,
+                seq: 0,
+                failure: None,
+                done: false,
+                handles: Vec::new(),
+            }),
+            cond: Condvar::new(),
+            config,
+        });
+
+        exec.spawn(Box::new(move || f()));
+        let mut state = exec.state.lock().unwrap();
+        state.active = Some(0);
+        exec.cond.notify_all();
+        while !state.done && state.failure.is_none() {
+            state = exec.cond.wait(state).unwrap();
+        }
+        let handles = std::mem::take(&mut state.handles);
+        drop(state);
+        for h in handles {
+            let _ = h.join();
+        }
+
+        let mut state = exec.state.lock().unwrap();
+        // 실패했으면 실패 지점 이후의 branch 는 탐색할 필요가 없다
+        let pos = state.pos;
+        state.path.truncate(pos);
+        Outcome {
+            path: std::mem::take(&mut state.path),
+            trace: std::mem::take(&mut state.trace),
+            failure: state.failure.take(),
+        }
+    }
+
+    /// 새 model thread 를 등록하고 (Runnable) 차례를 기다리는 OS thread 를 띄운다.
+    pub(crate) fn spawn(self: &Arc<Self>, f: Box<dyn FnOnce() + Send>) -> usize {
+        let mut state = self.state.lock().unwrap();
+        let id = state.threads.len();
+        state.threads.push(ThreadInfo {
+            status: Status::Runnable,
+            yielded: false,
+            timed_out: false,
+        });
+
+        let exec = self.clone();
+        let handle = thread::spawn(move || {
+            CURRENT.with(|c| *c.borrow_mut() = Some((exec.clone(), id)));
+            let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                exec.wait_turn(id);
+                f();
+            }));
+            match result {
+                Ok(()) => exec.finish(id),
+                Err(payload) if payload.is::<Abort>() => {}
+                Err(payload) => {
+                    exec.fail(format!("thread {} panicked: {}", id, message(&*payload)))
+                }
+            }
+            CURRENT.with(|c| *c.borrow_mut() = None);
+        });
+        state.handles.push(handle);
+        id
+    }
+
+    fn wait_turn(&self, me: usize) {
+        let state = self.state.lock().unwrap();
+        drop(self.wait_active(state, me));
+    }
+
+    fn wait_active<'a>(
+        &'a self,
+        mut state: MutexGuard<'a, State>,
+        me: usize,
+    ) -> MutexGuard<'a, State> {
+        while state.active != Some(me) {
+            if state.failure.is_some() {
+                drop(state);
+                panic::resume_unwind(Box::new(Abort));
+            }
+            state = self.cond.wait(state).unwrap();
+        }
+        state
+    }
+
+    pub(crate) fn switch(&self, me: usize) {
+        let state = self.state.lock().unwrap();
+        let state = self.schedule(state, me);
+        drop(self.wait_active(state, me));
+    }
+
+    /// 다음 thread 를 골라 active 로
+    fn schedule<'a>(
+        &'a self,
+        mut state: MutexGuard<'a, State>,
+        me: usize,
+    ) -> MutexGuard<'a, State> {
+        if state.failure.is_some() {
+            drop(state);
+            panic::resume_unwind(Box::new(Abort));
+        }
+        state.steps += 1;
+        if state.steps > self.config.max_steps {
+            let msg = format!("step limit {} exceeded (livelock?)", self.config.max_steps);
+            self.abort(state, msg);
+        }
+        match state.pick(me, self.config.preemption_bound) {
+            Ok(next) => {
+                state.active = Some(next);
+                if next != me {
+                    self.cond.notify_all();
+                }
+                state
+            }
+            Err(msg) => self.abort(state, msg),
+        }
+    }
+
+    fn abort<'a>(&'a self, mut state: MutexGuard<'a, State>, msg: String) -> ! {
+        state.failure.get_or_insert(msg);
+        state.active = None;
+        self.cond.notify_all();
+        drop(state);
+        panic::resume_unwind(Box::new(Abort));
+    }
+
+    fn fail(&self, msg: String) {
+        let mut state = self.state.lock().unwrap();
+        state.failure.get_or_insert(msg);
+        state.active = None;
+        self.cond.notify_all();
+    }
+
+    fn finish(&self, me: usize) {
+        let mut state = self.state.lock().unwrap();
+        state.threads[me].status = Status::Finished;
+        state.wake(Resource::Join(me), true);
+        if state.threads.iter().all(|t| t.status == Status::Finished) {
+            state.done = true;
+            state.active = None;
+            self.cond.notify_all();
+            return;
+        }
+        match state.pick(me, self.config.preemption_bound) {
+            Ok(next) => {
+                state.active = Some(next);
+                self.cond.notify_all();
+            }
+            Err(msg) => {
+                state.failure.get_or_insert(msg);
+                state.active = None;
+                self.cond.notify_all();
+            }
+        }
+    }
+
+    pub(crate) fn yield_now(&self, me: usize) {
+        self.state.lock().unwrap().threads[me].yielded = true;
+        self.switch(me);
+    }
+
+    /// on 을 기다리는 상태로 표시만 한다. (Condvar 는 표시한 뒤 mutex 를 풀고 wait_blocked)
+    pub(crate) fn begin_block(&self, me: usize, on: Resource, timed: bool) {
+        let mut state = self.state.lock().unwrap();
+        state.seq += 1;
+        let since = state.seq;
+        state.threads[me].status = Status::Blocked { on, timed, since };
+        state.threads[me].timed_out = false;
+    }
+
+    /// 다른 thread 가 깨워줄 때까지 대기. timeout 으로 깨어났으면 true
+    pub(crate) fn wait_blocked(&self, me: usize) -> bool {
+        self.switch(me);
+        let mut state = self.state.lock().unwrap();
+        std::mem::take(&mut state.threads[me].timed_out)
+    }
+
+    pub(crate) fn block(&self, me: usize, on: Resource) {
+        self.begin_block(me, on, false);
+        self.wait_blocked(me);
+    }
+
+    /// on 을 기다리는 thread 를 (all 이 아니면 가장 먼저 기다린 하나만) Runnable 로
+    pub(crate) fn wake(&self, on: Resource, all: bool) {
+        self.state.lock().unwrap().wake(on, all);
+    }
+
+    pub(crate) fn is_finished(&self, id: usize) -> bool {
+        self.state.lock().unwrap().threads[id].status == Status::Finished
+    }
+}
+
+impl State {
+    fn pick(&mut self, me: usize, bound: Option<usize>) -> Result<usize, String> {
+        let mut runnable: Vec<usize> = (0..self.threads.len())
+            .filter(|&i| self.threads[i].status == Status::Runnable)
+            .collect();
+
+        if runnable.is_empty() {
+            // 모두 기다리는 중 : timeout 이 있는 wait 는 시간이 지나서 깨어날 수 있다
+            match self.oldest(|on, timed| timed && matches!(on, Resource::Notify(_))) {
+                Some(id) => {
+                    self.threads[id].status = Status::Runnable;
+                    self.threads[id].timed_out = true;
+                    runnable.push(id);
+                }
+                None => return Err(self.deadlock()),
+            }
+        }
+
+        let me_runnable = runnable.contains(&me);
+        let yielded = std::mem::take(&mut self.threads[me].yielded);
+        let others: Vec<usize> = runnable.iter().copied().filter(|&i| i != me).collect();
+        let choices = if !me_runnable {
+            runnable
+        } else if yielded && !others.is_empty() {
+            others
+        } else if bound.is_none_or(|b| self.preemptions < b) {
+            std::iter::once(me).chain(others).collect()
+        } else {
+            vec![me]
+        };
+
+        let next = if choices.len() == 1 {
+            choices[0]
+        } else {
+            if self.pos < self.path.len() {
+                if self.path[self.pos].choices != choices {
+                    return Err(format!(
+                        "non-deterministic execution: expected choices {:?}, found {:?}",
+                        self.path[self.pos].choices, choices
+                    ));
+                }
+            } else {
+                let index = match self.replay.get(self.pos) {
+                    Some(t) => choices.iter().position(|c| c == t).ok_or_else(|| {
+                        format!(
+                            "replay: thread {} is not runnable (choices {:?})",
+                            t, choices
+                        )
+                    })?,
+                    None => 0,
+                };
+                self.path.push(Branch { choices, index });
+            }
+            self.pos += 1;
+            self.path[self.pos - 1].chosen()
+        };
+
+        if me_runnable && !yielded && next != me {
+            self.preemptions += 1;
+        }
+        self.trace.push(next);
+        Ok(next)
+    }
+
+    fn wake(&mut self, on: Resource, all: bool) {
+        if all {
+            for t in &mut self.threads {
+                if matches!(t.status, Status::Blocked { on: o, .. } if o == on) {
+                    t.status = Status::Runnable;
+                }
+            }
+        } else if let Some(id) = self.oldest(|o, _| o == on) {
+            self.threads[id].status = Status::Runnable;
+        }
+    }
+
+    fn oldest(&self, filter: impl Fn(Resource, bool) -> bool) -> Option<usize> {
+        self.threads
+            .iter()
+            .enumerate()
+            .filter_map(|(i, t)| match t.status {
+                Status::Blocked { on, timed, since } if filter(on, timed) => Some((since, i)),
+                _ => None,
+            })
+            .min()
+            .map(|(_, i)| i)
+    }
+
+    fn deadlock(&self) -> String {
+        let blocked: Vec<String> = self
+            .threads
+            .iter()
+            .enumerate()
+            .filter_map(|(i, t)| match t.status {
+                Status::Blocked { on, .. } => Some(format!("thread {} waits for {}", i, on)),
+                _ => None,
+            })
+            .collect();
+        format!("deadlock: {}", blocked.join(", "))
+    }
+}
+
+fn message(payload: &(dyn Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "Box<dyn Any>".to_string()
+    }
+}
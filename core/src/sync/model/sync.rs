@@ -0,0 +1,219 @@
+use super::rt::{self, Resource};
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::sync::{self as std_sync, LockResult, PoisonError, TryLockError, TryLockResult};
+use std::time::Duration;
+
+/// model thread 안에서는 대기 대신 scheduler 에 차례를 넘기는 Mutex (std::sync::Mutex 와 같은 API)
+///
+/// model 밖에서는 std::sync::Mutex 그대로 동작한다.
+/// model 안에서는 한 번에 한 thread 만 실행하므로 try_lock 이 실패하면 다른 thread 가 잡고 있는 것이고,
+/// 그 thread 가 unlock 할 때까지 Blocked 로 두고 다른 thread 를 실행한다.
+#[derive(Default)]
+pub struct Mutex<T: ?Sized> {
+    inner: std_sync::Mutex<T>,
+}
+
+pub struct MutexGuard<'a, T: ?Sized> {
+    lock: &'a Mutex<T>,
+    guard: Option<std_sync::MutexGuard<'a, T>>, // Condvar::wait 가 잠시 꺼내서 푼다
+}
+
+impl<T> Mutex<T> {
+    pub const fn new(v: T) -> Self {
+        Mutex {
+            inner: std_sync::Mutex::new(v),
+        }
+    }
+
+    pub fn into_inner(self) -> LockResult<T> {
+        self.inner.into_inner()
+    }
+}
+
+impl<T: ?Sized> Mutex<T> {
+    pub fn lock(&self) -> LockResult<MutexGuard<'_, T>> {
+        let Some((exec, me)) = rt::current() else {
+            return self.wrap(self.inner.lock());
+        };
+        loop {
+            exec.switch(me);
+            match self.inner.try_lock() {
+                Ok(g) => return Ok(self.guard(g)),
+                Err(TryLockError::Poisoned(e)) => {
+                    return Err(PoisonError::new(self.guard(e.into_inner())))
+                }
+                Err(TryLockError::WouldBlock) => exec.block(me, Resource::Lock(self.addr())),
+            }
+        }
+    }
+
+    pub fn try_lock(&self) -> TryLockResult<MutexGuard<'_, T>> {
+        rt::switch_point();
+        match self.inner.try_lock() {
+            Ok(g) => Ok(self.guard(g)),
+            Err(TryLockError::Poisoned(e)) => Err(TryLockError::Poisoned(PoisonError::new(
+                self.guard(e.into_inner()),
+            ))),
+            Err(TryLockError::WouldBlock) => Err(TryLockError::WouldBlock),
+        }
+    }
+
+    pub fn get_mut(&mut self) -> LockResult<&mut T> {
+        self.inner.get_mut()
+    }
+
+    fn guard<'a>(&'a self, g: std_sync::MutexGuard<'a, T>) -> MutexGuard<'a, T> {
+        MutexGuard {
+            lock: self,
+            guard: Some(g),
+        }
+    }
+
+    fn wrap<'a>(
+        &'a self,
+        r: LockResult<std_sync::MutexGuard<'a, T>>,
+    ) -> LockResult<MutexGuard<'a, T>> {
+        match r {
+            Ok(g) => Ok(self.guard(g)),
+            Err(e) => Err(PoisonError::new(self.guard(e.into_inner()))),
+        }
+    }
+
+    fn addr(&self) -> usize {
+        self as *const Self as *const () as usize
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for Mutex<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.inner, f)
+    }
+}
+
+impl<'a, T: ?Sized> Deref for MutexGuard<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.guard.as_ref().unwrap()
+    }
+}
+
+impl<'a, T: ?Sized> DerefMut for MutexGuard<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.guard.as_mut().unwrap()
+    }
+}
+
+impl<'a, T: ?Sized> Drop for MutexGuard<'a, T> {
+    fn drop(&mut self) {
+        if self.guard.take().is_some() {
+            if let Some((exec, _)) = rt::current() {
+                exec.wake(Resource::Lock(self.lock.addr()), true);
+            }
+        }
+    }
+}
+
+/// Condvar::wait_timeout 의 결과 (std 의 WaitTimeoutResult 는 밖에서 만들 수 없으므로 따로 정의)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WaitTimeoutResult(bool);
+
+impl WaitTimeoutResult {
+    pub fn timed_out(&self) -> bool {
+        self.0
+    }
+}
+
+/// model 안에서는 notify 될 때까지 Blocked 로 두는 Condvar (std::sync::Condvar 와 같은 API)
+///
+/// - notify_one 은 가장 먼저 wait 한 thread 를 깨운다. (가짜 깨어남 spurious wakeup 은 만들지 않음)
+/// - wait_timeout 은 모든 thread 가 기다리는 상태가 되면 timeout 된 것으로 보고 깨운다.
+#[derive(Debug, Default)]
+pub struct Condvar {
+    inner: std_sync::Condvar,
+}
+
+impl Condvar {
+    pub const fn new() -> Self {
+        Condvar {
+            inner: std_sync::Condvar::new(),
+        }
+    }
+
+    pub fn wait<'a, T>(&self, guard: MutexGuard<'a, T>) -> LockResult<MutexGuard<'a, T>> {
+        self.wait_inner(guard, None)
+            .map(|(g, _)| g)
+            .map_err(|e| PoisonError::new(e.into_inner().0))
+    }
+
+    pub fn wait_timeout<'a, T>(
+        &self,
+        guard: MutexGuard<'a, T>,
+        dur: Duration,
+    ) -> LockResult<(MutexGuard<'a, T>, WaitTimeoutResult)> {
+        self.wait_inner(guard, Some(dur))
+    }
+
+    fn wait_inner<'a, T>(
+        &self,
+        mut guard: MutexGuard<'a, T>,
+        dur: Option<Duration>,
+    ) -> LockResult<(MutexGuard<'a, T>, WaitTimeoutResult)> {
+        let lock = guard.lock;
+        let Some((exec, me)) = rt::current() else {
+            let g = guard.guard.take().unwrap();
+            return match dur {
+                None => match self.inner.wait(g) {
+                    Ok(g) => Ok((lock.guard(g), WaitTimeoutResult(false))),
+                    Err(e) => Err(PoisonError::new((
+                        lock.guard(e.into_inner()),
+                        WaitTimeoutResult(false),
+                    ))),
+                },
+                Some(dur) => match self.inner.wait_timeout(g, dur) {
+                    Ok((g, r)) => Ok((lock.guard(g), WaitTimeoutResult(r.timed_out()))),
+                    Err(e) => {
+                        let (g, r) = e.into_inner();
+                        Err(PoisonError::new((
+                            lock.guard(g),
+                            WaitTimeoutResult(r.timed_out()),
+                        )))
+                    }
+                },
+            };
+        };
+
+        // Blocked 로 표시한 뒤에 mutex 를 풀어야 그 사이의 notify 를 놓치지 않는다
+        exec.begin_block(me, Resource::Notify(self.addr()), dur.is_some());
+        drop(guard);
+        let timed_out = exec.wait_blocked(me);
+        lock.lock()
+            .map(|g| (g, WaitTimeoutResult(timed_out)))
+            .map_err(|e| PoisonError::new((e.into_inner(), WaitTimeoutResult(timed_out))))
+    }
+
+    pub fn notify_one(&self) {
+        match rt::current() {
+            Some((exec, me)) => {
+                exec.switch(me);
+                exec.wake(Resource::Notify(self.addr()), false);
+            }
+            None => self.inner.notify_one(),
+        }
+    }
+
+    pub fn notify_all(&self) {
+        match rt::current() {
+            Some((exec, me)) => {
+                exec.switch(me);
+                exec.wake(Resource::Notify(self.addr()), true);
+            }
+            None => self.inner.notify_all(),
+        }
+    }
+
+    fn addr(&self) -> usize {
+        self as *const Self as usize
+    }
+}
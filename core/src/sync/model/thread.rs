@@ -0,0 +1,72 @@
+use super::rt::{self, Resource};
+use std::sync::{Arc, Mutex};
+use std::thread as std_thread;
+
+/// model 안에서는 model thread 를, 밖에서는 std thread 를 만든다. (std::thread::spawn 과 같은 API)
+pub fn spawn<F, T>(f: F) -> JoinHandle<T>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    let Some((exec, me)) = rt::current() else {
+        return JoinHandle(Inner::Std(std_thread::spawn(f)));
+    };
+    let result = Arc::new(Mutex::new(None));
+    let r = result.clone();
+    let id = exec.spawn(Box::new(move || {
+        let v = f();
+        *r.lock().unwrap() = Some(v);
+    }));
+    // 새 thread 가 먼저 실행되는 경우도 탐색
+    exec.switch(me);
+    JoinHandle(Inner::Model { id, result })
+}
+
+/// 다른 thread 에 차례를 넘긴다. spin loop 안에서 부르면 같은 thread 만 계속 실행하는 schedule 을 건너뛴다.
+pub fn yield_now() {
+    match rt::current() {
+        Some((exec, me)) => exec.yield_now(me),
+        None => std_thread::yield_now(),
+    }
+}
+
+pub struct JoinHandle<T>(Inner<T>);
+
+enum Inner<T> {
+    Std(std_thread::JoinHandle<T>),
+    Model {
+        id: usize,
+        result: Arc<Mutex<Option<T>>>,
+    },
+}
+
+impl<T> JoinHandle<T> {
+    /// model thread 가 panic 하면 그 실행 전체가 실패하므로 Err 는 std thread 에서만 나온다.
+    pub fn join(self) -> std_thread::Result<T> {
+        match self.0 {
+            Inner::Std(h) => h.join(),
+            Inner::Model { id, result } => {
+                let (exec, me) =
+                    rt::current().expect("model thread joined outside of model::check");
+                exec.switch(me);
+                if !exec.is_finished(id) {
+                    exec.block(me, Resource::Join(id));
+                }
+                let v = result.lock().unwrap().take().unwrap();
+                Ok(v)
+            }
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        match &self.0 {
+            Inner::Std(h) => h.is_finished(),
+            Inner::Model { id, .. } => {
+                let (exec, me) =
+                    rt::current().expect("model thread polled outside of model::check");
+                exec.switch(me);
+                exec.is_finished(*id)
+            }
+        }
+    }
+}
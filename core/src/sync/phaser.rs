@@ -0,0 +1,173 @@
+use std::sync::{Condvar, Mutex};
+
+/// 참가자 수가 바뀔 수 있는 barrier (Java 의 Phaser)
+///
+/// Barrier 는 parties 가 생성 시점에 고정되지만, Phaser 는 실행 중에 register 로 참가하고
+/// arrive_and_deregister 로 빠질 수 있다. 등록된 참가자가 모두 도착하면 phase 가 1 증가한다.
+/// - arrive                   : 도착만 알리고 기다리지 않음
+/// - arrive_and_await_advance : 도착하고 다음 phase 가 될 때까지 대기 (Barrier::wait 와 같음)
+/// - arrive_and_deregister    : 도착하고 빠짐. 참가자가 0 이 되면 종료 (terminated)
+///
+/// 종료된 뒤에는 기다리지 않고 바로 반환한다.
+pub struct Phaser {
+    state: Mutex<State>,
+    cond: Condvar,
+}
+
+struct State {
+    phase: u64,
+    parties: usize,
+    arrived: usize,
+    terminated: bool,
+}
+
+impl State {
+    // 모두 도착했으면 다음 phase 로
+    fn try_advance(&mut self, cond: &Condvar) {
+        if self.parties == 0 {
+            self.terminated = true;
+            cond.notify_all();
+        } else if self.arrived == self.parties {
+            self.arrived = 0;
+            self.phase += 1;
+            cond.notify_all();
+        }
+    }
+}
+
+impl Phaser {
+    pub fn new(parties: usize) -> Self {
+        Phaser {
+            state: Mutex::new(State {
+                phase: 0,
+                parties,
+                arrived: 0,
+                terminated: false,
+            }),
+            cond: Condvar::new(),
+        }
+    }
+
+    /// 참가자 하나를 추가하고 현재 phase 를 반환 (현재 phase 에 아직 도착하지 않은 참가자로 추가된다)
+    pub fn register(&self) -> u64 {
+        self.bulk_register(1)
+    }
+
+    pub fn bulk_register(&self, parties: usize) -> u64 {
+        let mut state = self.state.lock().unwrap();
+        assert!(!state.terminated, "phaser is terminated");
+        state.parties += parties;
+        state.phase
+    }
+
+    /// 도착을 알리고 도착한 phase 를 반환 (기다리지 않음)
+    pub fn arrive(&self) -> u64 {
+        let mut state = self.state.lock().unwrap();
+        let phase = state.phase;
+        if !state.terminated {
+            assert!(state.arrived < state.parties, "more arrivals than registered parties");
+            state.arrived += 1;
+            state.try_advance(&self.cond);
+        }
+        phase
+    }
+
+    /// 도착을 알리고 참가자에서 빠진다. 도착한 phase 를 반환
+    pub fn arrive_and_deregister(&self) -> u64 {
+        let mut state = self.state.lock().unwrap();
+        let phase = state.phase;
+        if !state.terminated {
+            assert!(state.arrived < state.parties, "more arrivals than registered parties");
+            state.parties -= 1;
+            state.try_advance(&self.cond);
+        }
+        phase
+    }
+
+    /// 도착을 알리고 다음 phase 가 될 때까지 대기. 새 phase 를 반환
+    pub fn arrive_and_await_advance(&self) -> u64 {
+        let phase = self.arrive();
+        self.await_advance(phase)
+    }
+
+    /// phase 가 지나갈 때까지 대기. (이미 지났거나 종료되었으면 바로 반환) 현재 phase 를 반환
+    pub fn await_advance(&self, phase: u64) -> u64 {
+        let mut state = self.state.lock().unwrap();
+        while state.phase == phase && !state.terminated {
+            state = self.cond.wait(state).unwrap();
+        }
+        state.phase
+    }
+
+    pub fn phase(&self) -> u64 {
+        self.state.lock().unwrap().phase
+    }
+
+    pub fn registered_parties(&self) -> usize {
+        self.state.lock().unwrap().parties
+    }
+
+    pub fn arrived_parties(&self) -> usize {
+        self.state.lock().unwrap().arrived
+    }
+
+    pub fn is_terminated(&self) -> bool {
+        self.state.lock().unwrap().terminated
+    }
+}
+
+/// 실행 중에 참가자가 늘고 줄어든다.
+/// thread i 는 i + 1 개의 phase 에 참가하고 빠지므로, phase 마다 참가하는 thread 수가 줄어든다.
+#[test]
+fn dynamic_parties() {
+    use std::sync::Arc;
+    use std::thread;
+
+    const NUM_THREADS: usize = 4;
+
+    // main 도 참가자로 등록해서, 모든 thread 가 등록을 마친 뒤에 시작하도록 한다
+    let phaser = Arc::new(Phaser::new(1));
+    let log = Arc::new(Mutex::new(Vec::new()));
+
+    let mut v = Vec::new();
+    for i in 0..NUM_THREADS {
+        let phaser = phaser.clone();
+        let log = log.clone();
+        phaser.register();
+        let th = thread::spawn(move || {
+            for _ in 0..=i {
+                let phase = phaser.phase();
+                log.lock().unwrap().push(phase);
+                phaser.arrive_and_await_advance();
+            }
+            phaser.arrive_and_deregister();
+        });
+        v.push(th);
+    }
+    // main 은 빠지고, 나머지 참가자들이 진행
+    phaser.arrive_and_deregister();
+
+    for th in v {
+        th.join().unwrap();
+    }
+    assert!(phaser.is_terminated());
+
+    // phase 0 은 4 개, phase 1 은 3 개 ... 의 thread 가 참가
+    let mut log = log.lock().unwrap().clone();
+    log.sort();
+    assert_eq!(log, vec![0, 0, 0, 0, 1, 1, 1, 2, 2, 3]);
+}
+
+#[test]
+fn arrive_without_waiting() {
+    let phaser = Phaser::new(2);
+    assert_eq!(phaser.arrive(), 0);
+    assert_eq!(phaser.arrived_parties(), 1);
+    assert_eq!(phaser.arrive(), 0);
+    assert_eq!(phaser.phase(), 1);
+    // 이미 지난 phase 는 기다리지 않는다
+    assert_eq!(phaser.await_advance(0), 1);
+
+    phaser.register();
+    assert_eq!(phaser.registered_parties(), 3);
+}
@@ -0,0 +1,146 @@
+use std::ops::{Deref, DerefMut};
+
+/// lock 없이 (atomic 만으로) 주고받는 queue 모음
+///
+/// idioms2 의 false_sharing 예제처럼, 서로 다른 thread 가 같은 cache line 의 값을 쓰면
+/// 실제로는 다른 변수라도 cache line 이 core 사이를 계속 오가서 느려진다.
+/// queue 의 head 는 consumer 가, tail 은 producer 가 쓰므로 둘을 CachePadded 로 다른 cache line 에 둔다.
+/// - spsc : producer / consumer 가 하나씩인 길이가 유한한 ring buffer
+/// - mpsc : producer 여럿 / consumer 하나인 길이 제한이 없는 Vyukov queue (linked list)
+///
+/// 둘 다 대기하지 않는다. (가득 차거나 비어있으면 바로 반환) 대기가 필요하면 crate::sync::channel 을 사용.
+pub mod mpsc;
+pub mod spsc;
+
+/// 값을 cache line 크기로 정렬해서 옆의 값과 같은 cache line 에 들어가지 않도록 한다.
+///
+/// x86_64 는 cache line 이 64 byte 이지만 인접한 두 line 을 같이 가져오는 (spatial prefetcher) 경우가 있어서
+/// crossbeam 과 같이 128 byte 로 정렬한다.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[repr(align(128))]
+pub struct CachePadded<T> {
+    value: T,
+}
+
+impl<T> CachePadded<T> {
+    pub const fn new(value: T) -> Self {
+        CachePadded { value }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T> Deref for CachePadded<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T> DerefMut for CachePadded<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+#[test]
+fn cache_padded() {
+    use std::mem;
+    use std::sync::atomic::AtomicUsize;
+
+    struct HeadTail {
+        head: CachePadded<AtomicUsize>,
+        tail: CachePadded<AtomicUsize>,
+    }
+
+    assert_eq!(mem::align_of::<CachePadded<u8>>(), 128);
+    assert_eq!(mem::size_of::<CachePadded<u8>>(), 128);
+    let ht = HeadTail {
+        head: CachePadded::new(AtomicUsize::new(0)),
+        tail: CachePadded::new(AtomicUsize::new(0)),
+    };
+    let head = &*ht.head as *const _ as usize;
+    let tail = &*ht.tail as *const _ as usize;
+    assert!(head.abs_diff(tail) >= 128);
+}
+
+/// 1 producer -> 1 consumer 로 값을 전달하는 처리량 비교
+/// - spsc / mpsc   : 이 module 의 lock-free queue (가득 차거나 비어있으면 yield 후 재시도)
+/// - sync::channel : ch03 p128 의 (Semaphore + Mutex) channel 을 정리한 것 (Mutex + Condvar)
+/// - std mpsc      : std::sync::mpsc::sync_channel
+///
+/// cargo test --release bench_queues -- --ignored --nocapture
+#[test]
+#[ignore]
+fn bench_queues() {
+    use std::thread;
+    use std::time::Instant;
+
+    const NUM_MSGS: usize = 1_000_000;
+    const CAPACITY: usize = 1024;
+
+    fn run(name: &str, mut send: impl FnMut(usize) + Send + 'static, mut recv: impl FnMut() -> usize) {
+        let start = Instant::now();
+        let th = thread::spawn(move || {
+            for i in 0..NUM_MSGS {
+                send(i);
+            }
+        });
+        let mut sum = 0;
+        for _ in 0..NUM_MSGS {
+            sum += recv();
+        }
+        th.join().unwrap();
+        assert_eq!(sum, NUM_MSGS * (NUM_MSGS - 1) / 2);
+        println!("{:<14}: {:?}", name, start.elapsed());
+    }
+
+    let (tx, rx) = spsc::channel(CAPACITY);
+    run(
+        "queue::spsc",
+        move |mut i| loop {
+            match tx.push(i) {
+                Ok(()) => break,
+                Err(v) => {
+                    i = v;
+                    thread::yield_now();
+                }
+            }
+        },
+        || loop {
+            if let Some(v) = rx.pop() {
+                break v;
+            }
+            thread::yield_now();
+        },
+    );
+
+    let (tx, rx) = mpsc::channel();
+    run(
+        "queue::mpsc",
+        move |i| tx.push(i),
+        || loop {
+            if let Some(v) = rx.pop() {
+                break v;
+            }
+            thread::yield_now();
+        },
+    );
+
+    let (tx, rx) = crate::sync::channel::channel(CAPACITY);
+    run(
+        "sync::channel",
+        move |i| tx.send(i).unwrap(),
+        || rx.recv().unwrap(),
+    );
+
+    let (tx, rx) = std::sync::mpsc::sync_channel(CAPACITY);
+    run(
+        "std mpsc",
+        move |i| tx.send(i).unwrap(),
+        || rx.recv().unwrap(),
+    );
+}
@@ -0,0 +1,204 @@
+use super::CachePadded;
+use crate::sync::spinlock::Backoff;
+use std::cell::{Cell, UnsafeCell};
+use std::marker::PhantomData;
+use std::ptr;
+use std::sync::atomic::{AtomicPtr, Ordering};
+use std::sync::Arc;
+
+/// producer 여럿, consumer 하나인 길이 제한이 없는 queue (Dmitry Vyukov 의 MPSC node-based queue)
+///
+/// linked list 의 head 에 producer 가 붙이고, tail 에서 consumer 가 뗀다.
+/// - push : head 를 새 node 로 swap 한 뒤 이전 head 의 next 에 새 node 를 연결 (CAS 재시도가 없음)
+/// - pop  : tail 의 next 가 있으면 그 값을 꺼내고 next 를 새 tail (빈 node) 로 만든다
+///
+/// swap 과 next 연결 사이에 producer 가 멈추면 consumer 는 그 뒤의 값을 볼 수 없다. (잠깐 끊긴 상태)
+/// 이때 pop 은 연결될 때까지 spin 한다.
+pub fn channel<T>() -> (Producer<T>, Consumer<T>) {
+    // 항상 값이 없는 node (stub) 하나를 tail 로 둔다
+    let stub = Node::new(None);
+    let queue = Arc::new(Queue {
+        head: CachePadded::new(AtomicPtr::new(stub)),
+        tail: CachePadded::new(UnsafeCell::new(stub)),
+    });
+    let consumer = Consumer {
+        queue: queue.clone(),
+        _not_sync: PhantomData,
+    };
+    (Producer { queue }, consumer)
+}
+
+struct Node<T> {
+    next: AtomicPtr<Node<T>>,
+    value: Option<T>,
+}
+
+impl<T> Node<T> {
+    fn new(value: Option<T>) -> *mut Node<T> {
+        Box::into_raw(Box::new(Node {
+            next: AtomicPtr::new(ptr::null_mut()),
+            value,
+        }))
+    }
+}
+
+struct Queue<T> {
+    head: CachePadded<AtomicPtr<Node<T>>>,       // producer 들이 swap
+    tail: CachePadded<UnsafeCell<*mut Node<T>>>, // consumer 만 사용
+}
+
+unsafe impl<T: Send> Send for Queue<T> {}
+unsafe impl<T: Send> Sync for Queue<T> {}
+
+impl<T> Drop for Queue<T> {
+    fn drop(&mut self) {
+        let mut node = *self.tail.get_mut();
+        while !node.is_null() {
+            let next = unsafe { *(*node).next.get_mut() };
+            drop(unsafe { Box::from_raw(node) });
+            node = next;
+        }
+    }
+}
+
+/// 쓰는 쪽. clone 해서 여러 thread 가 사용할 수 있다.
+pub struct Producer<T> {
+    queue: Arc<Queue<T>>,
+}
+
+impl<T> Producer<T> {
+    pub fn push(&self, value: T) {
+        let node = Node::new(Some(value));
+        // 이전 head 를 가져간 producer 만 그 next 를 쓸 수 있다
+        let prev = self.queue.head.swap(node, Ordering::AcqRel);
+        // node 의 값이 next 연결보다 먼저 보이도록 Release
+        unsafe { (*prev).next.store(node, Ordering::Release) };
+    }
+}
+
+impl<T> Clone for Producer<T> {
+    fn clone(&self) -> Self {
+        Producer {
+            queue: self.queue.clone(),
+        }
+    }
+}
+
+/// 읽는 쪽. Clone 할 수 없고 Sync 도 아니므로 한 thread 만 사용한다.
+pub struct Consumer<T> {
+    queue: Arc<Queue<T>>,
+    // Queue 는 Sync 이므로 Consumer 를 !Sync 로 만들어서 여러 thread 가 동시에 pop 하지 않도록 한다
+    _not_sync: PhantomData<Cell<()>>,
+}
+
+impl<T> Consumer<T> {
+    /// 비어있으면 None
+    pub fn pop(&self) -> Option<T> {
+        let queue = &*self.queue;
+        unsafe {
+            let tail = *queue.tail.get();
+            let mut backoff = Backoff::new();
+            loop {
+                let next = (*tail).next.load(Ordering::Acquire);
+                if !next.is_null() {
+                    // next 가 새 stub 이 되고, 이전 stub 은 해제
+                    *queue.tail.get() = next;
+                    let value = (*next).value.take();
+                    drop(Box::from_raw(tail));
+                    return value;
+                }
+                if queue.head.load(Ordering::Acquire) == tail {
+                    return None;
+                }
+                // head 는 옮겨졌지만 next 가 아직 연결되지 않음 : producer 가 연결할 때까지 대기
+                // (producer 가 그 사이에 preempt 되었을 수 있으므로 spin 만 하지 않고 양보)
+                backoff.snooze();
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        let queue = &*self.queue;
+        let tail = unsafe { *queue.tail.get() };
+        queue.head.load(Ordering::Acquire) == tail
+    }
+}
+
+#[test]
+fn push_pop() {
+    let (tx, rx) = channel();
+    assert_eq!(rx.pop(), None);
+    assert!(rx.is_empty());
+    for i in 0..10 {
+        tx.push(i);
+    }
+    assert!(!rx.is_empty());
+    for i in 0..10 {
+        assert_eq!(rx.pop(), Some(i));
+    }
+    assert_eq!(rx.pop(), None);
+}
+
+/// 읽지 않은 값은 queue 가 사라질 때 drop 된다.
+#[test]
+fn drop_remaining() {
+    use std::sync::atomic::AtomicUsize;
+
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+    struct D;
+    impl Drop for D {
+        fn drop(&mut self) {
+            DROPS.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    let (tx, rx) = channel();
+    for _ in 0..5 {
+        tx.push(D);
+    }
+    drop(rx.pop());
+    assert_eq!(DROPS.load(Ordering::Relaxed), 1);
+    drop(rx);
+    drop(tx);
+    assert_eq!(DROPS.load(Ordering::Relaxed), 5);
+}
+
+/// 여러 producer 가 보낸 값이 빠짐없이 도착하고, producer 별로는 순서가 유지된다.
+#[test]
+fn producers() {
+    use std::thread;
+
+    const NUM_THREADS: usize = 4;
+    const NUM_LOOP: usize = 100_000;
+
+    let (tx, rx) = channel();
+    let mut v = Vec::new();
+    for id in 0..NUM_THREADS {
+        let tx = tx.clone();
+        let th = thread::spawn(move || {
+            for i in 0..NUM_LOOP {
+                tx.push((id, i));
+            }
+        });
+        v.push(th);
+    }
+    drop(tx);
+
+    let mut next = [0; NUM_THREADS];
+    let mut received = 0;
+    while received < NUM_THREADS * NUM_LOOP {
+        match rx.pop() {
+            Some((id, i)) => {
+                assert_eq!(next[id], i);
+                next[id] += 1;
+                received += 1;
+            }
+            None => thread::yield_now(),
+        }
+    }
+
+    for th in v {
+        th.join().unwrap();
+    }
+    assert_eq!(rx.pop(), None);
+}
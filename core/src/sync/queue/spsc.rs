@@ -0,0 +1,226 @@
+use super::CachePadded;
+use std::cell::{Cell, UnsafeCell};
+use std::mem::MaybeUninit;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// producer 하나, consumer 하나인 길이가 유한한 ring buffer
+///
+/// head (다음에 읽을 위치) 는 consumer 만, tail (다음에 쓸 위치) 은 producer 만 증가시키므로 CAS 가 필요 없다.
+/// 두 index 는 계속 증가하고 (wrapping) buffer 의 위치는 index & mask 로 구한다.
+/// - tail - head == capacity 이면 가득 참, tail == head 이면 비어있음
+///
+/// 상대방의 index 는 읽을 때마다 다른 core 의 cache line 을 가져와야 하므로,
+/// 마지막으로 읽은 값을 자기 쪽에 저장해두고 (cached) 그 값으로 판단이 안될 때만 다시 읽는다.
+///
+/// capacity 는 2 의 거듭제곱으로 올림한다.
+pub fn channel<T>(capacity: usize) -> (Producer<T>, Consumer<T>) {
+    assert!(capacity > 0);
+    let capacity = capacity.next_power_of_two();
+    let buffer = (0..capacity)
+        .map(|_| UnsafeCell::new(MaybeUninit::uninit()))
+        .collect();
+    let ring = Arc::new(Ring {
+        head: CachePadded::new(AtomicUsize::new(0)),
+        tail: CachePadded::new(AtomicUsize::new(0)),
+        buffer,
+        mask: capacity - 1,
+    });
+
+    let producer = Producer {
+        ring: ring.clone(),
+        cached_head: Cell::new(0),
+    };
+    let consumer = Consumer {
+        ring,
+        cached_tail: Cell::new(0),
+    };
+    (producer, consumer)
+}
+
+struct Ring<T> {
+    head: CachePadded<AtomicUsize>,
+    tail: CachePadded<AtomicUsize>,
+    buffer: Box<[UnsafeCell<MaybeUninit<T>>]>,
+    mask: usize,
+}
+
+unsafe impl<T: Send> Send for Ring<T> {}
+unsafe impl<T: Send> Sync for Ring<T> {}
+
+impl<T> Ring<T> {
+    fn capacity(&self) -> usize {
+        self.mask + 1
+    }
+
+    fn len(&self) -> usize {
+        let head = self.head.load(Ordering::Acquire);
+        let tail = self.tail.load(Ordering::Acquire);
+        tail.wrapping_sub(head)
+    }
+
+    fn slot(&self, index: usize) -> *mut MaybeUninit<T> {
+        self.buffer[index & self.mask].get()
+    }
+}
+
+impl<T> Drop for Ring<T> {
+    fn drop(&mut self) {
+        // 읽히지 않고 남은 값들을 drop
+        let head = *self.head.get_mut();
+        let tail = *self.tail.get_mut();
+        let mut i = head;
+        while i != tail {
+            unsafe { (*self.slot(i)).assume_init_drop() };
+            i = i.wrapping_add(1);
+        }
+    }
+}
+
+/// 쓰는 쪽. Clone 할 수 없고 Sync 도 아니므로 한 thread 만 사용한다.
+pub struct Producer<T> {
+    ring: Arc<Ring<T>>,
+    cached_head: Cell<usize>,
+}
+
+impl<T> Producer<T> {
+    /// 가득 차 있으면 Err 로 값을 돌려준다.
+    pub fn push(&self, value: T) -> Result<(), T> {
+        let ring = &*self.ring;
+        let tail = ring.tail.load(Ordering::Relaxed);
+        if tail.wrapping_sub(self.cached_head.get()) == ring.capacity() {
+            // consumer 의 읽기 (slot 에서 값을 꺼내기) 가 끝난 뒤에 같은 slot 을 덮어쓰도록 Acquire
+            self.cached_head.set(ring.head.load(Ordering::Acquire));
+            if tail.wrapping_sub(self.cached_head.get()) == ring.capacity() {
+                return Err(value);
+            }
+        }
+        unsafe { (*ring.slot(tail)).write(value) };
+        // slot 에 쓴 값이 tail 증가보다 먼저 보이도록 Release
+        ring.tail.store(tail.wrapping_add(1), Ordering::Release);
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.ring.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.ring.capacity()
+    }
+}
+
+/// 읽는 쪽. Clone 할 수 없고 Sync 도 아니므로 한 thread 만 사용한다.
+pub struct Consumer<T> {
+    ring: Arc<Ring<T>>,
+    cached_tail: Cell<usize>,
+}
+
+impl<T> Consumer<T> {
+    /// 비어있으면 None
+    pub fn pop(&self) -> Option<T> {
+        let ring = &*self.ring;
+        let head = ring.head.load(Ordering::Relaxed);
+        if head == self.cached_tail.get() {
+            self.cached_tail.set(ring.tail.load(Ordering::Acquire));
+            if head == self.cached_tail.get() {
+                return None;
+            }
+        }
+        let value = unsafe { (*ring.slot(head)).assume_init_read() };
+        ring.head.store(head.wrapping_add(1), Ordering::Release);
+        Some(value)
+    }
+
+    pub fn len(&self) -> usize {
+        self.ring.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.ring.capacity()
+    }
+}
+
+#[test]
+fn push_pop() {
+    let (tx, rx) = channel(3);
+    assert_eq!(tx.capacity(), 4);
+    assert_eq!(rx.pop(), None);
+
+    for i in 0..4 {
+        tx.push(i).unwrap();
+    }
+    assert_eq!(tx.push(4), Err(4));
+    assert_eq!(rx.len(), 4);
+
+    // index 가 buffer 끝을 넘어가도 순서대로
+    for i in 0..10 {
+        assert_eq!(rx.pop(), Some(i));
+        tx.push(i + 4).unwrap();
+    }
+    assert_eq!(rx.len(), 4);
+}
+
+/// 읽지 않은 값은 queue 가 사라질 때 drop 된다.
+#[test]
+fn drop_remaining() {
+    use std::sync::atomic::AtomicUsize;
+
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+    struct D;
+    impl Drop for D {
+        fn drop(&mut self) {
+            DROPS.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    let (tx, rx) = channel(8);
+    for _ in 0..5 {
+        assert!(tx.push(D).is_ok());
+    }
+    drop(rx.pop());
+    assert_eq!(DROPS.load(Ordering::Relaxed), 1);
+    drop(tx);
+    drop(rx);
+    assert_eq!(DROPS.load(Ordering::Relaxed), 5);
+}
+
+/// 작은 buffer 로 thread 사이에 많은 값을 보내도 순서가 유지된다.
+#[test]
+fn two_threads() {
+    use std::thread;
+
+    const NUM_LOOP: usize = 100_000;
+
+    let (tx, rx) = channel(16);
+    let th = thread::spawn(move || {
+        for i in 0..NUM_LOOP {
+            let mut v = Box::new(i);
+            while let Err(back) = tx.push(v) {
+                v = back;
+                thread::yield_now();
+            }
+        }
+    });
+
+    let mut expected = 0;
+    while expected < NUM_LOOP {
+        match rx.pop() {
+            Some(v) => {
+                assert_eq!(*v, expected);
+                expected += 1;
+            }
+            None => thread::yield_now(),
+        }
+    }
+    th.join().unwrap();
+    assert!(rx.is_empty());
+}
@@ -0,0 +1,414 @@
+use std::cell::UnsafeCell;
+use std::hint;
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Condvar, Mutex, MutexGuard};
+
+/// Reader-writer lock (ch03 p126 의 std::sync::RwLock 을 직접 구현)
+///
+/// 읽기는 여러 thread 가 동시에, 쓰기는 한 thread 만 할 수 있다.
+/// 읽기가 많은 경우 reader 가 계속 들어오면 writer 가 영원히 기다릴 수 있으므로 (writer starvation)
+/// writer 가 기다리기 시작하면 새 reader 는 들어오지 못하게 한다. (writer 우선)
+///
+/// 대기가 없는 경우는 state 하나에 대한 CAS 로만 처리하고,
+/// 기다려야 할 때만 Mutex + Condvar 로 잠든다. (read lock 을 풀 때는 writer 가 기다리는 경우에만 Mutex 를 잡는다)
+///
+/// upgradable_read 는 다른 reader 와 함께 읽다가, 필요하면 lock 을 놓지 않고 writer 로 바꿀 수 있다.
+/// (읽고 판단한 뒤 쓰는 동안 다른 writer 가 끼어들지 않음) 동시에 하나만 가질 수 있다.
+pub struct RwLock<T> {
+    state: AtomicUsize,
+    waiting: Mutex<Waiting>,
+    read_cond: Condvar,  // reader, upgradable reader 가 대기
+    write_cond: Condvar, // writer, upgrade 중인 reader 가 대기
+    data: UnsafeCell<T>,
+}
+
+unsafe impl<T: Send> Send for RwLock<T> {}
+unsafe impl<T: Send + Sync> Sync for RwLock<T> {}
+
+// state 의 bit 구성 : [reader 수 ...][UPGRADABLE][WRITER_WAITING][WRITER]
+const WRITER: usize = 1;
+const WRITER_WAITING: usize = 1 << 1;
+const UPGRADABLE: usize = 1 << 2;
+const READER: usize = 1 << 3;
+const READERS: usize = !(READER - 1);
+
+// 잠들기 전에 CAS 를 다시 시도하는 횟수
+const SPIN_LIMIT: usize = 100;
+
+#[derive(Default)]
+struct Waiting {
+    readers: usize,
+    writers: usize, // upgrade 중인 reader 포함
+}
+
+impl<T> RwLock<T> {
+    pub fn new(v: T) -> Self {
+        RwLock {
+            state: AtomicUsize::new(0),
+            waiting: Mutex::new(Waiting::default()),
+            read_cond: Condvar::new(),
+            write_cond: Condvar::new(),
+            data: UnsafeCell::new(v),
+        }
+    }
+
+    fn try_read_state(&self, s: usize) -> bool {
+        s & (WRITER | WRITER_WAITING) == 0
+            && self
+                .state
+                .compare_exchange_weak(s, s + READER, Ordering::Acquire, Ordering::Relaxed)
+                .is_ok()
+    }
+
+    pub fn read(&self) -> RwLockReadGuard<'_, T> {
+        for _ in 0..SPIN_LIMIT {
+            let s = self.state.load(Ordering::Relaxed);
+            if self.try_read_state(s) {
+                return RwLockReadGuard { lock: self };
+            }
+            hint::spin_loop();
+        }
+
+        let mut w = self.waiting.lock().unwrap();
+        loop {
+            // Mutex 를 잡은 상태에서 확인하므로, 이후 state 를 바꾼 쪽은 Mutex 를 잡고 깨운다
+            let s = self.state.load(Ordering::Relaxed);
+            if s & (WRITER | WRITER_WAITING) == 0 {
+                if self.try_read_state(s) {
+                    return RwLockReadGuard { lock: self };
+                }
+                continue;
+            }
+            w.readers += 1;
+            w = self.read_cond.wait(w).unwrap();
+            w.readers -= 1;
+        }
+    }
+
+    pub fn try_read(&self) -> Option<RwLockReadGuard<'_, T>> {
+        let mut s = self.state.load(Ordering::Relaxed);
+        while s & (WRITER | WRITER_WAITING) == 0 {
+            match self
+                .state
+                .compare_exchange_weak(s, s + READER, Ordering::Acquire, Ordering::Relaxed)
+            {
+                Ok(_) => return Some(RwLockReadGuard { lock: self }),
+                Err(x) => s = x,
+            }
+        }
+        None
+    }
+
+    pub fn write(&self) -> RwLockWriteGuard<'_, T> {
+        if self
+            .state
+            .compare_exchange(0, WRITER, Ordering::Acquire, Ordering::Relaxed)
+            .is_ok()
+        {
+            return RwLockWriteGuard { lock: self };
+        }
+
+        let w = self.waiting.lock().unwrap();
+        self.wait_for_write(w, WRITER | UPGRADABLE | READERS, 0);
+        RwLockWriteGuard { lock: self }
+    }
+
+    pub fn try_write(&self) -> Option<RwLockWriteGuard<'_, T>> {
+        let s = self.state.load(Ordering::Relaxed);
+        if s & (WRITER | UPGRADABLE | READERS) != 0 {
+            return None;
+        }
+        // 기다리는 writer 가 있으면 WRITER_WAITING 은 그대로 둔다
+        self.state
+            .compare_exchange(s, s | WRITER, Ordering::Acquire, Ordering::Relaxed)
+            .ok()
+            .map(|_| RwLockWriteGuard { lock: self })
+    }
+
+    // blocked 가 모두 0 이 될 때까지 기다린 뒤 clear bit 를 지우고 WRITER 를 설정
+    fn wait_for_write(&self, mut w: MutexGuard<'_, Waiting>, blocked: usize, clear: usize) {
+        w.writers += 1;
+        // 새 reader 가 들어오지 못하게 막는다
+        self.state.fetch_or(WRITER_WAITING, Ordering::Relaxed);
+        loop {
+            let s = self.state.load(Ordering::Relaxed);
+            if s & blocked == 0 {
+                // 다른 writer 가 아직 기다리면 WRITER_WAITING 을 유지
+                let waiting = if w.writers > 1 { WRITER_WAITING } else { 0 };
+                let new = (s & !(clear | WRITER_WAITING)) | WRITER | waiting;
+                if self
+                    .state
+                    .compare_exchange(s, new, Ordering::Acquire, Ordering::Relaxed)
+                    .is_ok()
+                {
+                    w.writers -= 1;
+                    return;
+                }
+                continue;
+            }
+            w = self.write_cond.wait(w).unwrap();
+        }
+    }
+
+    pub fn upgradable_read(&self) -> RwLockUpgradableGuard<'_, T> {
+        let mut w = self.waiting.lock().unwrap();
+        loop {
+            if let Some(guard) = self.try_upgradable_read() {
+                return guard;
+            }
+            w.readers += 1;
+            w = self.read_cond.wait(w).unwrap();
+            w.readers -= 1;
+        }
+    }
+
+    pub fn try_upgradable_read(&self) -> Option<RwLockUpgradableGuard<'_, T>> {
+        let mut s = self.state.load(Ordering::Relaxed);
+        while s & (WRITER | WRITER_WAITING | UPGRADABLE) == 0 {
+            match self
+                .state
+                .compare_exchange_weak(s, s | UPGRADABLE, Ordering::Acquire, Ordering::Relaxed)
+            {
+                Ok(_) => return Some(RwLockUpgradableGuard { lock: self }),
+                Err(x) => s = x,
+            }
+        }
+        None
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        self.data.get_mut()
+    }
+
+    pub fn into_inner(self) -> T {
+        self.data.into_inner()
+    }
+
+    fn read_unlock(&self) {
+        let prev = self.state.fetch_sub(READER, Ordering::Release);
+        // 마지막 reader 이고 writer 가 기다리고 있을 때만 깨운다
+        if prev & READERS == READER && prev & WRITER_WAITING != 0 {
+            let _w = self.waiting.lock().unwrap();
+            self.write_cond.notify_all();
+        }
+    }
+
+    // writer / upgradable 이 풀리면 reader 와 writer 모두 진행할 수 있을 수 있다
+    fn unlock(&self, bit: usize) {
+        self.state.fetch_and(!bit, Ordering::Release);
+        let w = self.waiting.lock().unwrap();
+        if w.writers > 0 {
+            self.write_cond.notify_all();
+        }
+        if w.readers > 0 {
+            self.read_cond.notify_all();
+        }
+    }
+}
+
+impl<T: Default> Default for RwLock<T> {
+    fn default() -> Self {
+        RwLock::new(T::default())
+    }
+}
+
+pub struct RwLockReadGuard<'a, T> {
+    lock: &'a RwLock<T>,
+}
+
+impl<'a, T> Deref for RwLockReadGuard<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<'a, T> Drop for RwLockReadGuard<'a, T> {
+    fn drop(&mut self) {
+        self.lock.read_unlock();
+    }
+}
+
+pub struct RwLockWriteGuard<'a, T> {
+    lock: &'a RwLock<T>,
+}
+
+impl<'a, T> Deref for RwLockWriteGuard<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<'a, T> DerefMut for RwLockWriteGuard<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.lock.data.get() }
+    }
+}
+
+impl<'a, T> Drop for RwLockWriteGuard<'a, T> {
+    fn drop(&mut self) {
+        self.lock.unlock(WRITER);
+    }
+}
+
+pub struct RwLockUpgradableGuard<'a, T> {
+    lock: &'a RwLock<T>,
+}
+
+impl<'a, T> RwLockUpgradableGuard<'a, T> {
+    /// 다른 reader 가 모두 나갈 때까지 기다린 뒤 writer 가 된다. (그 사이 새 reader 는 들어오지 못함)
+    pub fn upgrade(self) -> RwLockWriteGuard<'a, T> {
+        let lock = self.lock;
+        std::mem::forget(self);
+
+        if lock
+            .state
+            .compare_exchange(UPGRADABLE, WRITER, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            let w = lock.waiting.lock().unwrap();
+            // UPGRADABLE 을 가지고 있으므로 다른 writer 는 WRITER 를 얻을 수 없다
+            lock.wait_for_write(w, WRITER | READERS, UPGRADABLE);
+        }
+        RwLockWriteGuard { lock }
+    }
+
+    /// 다른 reader 가 없으면 바로 writer 가 되고, 있으면 그대로 돌려준다.
+    pub fn try_upgrade(self) -> Result<RwLockWriteGuard<'a, T>, Self> {
+        let mut s = self.lock.state.load(Ordering::Relaxed);
+        while s & READERS == 0 {
+            let new = (s & !UPGRADABLE) | WRITER;
+            match self
+                .lock
+                .state
+                .compare_exchange_weak(s, new, Ordering::Acquire, Ordering::Relaxed)
+            {
+                Ok(_) => {
+                    let lock = self.lock;
+                    std::mem::forget(self);
+                    return Ok(RwLockWriteGuard { lock });
+                }
+                Err(x) => s = x,
+            }
+        }
+        Err(self)
+    }
+}
+
+impl<'a, T> Deref for RwLockUpgradableGuard<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<'a, T> Drop for RwLockUpgradableGuard<'a, T> {
+    fn drop(&mut self) {
+        self.lock.unlock(UPGRADABLE);
+    }
+}
+
+/// p126 과 같이 read 는 동시에 여러개, write 는 하나만
+#[test]
+fn p126() {
+    let lock = RwLock::new(10);
+    {
+        let r1 = lock.read();
+        let r2 = lock.read();
+        assert_eq!(*r1 + *r2, 20);
+        assert!(lock.try_write().is_none());
+    }
+    {
+        let mut w1 = lock.write();
+        // p126 에서 starvation 이 나던 두번째 write 는 try_write 로 확인
+        assert!(lock.try_write().is_none());
+        assert!(lock.try_read().is_none());
+        *w1 = 7;
+    }
+    assert_eq!(*lock.read(), 7);
+}
+
+/// reader 가 쉬지 않고 들어와도 writer 가 lock 을 얻는다. (writer 우선)
+#[test]
+fn writer_preference() {
+    use std::sync::atomic::AtomicBool;
+    use std::sync::Arc;
+    use std::thread;
+
+    const NUM_READERS: usize = 4;
+    const NUM_WRITES: usize = 100;
+
+    let lock = Arc::new(RwLock::new(0));
+    let done = Arc::new(AtomicBool::new(false));
+    let mut v = Vec::new();
+
+    for _ in 0..NUM_READERS {
+        let (lock, done) = (lock.clone(), done.clone());
+        v.push(thread::spawn(move || {
+            // reader 끼리는 거의 항상 누군가 read lock 을 잡고 있다
+            // (같은 thread 에서 read lock 을 겹쳐 잡으면 기다리는 writer 때문에 deadlock 이 되므로 주의)
+            let mut prev = 0;
+            while !done.load(Ordering::Relaxed) {
+                let r = lock.read();
+                assert!(*r >= prev);
+                prev = *r;
+                for _ in 0..100 {
+                    hint::spin_loop();
+                }
+            }
+        }));
+    }
+
+    for i in 1..=NUM_WRITES {
+        *lock.write() = i;
+    }
+    done.store(true, Ordering::Relaxed);
+    for t in v {
+        t.join().unwrap();
+    }
+    assert_eq!(*lock.read(), NUM_WRITES);
+}
+
+/// upgradable read 는 읽고 판단한 뒤 다른 writer 가 끼어들지 않은 상태에서 쓴다.
+#[test]
+fn upgradable() {
+    use std::sync::Arc;
+    use std::thread;
+
+    const NUM_THREADS: usize = 4;
+    const NUM_LOOP: usize = 1_000;
+
+    let lock = Arc::new(RwLock::new(0));
+    let mut v = Vec::new();
+
+    for _ in 0..NUM_THREADS {
+        let lock = lock.clone();
+        v.push(thread::spawn(move || {
+            for _ in 0..NUM_LOOP {
+                let r = lock.upgradable_read();
+                let value = *r;
+                let mut w = r.upgrade();
+                assert_eq!(*w, value);
+                *w += 1;
+                drop(w);
+                // 일반 reader / writer 도 함께
+                let _ = *lock.read();
+                *lock.write() += 1;
+            }
+        }));
+    }
+    for t in v {
+        t.join().unwrap();
+    }
+
+    let r = lock.upgradable_read();
+    assert!(lock.try_upgradable_read().is_none());
+    let _other = lock.read();
+    let r = r.try_upgrade().err().unwrap();
+    assert_eq!(*r, NUM_THREADS * NUM_LOOP * 2);
+}
@@ -0,0 +1,216 @@
+use crate::sync::facade::{Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+/// Counting semaphore (ch03 p128 의 Semaphore 를 정리)
+///
+/// ch03 의 Semaphore 는 wait() / post() 를 직접 짝지어 호출해야 했다.
+/// 여기서는 acquire() 가 SemaphorePermit 을 돌려주고, permit 이 scope 을 벗어나면 (Drop)
+/// 자동으로 post 된다. (Mutex 의 MutexGuard 와 같은 RAII 패턴)
+pub struct Semaphore {
+    mutex: Mutex<usize>, // 현재 남아있는 (획득 가능한) permit 수
+    cond: Condvar,
+    max: usize,          // permit 의 최대값
+}
+
+impl Semaphore {
+    pub fn new(max: usize) -> Self {
+        Semaphore {
+            mutex: Mutex::new(max),
+            cond: Condvar::new(),
+            max,
+        }
+    }
+
+    /// permit 1개를 얻을 때까지 대기
+    pub fn acquire(&self) -> SemaphorePermit<'_> {
+        self.acquire_many(1)
+    }
+
+    /// permit n 개를 한번에 얻을 때까지 대기
+    /// n 이 max 보다 크면 영원히 얻을 수 없으므로 panic
+    pub fn acquire_many(&self, n: usize) -> SemaphorePermit<'_> {
+        assert!(n <= self.max, "cannot acquire {} permits from a semaphore of {}", n, self.max);
+
+        let mut cnt = self.mutex.lock().unwrap();
+        while *cnt < n {
+            cnt = self.cond.wait(cnt).unwrap();
+        }
+        *cnt -= n;
+        SemaphorePermit { sem: self, permits: n }
+    }
+
+    /// 대기하지 않고 바로 시도. 남은 permit 이 없으면 None
+    pub fn try_acquire(&self) -> Option<SemaphorePermit<'_>> {
+        let mut cnt = self.mutex.lock().unwrap();
+        if *cnt == 0 {
+            return None;
+        }
+        *cnt -= 1;
+        Some(SemaphorePermit { sem: self, permits: 1 })
+    }
+
+    /// 최대 timeout 만큼만 대기 (p124plus 의 wait_timeout 과 같은 방식)
+    /// spurious wakeup 이 있을 수 있으므로 남은 시간을 다시 계산해서 기다린다.
+    pub fn acquire_timeout(&self, timeout: Duration) -> Option<SemaphorePermit<'_>> {
+        let deadline = Instant::now() + timeout;
+        let mut cnt = self.mutex.lock().unwrap();
+        while *cnt == 0 {
+            let now = Instant::now();
+            if now >= deadline {
+                return None;
+            }
+            cnt = self.cond.wait_timeout(cnt, deadline - now).unwrap().0;
+        }
+        *cnt -= 1;
+        Some(SemaphorePermit { sem: self, permits: 1 })
+    }
+
+    pub fn available_permits(&self) -> usize {
+        *self.mutex.lock().unwrap()
+    }
+
+    pub fn max_permits(&self) -> usize {
+        self.max
+    }
+
+    fn release(&self, n: usize) {
+        let mut cnt = self.mutex.lock().unwrap();
+        *cnt += n;
+        // acquire_many 로 여러개를 기다리는 thread 가 있을 수 있으므로 notify_one 이 아닌 notify_all
+        self.cond.notify_all();
+    }
+}
+
+/// acquire 로 얻은 permit. drop 될 때 semaphore 에 반환된다.
+#[must_use = "permit 을 변수에 담지 않으면 바로 반환된다"]
+pub struct SemaphorePermit<'a> {
+    sem: &'a Semaphore,
+    permits: usize,
+}
+
+impl<'a> SemaphorePermit<'a> {
+    pub fn num_permits(&self) -> usize {
+        self.permits
+    }
+}
+
+impl<'a> Drop for SemaphorePermit<'a> {
+    fn drop(&mut self) {
+        self.sem.release(self.permits);
+    }
+}
+
+/// ch03 p128 의 test_code 를 그대로 옮긴 regression test
+/// SEM_NUM 개 이상의 thread 가 동시에 임계 영역에 들어가면 안된다.
+#[test]
+fn p128() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    const NUM_LOOP: usize = 100_000;
+    const NUM_THREADS: usize = 8;
+    const SEM_NUM: usize = 4;
+
+    static CNT: AtomicUsize = AtomicUsize::new(0);
+
+    let mut v = Vec::new();
+    let sem = Arc::new(Semaphore::new(SEM_NUM));
+
+    for _ in 0..NUM_THREADS {
+        let s = sem.clone();
+        let t = std::thread::spawn(move || {
+            for _ in 0..NUM_LOOP {
+                let _permit = s.acquire();
+
+                CNT.fetch_add(1, Ordering::SeqCst);
+                let n = CNT.load(Ordering::SeqCst);
+                assert!(n <= SEM_NUM);
+                CNT.fetch_sub(1, Ordering::SeqCst);
+            }
+        });
+        v.push(t);
+    }
+    for t in v {
+        t.join().unwrap();
+    }
+
+    assert_eq!(sem.available_permits(), SEM_NUM);
+}
+
+/// p128 의 test_code 를 model checker 로 : thread 3개, SEM_NUM 2 로 줄여서 모든 interleaving 을 검사
+/// cargo test --features model-check model_
+#[cfg(feature = "model-check")]
+#[test]
+fn model_p128() {
+    use crate::sync::model::atomic::{AtomicUsize, Ordering};
+    use crate::sync::model::{self, thread};
+    use std::sync::Arc;
+
+    const NUM_THREADS: usize = 3;
+    const SEM_NUM: usize = 2;
+
+    let executions = model::check(|| {
+        let cnt = Arc::new(AtomicUsize::new(0));
+        let sem = Arc::new(Semaphore::new(SEM_NUM));
+
+        let v: Vec<_> = (0..NUM_THREADS)
+            .map(|_| {
+                let (s, cnt) = (sem.clone(), cnt.clone());
+                thread::spawn(move || {
+                    let _permit = s.acquire();
+
+                    cnt.fetch_add(1, Ordering::SeqCst);
+                    let n = cnt.load(Ordering::SeqCst);
+                    assert!(n <= SEM_NUM);
+                    cnt.fetch_sub(1, Ordering::SeqCst);
+                })
+            })
+            .collect();
+        for t in v {
+            t.join().unwrap();
+        }
+
+        assert_eq!(sem.available_permits(), SEM_NUM);
+    });
+    assert!(executions > 1);
+}
+
+#[test]
+fn try_acquire_and_timeout() {
+    let sem = Semaphore::new(2);
+
+    let p1 = sem.try_acquire().unwrap();
+    let p2 = sem.acquire_timeout(Duration::from_millis(10)).unwrap();
+    assert_eq!(sem.available_permits(), 0);
+    assert!(sem.try_acquire().is_none());
+    assert!(sem.acquire_timeout(Duration::from_millis(10)).is_none());
+
+    drop(p1);
+    assert_eq!(sem.available_permits(), 1);
+    drop(p2);
+    assert_eq!(sem.available_permits(), 2);
+}
+
+#[test]
+fn acquire_many() {
+    use std::sync::Arc;
+    use std::thread;
+
+    let sem = Arc::new(Semaphore::new(3));
+    let one = sem.acquire();
+
+    // 3개가 필요하므로 one 이 반환될 때까지 대기
+    let s = sem.clone();
+    let t = thread::spawn(move || {
+        let all = s.acquire_many(3);
+        assert_eq!(all.num_permits(), 3);
+        assert_eq!(s.available_permits(), 0);
+    });
+
+    thread::sleep(Duration::from_millis(20));
+    assert_eq!(sem.available_permits(), 2);
+    drop(one);
+
+    t.join().unwrap();
+    assert_eq!(sem.available_permits(), 3);
+}
@@ -0,0 +1,183 @@
+use std::cell::UnsafeCell;
+use std::hint;
+use std::mem::MaybeUninit;
+use std::ptr;
+use std::sync::atomic::{self, AtomicUsize, Ordering};
+
+/// Sequence lock : 자주 읽고 드물게 쓰는 작은 Copy 값 (설정값 등) 용
+///
+/// RwLock 은 reader 도 state 에 쓰기 (CAS) 를 하므로 reader 가 많으면 cache line 경합이 생긴다.
+/// SeqLock 의 reader 는 아무것도 쓰지 않는다.
+/// - writer 는 쓰기 전후로 seq 를 1 씩 증가시킨다. (쓰는 중에는 홀수)
+/// - reader 는 읽기 전후의 seq 가 같고 짝수이면 그 사이에 쓰기가 없었다고 보고, 아니면 다시 읽는다.
+///
+/// 쓰는 도중의 값을 읽을 수 있으므로 (MaybeUninit 로 읽어서 버린다) T 는 Copy 이어야 한다. (drop 이 없는 값)
+/// writer 가 계속 쓰면 reader 가 계속 재시도하므로 쓰기가 드문 경우에만 사용한다.
+pub struct SeqLock<T: Copy> {
+    seq: AtomicUsize,
+    data: UnsafeCell<T>,
+}
+
+unsafe impl<T: Copy + Send> Send for SeqLock<T> {}
+unsafe impl<T: Copy + Send> Sync for SeqLock<T> {}
+
+impl<T: Copy> SeqLock<T> {
+    pub const fn new(v: T) -> Self {
+        SeqLock {
+            seq: AtomicUsize::new(0),
+            data: UnsafeCell::new(v),
+        }
+    }
+
+    pub fn read(&self) -> T {
+        loop {
+            let s1 = self.seq.load(Ordering::Acquire);
+            if s1 & 1 == 1 {
+                hint::spin_loop();
+                continue;
+            }
+            // writer 와 동시에 읽을 수 있으므로 volatile 로 읽고, 찢어진 값이면 아래에서 버린다.
+            // 찢어진 값은 bool / enum / reference 에게 잘못된 bit pattern 일 수 있으므로 확인 전까지 T 로 만들지 않는다
+            let value = unsafe { ptr::read_volatile(self.data.get() as *const MaybeUninit<T>) };
+            // data 읽기가 두번째 seq 읽기 뒤로 밀리지 않도록
+            atomic::fence(Ordering::Acquire);
+            let s2 = self.seq.load(Ordering::Relaxed);
+            if s1 == s2 {
+                // 그 사이에 쓰기가 없었으므로 writer 가 쓴 온전한 T
+                return unsafe { value.assume_init() };
+            }
+        }
+    }
+
+    pub fn write(&self, v: T) {
+        self.update(|_| v);
+    }
+
+    /// 현재 값으로 새 값을 계산해서 쓴다. (writer 끼리는 seq 로 배타 처리)
+    pub fn update(&self, f: impl FnOnce(T) -> T) {
+        let s = self.lock();
+        // f 가 panic 해도 seq 를 짝수로 되돌려야 reader 가 영원히 기다리지 않는다
+        let _unlock = Unlock(&self.seq, s);
+        unsafe {
+            let old = ptr::read_volatile(self.data.get());
+            ptr::write_volatile(self.data.get(), f(old));
+        }
+    }
+
+    // seq 를 짝수 -> 홀수로 바꾼 writer 만 쓸 수 있다
+    fn lock(&self) -> usize {
+        loop {
+            let s = self.seq.load(Ordering::Relaxed);
+            if s & 1 == 0
+                && self
+                    .seq
+                    .compare_exchange_weak(s, s.wrapping_add(1), Ordering::Acquire, Ordering::Relaxed)
+                    .is_ok()
+            {
+                // 홀수가 된 seq 가 data 쓰기보다 먼저 보이도록
+                atomic::fence(Ordering::Release);
+                return s;
+            }
+            hint::spin_loop();
+        }
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        self.data.get_mut()
+    }
+
+    pub fn into_inner(self) -> T {
+        self.data.into_inner()
+    }
+}
+
+struct Unlock<'a>(&'a AtomicUsize, usize);
+
+impl<'a> Drop for Unlock<'a> {
+    fn drop(&mut self) {
+        self.0.store(self.1.wrapping_add(2), Ordering::Release);
+    }
+}
+
+impl<T: Copy + Default> Default for SeqLock<T> {
+    fn default() -> Self {
+        SeqLock::new(T::default())
+    }
+}
+
+/// 쓰는 도중의 값 (a != b) 을 reader 가 돌려받지 않는다.
+#[test]
+fn no_torn_read() {
+    use std::sync::Arc;
+    use std::thread;
+
+    const NUM_READERS: usize = 4;
+    const NUM_WRITES: u64 = 100_000;
+
+    #[derive(Clone, Copy)]
+    struct Config {
+        a: u64,
+        b: u64,
+        c: [u64; 4],
+    }
+
+    let lock = Arc::new(SeqLock::new(Config { a: 0, b: 0, c: [0; 4] }));
+    let mut v = Vec::new();
+
+    for _ in 0..NUM_READERS {
+        let lock = lock.clone();
+        v.push(thread::spawn(move || {
+            let mut last = 0;
+            loop {
+                let config = lock.read();
+                assert_eq!(config.a, config.b);
+                assert!(config.c.iter().all(|&c| c == config.a));
+                assert!(config.a >= last);
+                last = config.a;
+                if last == NUM_WRITES {
+                    break;
+                }
+            }
+        }));
+    }
+
+    let writer = {
+        let lock = lock.clone();
+        thread::spawn(move || {
+            for i in 1..=NUM_WRITES {
+                lock.write(Config { a: i, b: i, c: [i; 4] });
+            }
+        })
+    };
+
+    writer.join().unwrap();
+    for t in v {
+        t.join().unwrap();
+    }
+}
+
+/// writer 끼리도 update 가 섞이지 않는다.
+#[test]
+fn concurrent_update() {
+    use std::sync::Arc;
+    use std::thread;
+
+    const NUM_THREADS: usize = 4;
+    const NUM_LOOP: usize = 10_000;
+
+    let lock = Arc::new(SeqLock::new(0usize));
+    let v: Vec<_> = (0..NUM_THREADS)
+        .map(|_| {
+            let lock = lock.clone();
+            thread::spawn(move || {
+                for _ in 0..NUM_LOOP {
+                    lock.update(|x| x + 1);
+                }
+            })
+        })
+        .collect();
+    for t in v {
+        t.join().unwrap();
+    }
+    assert_eq!(lock.read(), NUM_THREADS * NUM_LOOP);
+}
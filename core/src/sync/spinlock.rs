@@ -0,0 +1,284 @@
+use crate::sync::facade::atomic::{AtomicBool, AtomicU64, Ordering};
+use crate::sync::facade::{hint, thread};
+use std::cell::UnsafeCell;
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+
+/// Spin lock (ch04 p172 의 SpinLock 을 정리)
+///
+/// p172 의 lock 은 lock 이 풀릴 때까지 아무것도 하지 않고 load 만 반복했다.
+/// 여기서는 실패할 때마다 Backoff 로 대기 시간을 지수적으로 늘리고 (spin_loop hint),
+/// 일정 횟수가 넘으면 thread::yield_now 로 다른 thread 에 CPU 를 양보한다.
+///
+/// with_stats 로 생성하면 lock 획득 횟수 / 경합 횟수 / spin 횟수를 기록하므로
+/// 실제 hot path 에서 Mutex 보다 나은지 측정할 수 있다.
+pub struct SpinLock<T> {
+    lock: AtomicBool,
+    data: UnsafeCell<T>,
+    stats: Option<Counters>,
+}
+
+// 보호 대상 데이터를 다른 thread 로 넘길 수 있어야 하므로 T: Send 일 때만 공유 가능
+unsafe impl<T: Send> Sync for SpinLock<T> {}
+unsafe impl<T: Send> Send for SpinLock<T> {}
+
+#[derive(Default)]
+struct Counters {
+    acquisitions: AtomicU64,
+    contended: AtomicU64,
+    spins: AtomicU64,
+}
+
+/// SpinLock::stats 의 snapshot
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SpinLockStats {
+    pub acquisitions: u64, // lock 획득 횟수 (try_lock 성공 포함)
+    pub contended: u64,    // 그 중 한번이라도 기다려야 했던 횟수
+    pub spins: u64,        // 기다리면서 backoff 한 총 횟수
+}
+
+impl<T> SpinLock<T> {
+    pub const fn new(v: T) -> Self {
+        SpinLock {
+            lock: AtomicBool::new(false),
+            data: UnsafeCell::new(v),
+            stats: None,
+        }
+    }
+
+    /// 경합 counter 를 기록하는 SpinLock
+    pub fn with_stats(v: T) -> Self {
+        SpinLock {
+            lock: AtomicBool::new(false),
+            data: UnsafeCell::new(v),
+            stats: Some(Counters::default()),
+        }
+    }
+
+    pub fn lock(&self) -> SpinLockGuard<'_, T> {
+        let mut backoff = Backoff::new();
+        let mut spins = 0;
+        loop {
+            // 성공 시 Acquire : 임계 영역 안의 읽기 쓰기가 lock 획득 전으로 올라오지 않도록 (p172 참고)
+            if self
+                .lock
+                .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+                .is_ok()
+            {
+                break;
+            }
+            // CAS 는 cache line 을 독점하므로 풀릴 때까지는 load 로만 확인 (test and test-and-set)
+            while self.lock.load(Ordering::Relaxed) {
+                backoff.snooze();
+                spins += 1;
+            }
+        }
+        self.record(spins);
+        SpinLockGuard {
+            spin_lock: self,
+            _marker: PhantomData,
+        }
+    }
+
+    /// 대기하지 않고 바로 시도
+    pub fn try_lock(&self) -> Option<SpinLockGuard<'_, T>> {
+        if self
+            .lock
+            .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_ok()
+        {
+            self.record(0);
+            Some(SpinLockGuard {
+                spin_lock: self,
+                _marker: PhantomData,
+            })
+        } else {
+            None
+        }
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.lock.load(Ordering::Relaxed)
+    }
+
+    /// &mut self 이면 다른 thread 가 접근할 수 없으므로 lock 없이 접근 가능
+    pub fn get_mut(&mut self) -> &mut T {
+        self.data.get_mut()
+    }
+
+    pub fn into_inner(self) -> T {
+        self.data.into_inner()
+    }
+
+    /// with_stats 로 생성한 경우에만 Some
+    pub fn stats(&self) -> Option<SpinLockStats> {
+        self.stats.as_ref().map(|c| SpinLockStats {
+            acquisitions: c.acquisitions.load(Ordering::Relaxed),
+            contended: c.contended.load(Ordering::Relaxed),
+            spins: c.spins.load(Ordering::Relaxed),
+        })
+    }
+
+    fn record(&self, spins: u64) {
+        if let Some(c) = &self.stats {
+            c.acquisitions.fetch_add(1, Ordering::Relaxed);
+            if spins > 0 {
+                c.contended.fetch_add(1, Ordering::Relaxed);
+                c.spins.fetch_add(spins, Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+impl<T: Default> Default for SpinLock<T> {
+    fn default() -> Self {
+        SpinLock::new(T::default())
+    }
+}
+
+impl<T> From<T> for SpinLock<T> {
+    fn from(v: T) -> Self {
+        SpinLock::new(v)
+    }
+}
+
+/// lock 해제 및 lock 중에 보호 대상 데이터를 조작하기 위한 type
+pub struct SpinLockGuard<'a, T> {
+    spin_lock: &'a SpinLock<T>,
+    // std 의 MutexGuard 처럼 자동 Send / Sync 를 막는다. (&SpinLock<T> 는 T: Send 이면 Sync 라서
+    // 그대로 두면 &SpinLockGuard<Cell<_>> 를 여러 thread 가 공유해서 Cell 을 동시에 고칠 수 있다)
+    _marker: PhantomData<*const ()>,
+}
+
+// guard 를 공유하면 &T 를 공유하는 것이므로 T: Sync 일 때만
+unsafe impl<'a, T: Sync> Sync for SpinLockGuard<'a, T> {}
+
+impl<'a, T> Drop for SpinLockGuard<'a, T> {
+    fn drop(&mut self) {
+        self.spin_lock.lock.store(false, Ordering::Release);
+    }
+}
+
+impl<'a, T> Deref for SpinLockGuard<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { &*self.spin_lock.data.get() }
+    }
+}
+
+impl<'a, T> DerefMut for SpinLockGuard<'a, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { &mut *self.spin_lock.data.get() }
+    }
+}
+
+/// 지수 backoff
+/// 처음에는 1, 2, 4 .. 2^SPIN_LIMIT 번 spin_loop hint 를 주며 기다리고,
+/// 그 이상 기다려야 하면 thread::yield_now 로 scheduler 에 양보한다.
+pub(crate) struct Backoff {
+    step: u32,
+}
+
+impl Backoff {
+    const SPIN_LIMIT: u32 = 6;
+
+    pub(crate) fn new() -> Self {
+        Backoff { step: 0 }
+    }
+
+    pub(crate) fn snooze(&mut self) {
+        if self.step <= Self::SPIN_LIMIT {
+            for _ in 0..1 << self.step {
+                hint::spin_loop();
+            }
+            self.step += 1;
+        } else {
+            thread::yield_now();
+        }
+    }
+}
+
+/// ch04 p172 의 main 과 같은 구성
+#[test]
+fn p172() {
+    use std::sync::Arc;
+
+    const NUM_THREADS: usize = 4;
+    const NUM_LOOP: usize = 100_000;
+
+    let lock = Arc::new(SpinLock::with_stats(0));
+    let mut v = Vec::new();
+
+    for _ in 0..NUM_THREADS {
+        let lock0 = lock.clone();
+        let t = std::thread::spawn(move || {
+            for _ in 0..NUM_LOOP {
+                let mut data = lock0.lock();
+                *data += 1;
+            }
+        });
+        v.push(t);
+    }
+
+    for t in v {
+        t.join().unwrap();
+    }
+
+    assert_eq!(*lock.lock(), NUM_LOOP * NUM_THREADS);
+
+    let stats = lock.stats().unwrap();
+    assert_eq!(stats.acquisitions as usize, NUM_LOOP * NUM_THREADS + 1);
+    assert!(stats.contended <= stats.acquisitions);
+}
+
+/// 두 thread 가 동시에 임계 영역에 들어가는 schedule 이 없는지 model checker 로 검사
+#[cfg(feature = "model-check")]
+#[test]
+fn model_mutual_exclusion() {
+    use crate::sync::model::atomic::AtomicUsize;
+    use crate::sync::model::{self, thread};
+    use std::sync::Arc;
+
+    model::check(|| {
+        let lock = Arc::new(SpinLock::new(0));
+        let inside = Arc::new(AtomicUsize::new(0));
+
+        let v: Vec<_> = (0..2)
+            .map(|_| {
+                let (lock, inside) = (lock.clone(), inside.clone());
+                thread::spawn(move || {
+                    let mut data = lock.lock();
+                    assert_eq!(inside.fetch_add(1, Ordering::SeqCst), 0);
+                    *data += 1;
+                    inside.fetch_sub(1, Ordering::SeqCst);
+                })
+            })
+            .collect();
+        for t in v {
+            t.join().unwrap();
+        }
+
+        assert_eq!(*lock.lock(), 2);
+    });
+}
+
+#[test]
+fn try_lock() {
+    let mut lock = SpinLock::from(vec![1]);
+    assert!(lock.stats().is_none());
+
+    {
+        let mut g = lock.try_lock().unwrap();
+        assert!(lock.is_locked());
+        assert!(lock.try_lock().is_none());
+        g.push(2);
+    }
+    assert!(!lock.is_locked());
+
+    lock.get_mut().push(3);
+    assert_eq!(lock.into_inner(), vec![1, 2, 3]);
+
+    let lock: SpinLock<u32> = SpinLock::default();
+    assert_eq!(*lock.lock(), 0);
+}
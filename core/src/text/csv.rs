@@ -0,0 +1,546 @@
+use super::split::{quoted_end, QuotedSplit};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::io::{self, BufRead, Write};
+use std::ops::Range;
+
+/// csv 를 읽다가 실패한 이유
+#[derive(Debug)]
+pub enum CsvError {
+    Io(io::Error),
+    /// 따옴표가 닫히지 않은 채 입력이 끝남 (line 은 record 가 시작한 줄, 1 부터)
+    UnterminatedQuote {
+        line: usize,
+    },
+    /// record 의 field 수가 첫 record (header) 와 다름
+    FieldCount {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl fmt::Display for CsvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CsvError::Io(e) => write!(f, "csv read failed: {}", e),
+            CsvError::UnterminatedQuote { line } => {
+                write!(f, "line {}: quoted field is never closed", line)
+            }
+            CsvError::FieldCount {
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "line {}: expected {} fields but found {}",
+                line, expected, found
+            ),
+        }
+    }
+}
+
+impl Error for CsvError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CsvError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for CsvError {
+    fn from(e: io::Error) -> Self {
+        CsvError::Io(e)
+    }
+}
+
+/// header 이름 -> column 위치
+#[derive(Debug, Clone)]
+pub struct Headers {
+    names: Vec<String>,
+    index: HashMap<String, usize>,
+}
+
+impl Headers {
+    fn new(record: &Record<'_>) -> Self {
+        let names: Vec<String> = record.iter().map(str::to_owned).collect();
+        // 같은 이름이 여러 번 나오면 첫 번째 column
+        let mut index = HashMap::new();
+        for (i, name) in names.iter().enumerate() {
+            index.entry(name.clone()).or_insert(i);
+        }
+        Headers { names, index }
+    }
+
+    pub fn position(&self, name: &str) -> Option<usize> {
+        self.index.get(name).copied()
+    }
+
+    pub fn get(&self, i: usize) -> Option<&str> {
+        self.names.get(i).map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = &str> {
+        self.names.iter().map(String::as_str)
+    }
+}
+
+/// `BufRead` 에서 record 를 하나씩 읽는 csv (RFC 4180) / tsv reader
+///
+/// - record 의 field 는 reader 안의 buffer 를 빌린 `&str` 이다. buffer 는 record 마다 비우고 다시 쓰므로
+///   처음 몇 record 이후에는 할당이 없다. 그래서 `Iterator` 가 아니고 다음 record 를 읽기 전에 이전 record 를 놓아야 한다.
+/// - 한 record 를 이루는 줄을 모은 뒤 ([`QuotedSplit`] 으로) 따옴표 밖의 구분자로 나누고, 따옴표를 벗겨 `""` 를 `"` 로 바꾼다.
+///   따옴표는 field 의 맨 앞에서만 열리고, 따옴표로 시작하지 않은 field 중간의 따옴표 (`5" pipe`) 는 보통 문자이다.
+/// - 따옴표 안의 줄바꿈은 field 의 일부. 줄 끝은 `\n` 과 `\r\n` 모두 허용하고, 빈 줄은 건너뛴다.
+/// - 첫 record 는 기본적으로 header 로 읽어서 이름으로 field 를 찾을 수 있게 한다 (`has_headers(false)` 로 끔).
+/// - field 수가 첫 record 와 다르면 error (`flexible(true)` 로 끔).
+pub struct Reader<R> {
+    inner: R,
+    delimiter: char,
+    quote: char,
+    has_headers: bool,
+    flexible: bool,
+    headers: Option<Headers>,
+    expected: Option<usize>,
+    line: usize,
+    // record 마다 다시 쓰는 buffer
+    raw: String,
+    fields: String,
+    bounds: Vec<Range<usize>>,
+}
+
+impl<R: BufRead> Reader<R> {
+    pub fn new(inner: R) -> Self {
+        Reader {
+            inner,
+            delimiter: ',',
+            quote: '"',
+            has_headers: true,
+            flexible: false,
+            headers: None,
+            expected: None,
+            line: 0,
+            raw: String::new(),
+            fields: String::new(),
+            bounds: Vec::new(),
+        }
+    }
+
+    /// tab 으로 구분하는 reader
+    pub fn tsv(inner: R) -> Self {
+        Reader::new(inner).delimiter('\t')
+    }
+
+    pub fn delimiter(mut self, delimiter: char) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    pub fn quote(mut self, quote: char) -> Self {
+        self.quote = quote;
+        self
+    }
+
+    pub fn has_headers(mut self, yes: bool) -> Self {
+        self.has_headers = yes;
+        self
+    }
+
+    /// record 마다 field 수가 달라도 허용
+    pub fn flexible(mut self, yes: bool) -> Self {
+        self.flexible = yes;
+        self
+    }
+
+    /// 지금까지 읽은 줄 수
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// header (아직 읽지 않았으면 첫 record 를 읽는다). header 가 없는 입력이면 None.
+    pub fn headers(&mut self) -> Result<Option<&Headers>, CsvError> {
+        if self.has_headers && self.headers.is_none() {
+            if let Some(line) = self.read_raw()? {
+                self.parse(line)?;
+                self.headers = Some(Headers::new(&self.record(line)));
+            }
+        }
+        Ok(self.headers.as_ref())
+    }
+
+    /// 다음 record. 입력이 끝나면 None.
+    pub fn read_record(&mut self) -> Result<Option<Record<'_>>, CsvError> {
+        self.headers()?;
+        let Some(line) = self.read_raw()? else {
+            return Ok(None);
+        };
+        self.parse(line)?;
+        Ok(Some(self.record(line)))
+    }
+
+    fn record(&self, line: usize) -> Record<'_> {
+        Record {
+            fields: &self.fields,
+            bounds: &self.bounds,
+            headers: self.headers.as_ref(),
+            line,
+        }
+    }
+
+    /// 따옴표가 닫힐 때까지 줄을 모아서 record 하나를 raw 에 읽고, 시작한 줄 번호를 반환
+    fn read_raw(&mut self) -> Result<Option<usize>, CsvError> {
+        loop {
+            self.raw.clear();
+            let start = self.line + 1;
+            // 마지막 field 의 시작 위치. 앞의 field 는 이미 닫혔으므로 새 줄을 읽으면 여기서부터 다시 나눈다.
+            let mut last = 0;
+            loop {
+                if self.inner.read_line(&mut self.raw)? == 0 {
+                    if self.is_open(last) {
+                        return Err(CsvError::UnterminatedQuote { line: start });
+                    }
+                    break;
+                }
+                self.line += 1;
+                // QuotedSplit 의 마지막 조각은 항상 raw 의 끝까지이다
+                let tail = QuotedSplit::new(&self.raw[last..], self.delimiter, self.quote)
+                    .last()
+                    .unwrap_or_default();
+                last = self.raw.len() - tail.len();
+                if !self.is_open(last) {
+                    break;
+                }
+            }
+            if self.raw.is_empty() {
+                return Ok(None);
+            }
+            let trimmed = self.raw.trim_end_matches('\n').trim_end_matches('\r');
+            if !trimmed.is_empty() {
+                self.raw.truncate(trimmed.len());
+                return Ok(Some(start));
+            }
+        }
+    }
+
+    /// raw[last..] 가 따옴표로 시작해서 아직 닫히지 않았는지
+    fn is_open(&self, last: usize) -> bool {
+        let field = &self.raw[last..];
+        field.starts_with(self.quote) && quoted_end(field, self.quote).is_none()
+    }
+
+    /// raw 를 field 로 나눠서 fields / bounds 에 넣는다
+    fn parse(&mut self, line: usize) -> Result<(), CsvError> {
+        self.fields.clear();
+        self.bounds.clear();
+        let quote = self.quote;
+        for raw in QuotedSplit::new(&self.raw, self.delimiter, quote) {
+            let start = self.fields.len();
+            if raw.starts_with(quote) {
+                // read_raw 가 닫는 따옴표까지 모았으므로 항상 닫혀 있다
+                let end = quoted_end(raw, quote).ok_or(CsvError::UnterminatedQuote { line })?;
+                // "" -> "
+                let mut escaped = false;
+                for c in raw[quote.len_utf8()..end - quote.len_utf8()].chars() {
+                    if c == quote && !escaped {
+                        escaped = true;
+                        continue;
+                    }
+                    escaped = false;
+                    self.fields.push(c);
+                }
+                // 닫는 따옴표 뒤의 문자 ("a"b) 는 그대로 붙인다
+                self.fields.push_str(&raw[end..]);
+            } else {
+                // 따옴표로 시작하지 않은 field 는 그대로 (중간의 따옴표도 그대로 둔다)
+                self.fields.push_str(raw);
+            }
+            self.bounds.push(start..self.fields.len());
+        }
+
+        let found = self.bounds.len();
+        let expected = *self.expected.get_or_insert(found);
+        if !self.flexible && found != expected {
+            return Err(CsvError::FieldCount {
+                line,
+                expected,
+                found,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// reader 의 buffer 를 빌린 record 하나
+#[derive(Clone, Copy)]
+pub struct Record<'a> {
+    fields: &'a str,
+    bounds: &'a [Range<usize>],
+    headers: Option<&'a Headers>,
+    line: usize,
+}
+
+impl<'a> Record<'a> {
+    pub fn get(&self, i: usize) -> Option<&'a str> {
+        let range = self.bounds.get(i)?.clone();
+        Some(&self.fields[range])
+    }
+
+    /// header 이름으로 field 를 찾는다
+    pub fn field(&self, name: &str) -> Option<&'a str> {
+        self.get(self.headers?.position(name)?)
+    }
+
+    pub fn len(&self) -> usize {
+        self.bounds.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bounds.is_empty()
+    }
+
+    /// record 가 시작한 줄 (1 부터)
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = &'a str> + 'a {
+        let fields = self.fields;
+        self.bounds.iter().map(move |r| &fields[r.clone()])
+    }
+}
+
+impl fmt::Debug for Record<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// csv / tsv writer
+///
+/// 구분자, 따옴표, 줄바꿈이 들어있거나 앞뒤에 공백이 있는 field 만 따옴표로 감싸고, 안의 따옴표는 두 번 쓴다.
+pub struct Writer<W: Write> {
+    inner: W,
+    delimiter: char,
+    quote: char,
+    terminator: &'static str,
+}
+
+impl<W: Write> Writer<W> {
+    pub fn new(inner: W) -> Self {
+        Writer {
+            inner,
+            delimiter: ',',
+            quote: '"',
+            terminator: "\n",
+        }
+    }
+
+    pub fn tsv(inner: W) -> Self {
+        Writer::new(inner).delimiter('\t')
+    }
+
+    pub fn delimiter(mut self, delimiter: char) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    pub fn quote(mut self, quote: char) -> Self {
+        self.quote = quote;
+        self
+    }
+
+    /// 줄 끝을 RFC 4180 의 `\r\n` 으로
+    pub fn crlf(mut self, yes: bool) -> Self {
+        self.terminator = if yes { "\r\n" } else { "\n" };
+        self
+    }
+
+    pub fn write_record<I>(&mut self, record: I) -> io::Result<()>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        let mut buf = [0; 4];
+        let delimiter = &*self.delimiter.encode_utf8(&mut buf);
+        let mut quote = [0; 4];
+        let quote = &*self.quote.encode_utf8(&mut quote);
+        let mut count = 0;
+        let mut last_empty = false;
+        for (i, field) in record.into_iter().enumerate() {
+            let field = field.as_ref();
+            count += 1;
+            last_empty = field.is_empty();
+            if i > 0 {
+                self.inner.write_all(delimiter.as_bytes())?;
+            }
+            let needs_quotes = field.contains([self.delimiter, self.quote, '\n', '\r'])
+                || field.starts_with(' ')
+                || field.ends_with(' ');
+            if !needs_quotes {
+                self.inner.write_all(field.as_bytes())?;
+                continue;
+            }
+            self.inner.write_all(quote.as_bytes())?;
+            for (j, part) in field.split(self.quote).enumerate() {
+                if j > 0 {
+                    self.inner.write_all(quote.as_bytes())?;
+                    self.inner.write_all(quote.as_bytes())?;
+                }
+                self.inner.write_all(part.as_bytes())?;
+            }
+            self.inner.write_all(quote.as_bytes())?;
+        }
+        // 빈 field 하나뿐인 record 는 빈 줄이 되어 읽을 때 건너뛰므로 "" 로 쓴다
+        if count == 1 && last_empty {
+            self.inner.write_all(quote.as_bytes())?;
+            self.inner.write_all(quote.as_bytes())?;
+        }
+        self.inner.write_all(self.terminator.as_bytes())
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+/// RFC 4180 의 예 : 따옴표 안의 구분자 / 줄바꿈 / `""`, CRLF, 빈 field
+#[test]
+fn rfc4180() {
+    let input = "year,make,model,description\r\n\
+                 1997,Ford,E350,\"ac, abs, moon\"\r\n\
+                 1999,Chevy,\"Venture \"\"Extended Edition\"\"\",\"\"\r\n\
+                 \r\n\
+                 1996,Jeep,Grand Cherokee,\"MUST SELL!\nair, moon roof, loaded\"\r\n\
+                 1999,,,";
+    let mut reader = Reader::new(input.as_bytes());
+    let headers: Vec<String> = reader
+        .headers()
+        .unwrap()
+        .unwrap()
+        .iter()
+        .map(String::from)
+        .collect();
+    assert_eq!(headers, ["year", "make", "model", "description"]);
+
+    let mut rows = Vec::new();
+    while let Some(record) = reader.read_record().unwrap() {
+        rows.push((
+            record.line(),
+            record.iter().map(String::from).collect::<Vec<_>>(),
+        ));
+    }
+    assert_eq!(
+        rows,
+        [
+            (2, vec!["1997", "Ford", "E350", "ac, abs, moon"]),
+            (3, vec!["1999", "Chevy", "Venture \"Extended Edition\"", ""]),
+            (
+                5,
+                vec![
+                    "1996",
+                    "Jeep",
+                    "Grand Cherokee",
+                    "MUST SELL!\nair, moon roof, loaded"
+                ]
+            ),
+            (7, vec!["1999", "", "", ""]),
+        ]
+        .map(|(line, fields)| (line, fields.into_iter().map(String::from).collect()))
+    );
+    assert_eq!(reader.line(), 7);
+}
+
+/// 애플리케이션 목록 (inventory) 을 tsv 로 쓰고 다시 읽어서 header 이름으로 찾기
+#[test]
+fn inventory_round_trip() {
+    let apps = [
+        ["name", "version", "owner", "notes"],
+        ["billing", "2.4.1", "payments", "uses \"legacy\" db"],
+        ["search", "1.0.0", "platform", " padded "],
+        ["웹 front", "0.9", "web", "multi\nline\tnotes"],
+    ];
+    let mut writer = Writer::tsv(Vec::new()).crlf(true);
+    for app in &apps {
+        writer.write_record(app).unwrap();
+    }
+    let bytes = writer.into_inner();
+
+    let mut reader = Reader::tsv(bytes.as_slice());
+    assert_eq!(
+        reader.headers().unwrap().unwrap().position("owner"),
+        Some(2)
+    );
+    for app in &apps[1..] {
+        let record = reader.read_record().unwrap().unwrap();
+        assert_eq!(record.field("name"), Some(app[0]));
+        assert_eq!(record.field("notes"), Some(app[3]));
+        assert_eq!(record.field("missing"), None);
+        assert!(record.iter().eq(app.iter().copied()));
+    }
+    assert!(reader.read_record().unwrap().is_none());
+}
+
+#[test]
+fn errors() {
+    let mut reader = Reader::new("a,b\n1,2\n3\n".as_bytes());
+    assert!(reader.read_record().unwrap().is_some());
+    match reader.read_record() {
+        Err(CsvError::FieldCount {
+            line: 3,
+            expected: 2,
+            found: 1,
+        }) => {}
+        other => panic!("unexpected {:?}", other.map(|r| r.map(|r| r.len()))),
+    }
+
+    let mut reader = Reader::new("a,b\n1,2\n3\n".as_bytes()).flexible(true);
+    assert_eq!(reader.read_record().unwrap().unwrap().len(), 2);
+    assert_eq!(reader.read_record().unwrap().unwrap().get(0), Some("3"));
+
+    let mut reader = Reader::new("x;y\n1;\"open\n2;3\n".as_bytes())
+        .delimiter(';')
+        .has_headers(false);
+    assert_eq!(reader.read_record().unwrap().unwrap().get(1), Some("y"));
+    let err = reader.read_record().unwrap_err();
+    assert!(matches!(err, CsvError::UnterminatedQuote { line: 2 }));
+    assert_eq!(err.to_string(), "line 2: quoted field is never closed");
+}
+
+/// field 중간의 따옴표는 보통 문자이고, 빈 field 하나뿐인 record 도 다시 읽을 수 있다
+#[test]
+fn quotes_inside_fields() {
+    let mut reader = Reader::new("5\" pipe,x\n6,\"a\"b\n".as_bytes()).has_headers(false);
+    let record = reader.read_record().unwrap().unwrap();
+    assert!(record.iter().eq(["5\" pipe", "x"]));
+    let record = reader.read_record().unwrap().unwrap();
+    assert_eq!(record.line(), 2);
+    assert!(record.iter().eq(["6", "ab"]));
+    assert!(reader.read_record().unwrap().is_none());
+
+    let mut writer = Writer::new(Vec::new());
+    for record in [[""], ["a"], [""]] {
+        writer.write_record(record).unwrap();
+    }
+    let bytes = writer.into_inner();
+    assert_eq!(bytes, b"\"\"\na\n\"\"\n");
+    let mut reader = Reader::new(bytes.as_slice()).has_headers(false);
+    let mut rows = Vec::new();
+    while let Some(record) = reader.read_record().unwrap() {
+        rows.push(record.get(0).unwrap().to_string());
+    }
+    assert_eq!(rows, ["", "a", ""]);
+}
@@ -0,0 +1,6 @@
+/// csv / tsv streaming reader 와 writer
+pub mod csv;
+/// heap 할당 없이 앞뒤에서 lazily 나누는 문자열 tokenizer
+pub mod split;
+
+pub use split::{unquote, Delimiter, QuotedSplit, ReverseDelimiter, SplitN, StrSplit};
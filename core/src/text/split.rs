@@ -0,0 +1,444 @@
+use std::borrow::Cow;
+use std::ops::Range;
+
+/// 문자열을 나누는 구분자
+///
+/// std 의 `Pattern` 은 stable 이 아니므로 같은 역할의 trait 을 따로 둔다.
+/// `&str`, `char`, 여러 char (`[char; N]`, `&[char]`), 여러 문자열 (`[&str; N]`, `&[&str]`),
+/// `FnMut(char) -> bool` closure 가 구현한다.
+///
+/// 빈 문자열 구분자는 어디에도 match 하지 않는다 (std 와 달리 char 마다 나누지 않음).
+pub trait Delimiter {
+    /// haystack 에서 처음 나오는 구분자의 byte 범위
+    fn find(&mut self, haystack: &str) -> Option<Range<usize>>;
+}
+
+/// 뒤에서부터 찾아도 앞에서부터 나눈 것과 같은 조각이 나오는 구분자 (char 하나씩 match 하는 것들)
+///
+/// `&str` 처럼 match 가 겹칠 수 있는 구분자는 방향에 따라 조각이 달라지므로 구현하지 않는다.
+/// (`"aaa"` 를 `"aa"` 로 나누면 앞에서는 `["", "a"]`, 뒤에서는 `["a", ""]`) std 의 `DoubleEndedSearcher` 와 같은 구분.
+pub trait ReverseDelimiter: Delimiter {
+    /// haystack 에서 마지막으로 나오는 구분자의 byte 범위
+    fn rfind(&mut self, haystack: &str) -> Option<Range<usize>>;
+}
+
+impl Delimiter for &str {
+    fn find(&mut self, haystack: &str) -> Option<Range<usize>> {
+        if self.is_empty() {
+            return None;
+        }
+        haystack.find(*self).map(|i| i..i + self.len())
+    }
+}
+
+impl Delimiter for char {
+    fn find(&mut self, haystack: &str) -> Option<Range<usize>> {
+        haystack.find(*self).map(|i| i..i + self.len_utf8())
+    }
+}
+
+impl ReverseDelimiter for char {
+    fn rfind(&mut self, haystack: &str) -> Option<Range<usize>> {
+        haystack.rfind(*self).map(|i| i..i + self.len_utf8())
+    }
+}
+
+impl<F: FnMut(char) -> bool> Delimiter for F {
+    fn find(&mut self, haystack: &str) -> Option<Range<usize>> {
+        haystack
+            .char_indices()
+            .find(|&(_, c)| self(c))
+            .map(|(i, c)| i..i + c.len_utf8())
+    }
+}
+
+impl<F: FnMut(char) -> bool> ReverseDelimiter for F {
+    fn rfind(&mut self, haystack: &str) -> Option<Range<usize>> {
+        haystack
+            .char_indices()
+            .rfind(|&(_, c)| self(c))
+            .map(|(i, c)| i..i + c.len_utf8())
+    }
+}
+
+impl Delimiter for &[char] {
+    fn find(&mut self, haystack: &str) -> Option<Range<usize>> {
+        (|c: char| self.contains(&c)).find(haystack)
+    }
+}
+
+impl ReverseDelimiter for &[char] {
+    fn rfind(&mut self, haystack: &str) -> Option<Range<usize>> {
+        (|c: char| self.contains(&c)).rfind(haystack)
+    }
+}
+
+impl<const N: usize> Delimiter for [char; N] {
+    fn find(&mut self, haystack: &str) -> Option<Range<usize>> {
+        self.as_slice().find(haystack)
+    }
+}
+
+impl<const N: usize> ReverseDelimiter for [char; N] {
+    fn rfind(&mut self, haystack: &str) -> Option<Range<usize>> {
+        self.as_slice().rfind(haystack)
+    }
+}
+
+/// 여러 문자열 중 하나
+///
+/// 가장 앞에서 시작하는 것을 고르고, 같은 위치에서 여러 개가 match 하면 가장 긴 것.
+/// 후보마다 `str::find` 를 부르면 나오지 않는 후보 때문에 매번 끝까지 보게 되므로, 위치를 한 칸씩 옮기며 모든 후보를 확인한다.
+impl Delimiter for &[&str] {
+    fn find(&mut self, haystack: &str) -> Option<Range<usize>> {
+        haystack
+            .char_indices()
+            .find_map(|(i, _)| longest_at(self, haystack, i))
+    }
+}
+
+impl<const N: usize> Delimiter for [&str; N] {
+    fn find(&mut self, haystack: &str) -> Option<Range<usize>> {
+        self.as_slice().find(haystack)
+    }
+}
+
+fn longest_at(needles: &[&str], haystack: &str, i: usize) -> Option<Range<usize>> {
+    needles
+        .iter()
+        .filter(|n| !n.is_empty() && haystack[i..].starts_with(**n))
+        .map(|n| n.len())
+        .max()
+        .map(|len| i..i + len)
+}
+
+/// 구분자로 나눈 조각을 앞에서부터 (또는 뒤에서부터) 하나씩 돌려주는 iterator
+///
+/// - 남은 부분 (`remainder`) 만 slice 로 들고 있다가 `next` 가 불릴 때 다음 구분자를 찾는다. heap 할당이 없다.
+/// - 조각의 lifetime 은 나눌 문자열 ('s) 만 따르고 구분자와는 무관하다.
+///   구분자로 쓴 `String` 이 먼저 drop 되어도 조각은 계속 쓸 수 있다. (guides 의 foundation1 example_12 가 의도했던 것)
+/// - `str::split` 과 같은 결과 : 구분자가 맨 앞 / 맨 뒤에 있거나 연속되면 빈 조각이 나온다.
+///   `inclusive` 는 `str::split_inclusive` 와 같이 조각 끝에 구분자를 붙이고 마지막 빈 조각은 만들지 않는다.
+/// - 구분자가 `ReverseDelimiter` 이면 뒤에서부터도 꺼낼 수 있고 (`DoubleEndedIterator`),
+///   앞뒤에서 번갈아 꺼내도 같은 조각이 두 번 나오지 않는다.
+pub struct StrSplit<'s, D> {
+    remainder: &'s str,
+    delimiter: D,
+    inclusive: bool,
+    finished: bool,
+}
+
+impl<'s, D: Delimiter> StrSplit<'s, D> {
+    pub fn new(haystack: &'s str, delimiter: D) -> Self {
+        StrSplit {
+            remainder: haystack,
+            delimiter,
+            inclusive: false,
+            finished: false,
+        }
+    }
+
+    /// 각 조각의 끝에 구분자를 포함
+    pub fn inclusive(haystack: &'s str, delimiter: D) -> Self {
+        StrSplit {
+            inclusive: true,
+            ..StrSplit::new(haystack, delimiter)
+        }
+    }
+
+    /// 아직 꺼내지 않은 부분 (다 꺼냈으면 None)
+    pub fn remainder(&self) -> Option<&'s str> {
+        (!self.finished).then_some(self.remainder)
+    }
+
+    /// 남은 부분 전체를 마지막 조각으로 꺼내고 끝낸다
+    fn finish(&mut self) -> Option<&'s str> {
+        if self.finished || (self.inclusive && self.remainder.is_empty()) {
+            self.finished = true;
+            return None;
+        }
+        self.finished = true;
+        Some(self.remainder)
+    }
+}
+
+impl<'s, D: Delimiter> Iterator for StrSplit<'s, D> {
+    type Item = &'s str;
+
+    fn next(&mut self) -> Option<&'s str> {
+        if self.finished {
+            return None;
+        }
+        let Some(m) = self.delimiter.find(self.remainder) else {
+            return self.finish();
+        };
+        let end = if self.inclusive { m.end } else { m.start };
+        let token = &self.remainder[..end];
+        self.remainder = &self.remainder[m.end..];
+        Some(token)
+    }
+}
+
+impl<'s, D: ReverseDelimiter> DoubleEndedIterator for StrSplit<'s, D> {
+    fn next_back(&mut self) -> Option<&'s str> {
+        if self.finished || (self.inclusive && self.remainder.is_empty()) {
+            return self.finish();
+        }
+        let Some(mut m) = self.delimiter.rfind(self.remainder) else {
+            return self.finish();
+        };
+        if self.inclusive && m.end == self.remainder.len() {
+            // 끝의 구분자는 마지막 조각에 붙으므로, 조각의 시작은 그 앞의 구분자 뒤
+            match self.delimiter.rfind(&self.remainder[..m.start]) {
+                Some(prev) => m = prev,
+                None => return self.finish(),
+            }
+        }
+        let token = &self.remainder[m.end..];
+        let end = if self.inclusive { m.end } else { m.start };
+        self.remainder = &self.remainder[..end];
+        Some(token)
+    }
+}
+
+/// 최대 n 개로 나누는 iterator (`str::splitn`). 마지막 조각은 남은 부분 전체.
+pub struct SplitN<'s, D> {
+    inner: StrSplit<'s, D>,
+    count: usize,
+}
+
+impl<'s, D: Delimiter> SplitN<'s, D> {
+    pub fn new(haystack: &'s str, n: usize, delimiter: D) -> Self {
+        SplitN {
+            inner: StrSplit::new(haystack, delimiter),
+            count: n,
+        }
+    }
+}
+
+impl<'s, D: Delimiter> Iterator for SplitN<'s, D> {
+    type Item = &'s str;
+
+    fn next(&mut self) -> Option<&'s str> {
+        match self.count {
+            0 => None,
+            1 => {
+                self.count = 0;
+                self.inner.finish()
+            }
+            _ => {
+                self.count -= 1;
+                self.inner.next()
+            }
+        }
+    }
+}
+
+/// 따옴표 안의 구분자는 무시하고 나누는 iterator
+///
+/// - `a,"b,c",d` 를 ',' 로 나누면 `a`, `"b,c"`, `d`.
+/// - 따옴표는 조각의 맨 앞 (문자열의 처음이나 구분자 바로 뒤) 에서만 열린다. 그 밖의 따옴표 (`5" pipe`) 는 보통 문자이다.
+/// - 따옴표 안에서 따옴표는 두 번 써서 (`""`) 표현한다. 닫는 따옴표 뒤의 문자 (`"a"b`) 는 다음 구분자까지 같은 조각이다.
+/// - 조각은 따옴표를 그대로 포함한 원래 slice 이다. 따옴표를 벗기려면 [`unquote`].
+/// - 닫히지 않은 따옴표로 시작한 조각은 문자열 끝까지 한 조각.
+/// - 따옴표의 짝을 맞추려면 앞에서부터 읽어야 하므로 `DoubleEndedIterator` 가 아니다.
+pub struct QuotedSplit<'s, D> {
+    remainder: &'s str,
+    delimiter: D,
+    quote: char,
+    finished: bool,
+}
+
+impl<'s, D: Delimiter> QuotedSplit<'s, D> {
+    pub fn new(haystack: &'s str, delimiter: D, quote: char) -> Self {
+        QuotedSplit {
+            remainder: haystack,
+            delimiter,
+            quote,
+            finished: false,
+        }
+    }
+}
+
+impl<'s, D: Delimiter> Iterator for QuotedSplit<'s, D> {
+    type Item = &'s str;
+
+    fn next(&mut self) -> Option<&'s str> {
+        if self.finished {
+            return None;
+        }
+        let rest = self.remainder;
+        // 따옴표로 시작하면 닫는 따옴표 뒤에서부터 구분자를 찾는다
+        let from = if rest.starts_with(self.quote) {
+            quoted_end(rest, self.quote)
+        } else {
+            Some(0)
+        };
+        if let Some(m) = from.and_then(|from| {
+            let m = self.delimiter.find(&rest[from..])?;
+            Some(from + m.start..from + m.end)
+        }) {
+            self.remainder = &rest[m.end..];
+            return Some(&rest[..m.start]);
+        }
+        self.finished = true;
+        Some(rest)
+    }
+}
+
+/// 따옴표로 시작하는 조각에서 닫는 따옴표 바로 뒤의 위치. 닫히지 않았으면 None.
+///
+/// `QuotedSplit` 과 csv reader 가 같은 규칙으로 따옴표를 따라가도록 한 곳에 둔다.
+pub(crate) fn quoted_end(token: &str, quote: char) -> Option<usize> {
+    let q = quote.len_utf8();
+    let mut from = q;
+    loop {
+        let close = from + token[from..].find(quote)?;
+        // "" 는 따옴표 문자이므로 건너뛰고 계속 찾는다
+        if token[close + q..].starts_with(quote) {
+            from = close + 2 * q;
+        } else {
+            return Some(close + q);
+        }
+    }
+}
+
+/// 양 끝이 따옴표인 조각에서 따옴표를 벗기고 `""` 를 `"` 로 바꾼다
+///
+/// 바꿀 `""` 가 없으면 할당 없이 원래 문자열의 slice 를 돌려준다.
+pub fn unquote(token: &str, quote: char) -> Cow<'_, str> {
+    let mut buf = [0; 4];
+    let q = &*quote.encode_utf8(&mut buf);
+    let Some(inner) = token.strip_prefix(q).and_then(|t| t.strip_suffix(q)) else {
+        return Cow::Borrowed(token);
+    };
+    if inner.contains(quote) {
+        Cow::Owned(inner.replace(&q.repeat(2), q))
+    } else {
+        Cow::Borrowed(inner)
+    }
+}
+
+/// 여러 종류의 구분자로 나눈 결과가 std 의 split / rsplit / split_inclusive 와 같은지
+#[test]
+fn against_std() {
+    let inputs = [
+        "",
+        ",",
+        "a",
+        "a,b,c",
+        ",a,,b,",
+        "한,글,,자",
+        "key=value; other = 1;; last",
+    ];
+    for input in inputs {
+        let expected: Vec<_> = input.split(',').collect();
+        assert_eq!(StrSplit::new(input, ',').collect::<Vec<_>>(), expected);
+        assert_eq!(StrSplit::new(input, ",").collect::<Vec<_>>(), expected);
+        assert_eq!(
+            StrSplit::new(input, |c| c == ',').collect::<Vec<_>>(),
+            expected
+        );
+        assert!(StrSplit::new(input, ',').rev().eq(input.rsplit(',')));
+        assert!(StrSplit::new(input, ['=', ';']).eq(input.split(['=', ';'])));
+        assert!(StrSplit::new(input, ['=', ';'])
+            .rev()
+            .eq(input.rsplit(['=', ';'])));
+        assert!(StrSplit::new(input, "; ").eq(input.split("; ")));
+
+        let inclusive: Vec<_> = input.split_inclusive(',').collect();
+        assert_eq!(
+            StrSplit::inclusive(input, ',').collect::<Vec<_>>(),
+            inclusive
+        );
+        assert!(StrSplit::inclusive(input, ',')
+            .rev()
+            .eq(input.split_inclusive(',').rev()));
+
+        for n in 0..4 {
+            assert!(
+                SplitN::new(input, n, ',').eq(input.splitn(n, ',')),
+                "{:?} {}",
+                input,
+                n
+            );
+        }
+    }
+
+    // 여러 문자열 구분자 : 같은 위치면 긴 것
+    let tokens: Vec<_> = StrSplit::new("a->b=>c-d", ["->", "=>", "-"]).collect();
+    assert_eq!(tokens, ["a", "b", "c", "d"]);
+
+    // 겹칠 수 있는 구분자는 앞에서부터만 (ReverseDelimiter 가 아니라서 rev 는 compile 되지 않는다)
+    assert!(StrSplit::new("aaa", "aa").eq("aaa".split("aa")));
+}
+
+/// 앞뒤에서 번갈아 꺼내도 모든 조각이 한 번씩만 나오고, 조각은 원래 문자열을 가리킨다
+#[test]
+fn double_ended() {
+    let line = "2024-01-01 INFO  request done  path=/a";
+    for inclusive in [false, true] {
+        let split = |line| {
+            if inclusive {
+                StrSplit::inclusive(line, ' ')
+            } else {
+                StrSplit::new(line, ' ')
+            }
+        };
+        let expected: Vec<_> = split(line).collect();
+        let mut tokens = split(line);
+        let (mut front, mut back) = (Vec::new(), Vec::new());
+        for i in 0.. {
+            let token = if i % 3 == 0 {
+                tokens.next_back()
+            } else {
+                tokens.next()
+            };
+            match token {
+                Some(t) if i % 3 == 0 => back.push(t),
+                Some(t) => front.push(t),
+                None => break,
+            }
+        }
+        front.extend(back.into_iter().rev());
+        assert_eq!(front, expected);
+        assert!(front
+            .iter()
+            .all(|t| line.as_bytes().as_ptr_range().contains(&t.as_ptr()) || t.is_empty()));
+    }
+
+    // 조각은 구분자보다 오래 살 수 있다
+    let document = "a::b::c";
+    let tokens: Vec<&str> = {
+        let delimiter = String::from("::");
+        StrSplit::new(document, delimiter.as_str()).collect()
+    };
+    assert_eq!(tokens, ["a", "b", "c"]);
+}
+
+#[test]
+fn quoted() {
+    let line = r#"1,"Kim, Minsu","say ""hi""",,"unterminated, rest"#;
+    let fields: Vec<_> = QuotedSplit::new(line, ',', '"').collect();
+    assert_eq!(
+        fields,
+        [
+            "1",
+            r#""Kim, Minsu""#,
+            r#""say ""hi""""#,
+            "",
+            r#""unterminated, rest"#
+        ]
+    );
+    assert_eq!(unquote(fields[1], '"'), "Kim, Minsu");
+    assert!(matches!(unquote(fields[1], '"'), Cow::Borrowed(_)));
+    assert_eq!(unquote(fields[2], '"'), r#"say "hi""#);
+    assert_eq!(unquote(fields[0], '"'), "1");
+
+    assert!(QuotedSplit::new("a,b", ',', '"').eq(["a", "b"]));
+    assert!(QuotedSplit::new("", ',', '"').eq([""]));
+    assert!(QuotedSplit::new("'x; y';z", ';', '\'').eq(["'x; y'", "z"]));
+    // 조각 중간의 따옴표는 열지 않는다
+    assert!(QuotedSplit::new("5\" pipe,x", ',', '"').eq(["5\" pipe", "x"]));
+    assert!(QuotedSplit::new(r#""a"b,c"#, ',', '"').eq([r#""a"b"#, "c"]));
+}
@@ -0,0 +1,325 @@
+use std::error::Error;
+use std::fmt;
+
+/// 백분율
+pub mod percent;
+/// byte 크기 단위 (Bytes ..= TiB, 1024 배씩)
+pub mod size;
+/// 시간 단위 (Nanoseconds ..= Days) 와 std::time::Duration 변환
+pub mod time;
+
+pub use percent::Percent;
+pub use size::{Bytes, GiB, KiB, MiB, TiB};
+pub use time::{Days, Hours, Microseconds, Milliseconds, Minutes, Nanoseconds, Seconds};
+
+/// 같은 차원 (dimension) 의 단위끼리만 서로 바꾸고 더할 수 있다
+pub trait Dimension {
+    /// 문자열에서 쓰는 단위 이름과 기본 단위로 몇 개인지
+    const UNITS: &'static [(&'static str, u64)];
+}
+
+/// 시간. 기본 단위는 nanosecond.
+pub enum Time {}
+
+/// 크기. 기본 단위는 byte.
+pub enum Size {}
+
+/// u64 하나를 감싼 단위 newtype 의 공통 interface
+pub trait Quantity: Copy {
+    type Dimension: Dimension;
+    /// 이 단위 하나가 기본 단위로 몇 개인지
+    const SCALE: u64;
+    /// Display 에 붙이는 단위 이름
+    const SUFFIX: &'static str;
+
+    fn from_value(value: u64) -> Self;
+    fn to_value(self) -> u64;
+
+    /// 다른 단위로 바꾼다. 큰 단위로 바꾸면 나머지는 버리고, u64 를 넘으면 None.
+    fn checked_convert<U>(self) -> Option<U>
+    where
+        U: Quantity<Dimension = Self::Dimension>,
+    {
+        let base = u128::from(self.to_value()) * u128::from(Self::SCALE);
+        u64::try_from(base / u128::from(U::SCALE))
+            .ok()
+            .map(U::from_value)
+    }
+}
+
+/// 단위가 붙은 문자열을 읽지 못한 이유
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseUnitError {
+    /// 숫자 부분이 없거나 숫자가 아님
+    InvalidNumber,
+    /// 이 차원에 없는 단위 이름
+    UnknownUnit(String),
+    /// 목표 단위로 나누어 떨어지지 않음 (예: "1.5s" 를 Seconds 로)
+    Inexact,
+    /// u64 를 넘음
+    Overflow,
+}
+
+impl fmt::Display for ParseUnitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseUnitError::InvalidNumber => write!(f, "invalid number"),
+            ParseUnitError::UnknownUnit(unit) => write!(f, "unknown unit {:?}", unit),
+            ParseUnitError::Inexact => write!(f, "value is not a whole number of the target unit"),
+            ParseUnitError::Overflow => write!(f, "value is too large"),
+        }
+    }
+}
+
+impl Error for ParseUnitError {}
+
+/// "1.5s", "64 MiB", "42" (단위가 없으면 Q 의 단위) 같은 문자열을 Q 로 읽는다
+///
+/// 소수는 f64 를 거치지 않고 정수로 계산하므로, 목표 단위로 정확히 나누어 떨어질 때만 받아들인다.
+fn parse<Q: Quantity>(s: &str) -> Result<Q, ParseUnitError> {
+    let s = s.trim();
+    let split = s
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(s.len());
+    let (number, unit) = (&s[..split], s[split..].trim_start());
+    // "-1s" 처럼 숫자로 시작하지 않으면 단위를 보기 전에 거절한다 (부호는 받지 않음)
+    if number.is_empty() {
+        return Err(ParseUnitError::InvalidNumber);
+    }
+
+    let scale = if unit.is_empty() {
+        Q::SCALE
+    } else {
+        Q::Dimension::UNITS
+            .iter()
+            .find(|(name, _)| *name == unit)
+            .map(|(_, scale)| *scale)
+            .ok_or_else(|| ParseUnitError::UnknownUnit(unit.to_owned()))?
+    };
+
+    // "1.25" -> 125 / 10^2 (소수 끝의 0 은 값을 바꾸지 않으므로 버린다)
+    let (int, frac) = number.split_once('.').unwrap_or((number, ""));
+    if int.is_empty() && frac.is_empty() {
+        return Err(ParseUnitError::InvalidNumber);
+    }
+    let frac = frac.trim_end_matches('0');
+    let mut mantissa: u128 = 0;
+    for c in int.chars().chain(frac.chars()) {
+        let digit = c.to_digit(10).ok_or(ParseUnitError::InvalidNumber)?;
+        mantissa = mantissa
+            .checked_mul(10)
+            .and_then(|m| m.checked_add(u128::from(digit)))
+            .ok_or(ParseUnitError::Overflow)?;
+    }
+    let denominator = u32::try_from(frac.len())
+        .ok()
+        .and_then(|n| 10u128.checked_pow(n))
+        .ok_or(ParseUnitError::Overflow)?;
+
+    let base = mantissa
+        .checked_mul(u128::from(scale))
+        .ok_or(ParseUnitError::Overflow)?;
+    let target = denominator
+        .checked_mul(u128::from(Q::SCALE))
+        .ok_or(ParseUnitError::Overflow)?;
+    if base % target != 0 {
+        return Err(ParseUnitError::Inexact);
+    }
+    u64::try_from(base / target)
+        .map(Q::from_value)
+        .map_err(|_| ParseUnitError::Overflow)
+}
+
+/// 단위 newtype 하나를 정의한다
+///
+/// - 연산은 같은 단위이거나 나머지 없이 바꿀 수 있는 (`Self: TryFrom<R>`, 즉 더 큰) 단위하고만 된다.
+///   `Milliseconds + Seconds` 는 Milliseconds 이고, `Seconds + Milliseconds` 는 compile 되지 않는다.
+/// - 연산자는 overflow 에서 panic, `checked_*` 는 None.
+macro_rules! quantity {
+    ($(#[$meta:meta])* $name:ident, $dimension:ty, $scale:expr, $suffix:literal) => {
+        $(#[$meta])*
+        ///
+        /// 연산자는 u64 를 넘으면 panic 한다. 넘을 수 있으면 `checked_*` 를 쓴다.
+        /// 더 큰 단위로부터의 변환은 u64 를 넘을 수 있으므로 `From` 이 아니라 `TryFrom` 이다.
+        #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        #[repr(transparent)]
+        pub struct $name(u64);
+
+        impl $name {
+            pub const ZERO: $name = $name(0);
+            pub const MAX: $name = $name(u64::MAX);
+
+            pub const fn new(value: u64) -> Self {
+                $name(value)
+            }
+
+            pub const fn value(self) -> u64 {
+                self.0
+            }
+
+            pub fn checked_add<R>(self, rhs: R) -> Option<Self>
+            where
+                R: $crate::units::Quantity<Dimension = $dimension>,
+                Self: TryFrom<R>,
+            {
+                let rhs: Self = $crate::units::Quantity::checked_convert(rhs)?;
+                self.0.checked_add(rhs.0).map($name)
+            }
+
+            pub fn checked_sub<R>(self, rhs: R) -> Option<Self>
+            where
+                R: $crate::units::Quantity<Dimension = $dimension>,
+                Self: TryFrom<R>,
+            {
+                let rhs: Self = $crate::units::Quantity::checked_convert(rhs)?;
+                self.0.checked_sub(rhs.0).map($name)
+            }
+
+            pub fn saturating_add<R>(self, rhs: R) -> Self
+            where
+                R: $crate::units::Quantity<Dimension = $dimension>,
+                Self: TryFrom<R>,
+            {
+                self.checked_add(rhs).unwrap_or(Self::MAX)
+            }
+
+            pub fn saturating_sub<R>(self, rhs: R) -> Self
+            where
+                R: $crate::units::Quantity<Dimension = $dimension>,
+                Self: TryFrom<R>,
+            {
+                self.checked_sub(rhs).unwrap_or(Self::ZERO)
+            }
+
+            pub fn checked_mul(self, rhs: u64) -> Option<Self> {
+                self.0.checked_mul(rhs).map($name)
+            }
+
+            pub fn checked_div(self, rhs: u64) -> Option<Self> {
+                self.0.checked_div(rhs).map($name)
+            }
+        }
+
+        impl $crate::units::Quantity for $name {
+            type Dimension = $dimension;
+            const SCALE: u64 = $scale;
+            const SUFFIX: &'static str = $suffix;
+
+            fn from_value(value: u64) -> Self {
+                $name(value)
+            }
+
+            fn to_value(self) -> u64 {
+                self.0
+            }
+        }
+
+        impl<R> std::ops::Add<R> for $name
+        where
+            R: $crate::units::Quantity<Dimension = $dimension>,
+            $name: TryFrom<R>,
+        {
+            type Output = $name;
+
+            fn add(self, rhs: R) -> $name {
+                self.checked_add(rhs)
+                    .expect(concat!("overflow when adding to ", stringify!($name)))
+            }
+        }
+
+        impl<R> std::ops::Sub<R> for $name
+        where
+            R: $crate::units::Quantity<Dimension = $dimension>,
+            $name: TryFrom<R>,
+        {
+            type Output = $name;
+
+            fn sub(self, rhs: R) -> $name {
+                self.checked_sub(rhs)
+                    .expect(concat!("overflow when subtracting from ", stringify!($name)))
+            }
+        }
+
+        impl<R> std::ops::AddAssign<R> for $name
+        where
+            R: $crate::units::Quantity<Dimension = $dimension>,
+            $name: TryFrom<R>,
+        {
+            fn add_assign(&mut self, rhs: R) {
+                *self = *self + rhs;
+            }
+        }
+
+        impl<R> std::ops::SubAssign<R> for $name
+        where
+            R: $crate::units::Quantity<Dimension = $dimension>,
+            $name: TryFrom<R>,
+        {
+            fn sub_assign(&mut self, rhs: R) {
+                *self = *self - rhs;
+            }
+        }
+
+        impl std::ops::Mul<u64> for $name {
+            type Output = $name;
+
+            fn mul(self, rhs: u64) -> $name {
+                self.checked_mul(rhs)
+                    .expect(concat!("overflow when multiplying ", stringify!($name)))
+            }
+        }
+
+        impl std::ops::Div<u64> for $name {
+            type Output = $name;
+
+            fn div(self, rhs: u64) -> $name {
+                $name(self.0 / rhs)
+            }
+        }
+
+        impl std::iter::Sum for $name {
+            fn sum<I: Iterator<Item = $name>>(iter: I) -> $name {
+                iter.fold($name::ZERO, |a, b| a + b)
+            }
+        }
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "{}{}", self.0, $suffix)
+            }
+        }
+
+        impl std::str::FromStr for $name {
+            type Err = $crate::units::ParseUnitError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                $crate::units::parse(s)
+            }
+        }
+    };
+}
+
+/// 작은 단위부터 나열하면, 각 단위에 자기보다 큰 단위로부터의 `TryFrom` 을 만든다
+///
+/// 큰 단위의 값은 작은 단위로 바꾸면 u64 를 넘을 수 있으므로 (예: `Days::MAX` 를 Nanoseconds 로) `From` 이 아니다.
+macro_rules! widen {
+    ($fine:ident $(, $coarse:ident)*) => {
+        $(
+            impl TryFrom<$coarse> for $fine {
+                type Error = std::num::TryFromIntError;
+
+                fn try_from(value: $coarse) -> Result<$fine, std::num::TryFromIntError> {
+                    let base = u128::from(value.value())
+                        * u128::from(<$coarse as $crate::units::Quantity>::SCALE);
+                    u64::try_from(base / u128::from(<$fine as $crate::units::Quantity>::SCALE))
+                        .map($fine::new)
+                }
+            }
+        )*
+        widen!($($coarse),*);
+    };
+    () => {};
+}
+
+use quantity;
+use widen;
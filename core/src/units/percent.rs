@@ -0,0 +1,109 @@
+use super::{ParseUnitError, Quantity};
+use std::fmt;
+use std::ops::{Add, Sub};
+use std::str::FromStr;
+
+/// 백분율 (50% 는 `Percent::new(50.0)`)
+///
+/// 소수 백분율 ("12.5%") 이 흔하므로 다른 단위와 달리 f64 를 감싼다. NaN 은 만들 수 없다.
+#[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd)]
+#[repr(transparent)]
+pub struct Percent(f64);
+
+impl Percent {
+    pub const ZERO: Percent = Percent(0.0);
+    pub const HUNDRED: Percent = Percent(100.0);
+
+    pub fn new(value: f64) -> Self {
+        assert!(!value.is_nan(), "percent must not be NaN");
+        Percent(value)
+    }
+
+    /// 0.25 -> 25%
+    pub fn from_ratio(ratio: f64) -> Self {
+        Percent::new(ratio * 100.0)
+    }
+
+    pub fn value(self) -> f64 {
+        self.0
+    }
+
+    pub fn ratio(self) -> f64 {
+        self.0 / 100.0
+    }
+
+    /// 단위 값의 몇 % (소수점 아래는 버림, 음수는 0)
+    pub fn of<Q: Quantity>(self, quantity: Q) -> Q {
+        Q::from_value((quantity.to_value() as f64 * self.ratio()) as u64)
+    }
+
+    /// part 가 whole 의 몇 % 인지 (whole 이 0 이면 None)
+    pub fn between<Q: Quantity>(part: Q, whole: Q) -> Option<Self> {
+        if whole.to_value() == 0 {
+            return None;
+        }
+        Some(Percent::from_ratio(
+            part.to_value() as f64 / whole.to_value() as f64,
+        ))
+    }
+}
+
+impl Add for Percent {
+    type Output = Percent;
+
+    fn add(self, rhs: Percent) -> Percent {
+        Percent::new(self.0 + rhs.0)
+    }
+}
+
+impl Sub for Percent {
+    type Output = Percent;
+
+    fn sub(self, rhs: Percent) -> Percent {
+        Percent::new(self.0 - rhs.0)
+    }
+}
+
+impl fmt::Display for Percent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}%", self.0)
+    }
+}
+
+/// "12.5%", "-3 %", "40" (% 는 생략 가능)
+impl FromStr for Percent {
+    type Err = ParseUnitError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let number = s.strip_suffix('%').unwrap_or(s).trim_end();
+        match number.parse::<f64>() {
+            Ok(value) if value.is_finite() => Ok(Percent(value)),
+            _ => Err(ParseUnitError::InvalidNumber),
+        }
+    }
+}
+
+#[test]
+fn percent() {
+    use super::{Bytes, Milliseconds};
+
+    let p: Percent = "12.5%".parse().unwrap();
+    assert_eq!(p, Percent::new(12.5));
+    assert_eq!(p.to_string().parse(), Ok(p));
+    assert_eq!("40".parse(), Ok(Percent::new(40.0)));
+    assert!("NaN%".parse::<Percent>().is_err());
+    assert!("%".parse::<Percent>().is_err());
+
+    assert_eq!(Percent::new(50.0).of(Bytes::new(1_001)), Bytes::new(500));
+    assert_eq!(
+        Percent::from_ratio(0.1).of(Milliseconds::new(2_000)),
+        Milliseconds::new(200)
+    );
+    assert_eq!(
+        Percent::between(Bytes::new(1), Bytes::new(4)),
+        Some(Percent::new(25.0))
+    );
+    assert_eq!(Percent::between(Bytes::ZERO, Bytes::ZERO), None);
+    assert_eq!(Percent::HUNDRED - Percent::new(30.0), Percent::new(70.0));
+}
@@ -0,0 +1,44 @@
+use super::{quantity, widen, Dimension, Size};
+
+impl Dimension for Size {
+    const UNITS: &'static [(&'static str, u64)] = &[
+        ("B", 1),
+        ("KiB", 1 << 10),
+        ("MiB", 1 << 20),
+        ("GiB", 1 << 30),
+        ("TiB", 1 << 40),
+    ];
+}
+
+quantity!(Bytes, Size, 1, "B");
+quantity!(KiB, Size, 1 << 10, "KiB");
+quantity!(MiB, Size, 1 << 20, "MiB");
+quantity!(GiB, Size, 1 << 30, "GiB");
+quantity!(TiB, Size, 1 << 40, "TiB");
+
+widen!(Bytes, KiB, MiB, GiB, TiB);
+
+impl Bytes {
+    /// 메모리 / 파일 크기를 usize 로 (32 bit target 에서 넘으면 None)
+    pub fn as_usize(self) -> Option<usize> {
+        usize::try_from(self.value()).ok()
+    }
+}
+
+/// 요청 body 크기 제한처럼 설정 문자열로 받은 크기와 비교
+#[test]
+fn body_limit() {
+    let limit: Bytes = "64MiB".parse().unwrap();
+    assert_eq!(Bytes::try_from(MiB::new(64)), Ok(limit));
+    assert_eq!(limit.as_usize(), Some(64 * 1024 * 1024));
+
+    let received = KiB::new(65_535) + MiB::new(0);
+    let received = Bytes::try_from(received).unwrap();
+    assert!(received < limit);
+    assert!(received + KiB::new(2) > limit);
+
+    assert_eq!("1.5KiB".parse(), Ok(Bytes::new(1_536)));
+    assert_eq!("0.5GiB".parse(), Ok(MiB::new(512)));
+    assert!("1.1B".parse::<Bytes>().is_err());
+    assert_eq!(GiB::new(3).to_string(), "3GiB");
+}
@@ -0,0 +1,209 @@
+use super::{quantity, widen, Dimension, Quantity, Time};
+use std::num::TryFromIntError;
+use std::time::Duration;
+
+impl Dimension for Time {
+    const UNITS: &'static [(&'static str, u64)] = &[
+        ("ns", 1),
+        ("us", 1_000),
+        ("µs", 1_000),
+        ("ms", 1_000_000),
+        ("s", 1_000_000_000),
+        ("min", 60 * 1_000_000_000),
+        ("h", 60 * 60 * 1_000_000_000),
+        ("d", 24 * 60 * 60 * 1_000_000_000),
+    ];
+}
+
+quantity!(Nanoseconds, Time, 1, "ns");
+quantity!(Microseconds, Time, 1_000, "us");
+quantity!(Milliseconds, Time, 1_000_000, "ms");
+quantity!(
+    /// guides 의 idioms1 Second 를 일반화한 것
+    Seconds,
+    Time,
+    1_000_000_000,
+    "s"
+);
+quantity!(Minutes, Time, 60 * 1_000_000_000, "min");
+quantity!(Hours, Time, 60 * 60 * 1_000_000_000, "h");
+quantity!(Days, Time, 24 * 60 * 60 * 1_000_000_000, "d");
+
+widen!(
+    Nanoseconds,
+    Microseconds,
+    Milliseconds,
+    Seconds,
+    Minutes,
+    Hours,
+    Days
+);
+
+/// Duration 에서 바꾸는 변환. 단위보다 작은 부분은 버리고, u64 를 넘으면 error.
+macro_rules! from_duration {
+    ($($name:ident),*) => {
+        $(
+            impl TryFrom<Duration> for $name {
+                type Error = TryFromIntError;
+
+                fn try_from(duration: Duration) -> Result<$name, TryFromIntError> {
+                    let value = duration.as_nanos() / u128::from(<$name as Quantity>::SCALE);
+                    u64::try_from(value).map($name::new)
+                }
+            }
+        )*
+    };
+}
+
+from_duration!(
+    Nanoseconds,
+    Microseconds,
+    Milliseconds,
+    Seconds,
+    Minutes,
+    Hours,
+    Days
+);
+
+/// Seconds 이하의 단위는 초가 u64 를 넘지 않으므로 항상 Duration 으로 바꿀 수 있다
+macro_rules! into_duration {
+    ($($name:ident),*) => {
+        $(
+            impl From<$name> for Duration {
+                fn from(value: $name) -> Duration {
+                    let nanos = u128::from(value.value()) * u128::from(<$name as Quantity>::SCALE);
+                    Duration::new((nanos / 1_000_000_000) as u64, (nanos % 1_000_000_000) as u32)
+                }
+            }
+        )*
+    };
+}
+
+into_duration!(Nanoseconds, Microseconds, Milliseconds, Seconds);
+
+/// Minutes 이상은 Duration 의 범위 (u64 초) 를 넘을 수 있다
+macro_rules! try_into_duration {
+    ($($name:ident),*) => {
+        $(
+            impl TryFrom<$name> for Duration {
+                type Error = TryFromIntError;
+
+                fn try_from(value: $name) -> Result<Duration, TryFromIntError> {
+                    let nanos = u128::from(value.value()) * u128::from(<$name as Quantity>::SCALE);
+                    let secs = u64::try_from(nanos / 1_000_000_000)?;
+                    Ok(Duration::new(secs, (nanos % 1_000_000_000) as u32))
+                }
+            }
+        )*
+    };
+}
+
+try_into_duration!(Minutes, Hours, Days);
+
+#[test]
+fn conversion_and_arithmetic() {
+    assert_eq!(
+        Milliseconds::try_from(Seconds::new(3)),
+        Ok(Milliseconds::new(3_000))
+    );
+    assert_eq!(Seconds::try_from(Days::new(1)), Ok(Seconds::new(86_400)));
+    // 설정 값이 커도 panic 하지 않는다
+    assert!(Nanoseconds::try_from(Days::new(300_000)).is_err());
+
+    // 작은 단위 쪽으로 맞춰서 계산
+    let timeout = Milliseconds::new(250) + Seconds::new(2);
+    assert_eq!(timeout, Milliseconds::new(2_250));
+    let mut total = Minutes::new(1);
+    total += Hours::new(1);
+    total -= Minutes::new(30);
+    assert_eq!(total, Minutes::new(31));
+    assert_eq!(
+        [Seconds::new(1), Seconds::new(2)]
+            .into_iter()
+            .sum::<Seconds>()
+            * 3,
+        Seconds::new(9)
+    );
+
+    assert_eq!(Seconds::new(1).checked_sub(Seconds::new(2)), None);
+    assert_eq!(Nanoseconds::MAX.checked_add(Nanoseconds::new(1)), None);
+    assert_eq!(Nanoseconds::new(5).checked_add(Days::MAX), None);
+    assert_eq!(
+        Seconds::new(1).saturating_sub(Minutes::new(1)),
+        Seconds::ZERO
+    );
+
+    // 큰 단위로는 명시적으로 (나머지는 버림)
+    assert_eq!(
+        Milliseconds::new(2_999).checked_convert::<Seconds>(),
+        Some(Seconds::new(2))
+    );
+    assert_eq!(Days::MAX.checked_convert::<Nanoseconds>(), None);
+
+    assert_eq!(
+        Duration::from(Milliseconds::new(1_500)),
+        Duration::from_millis(1_500)
+    );
+    assert_eq!(
+        Seconds::try_from(Duration::from_millis(2_500)),
+        Ok(Seconds::new(2))
+    );
+    assert!(Nanoseconds::try_from(Duration::MAX).is_err());
+    assert_eq!(Duration::from(Seconds::MAX), Duration::from_secs(u64::MAX));
+    assert_eq!(
+        Duration::try_from(Minutes::new(2)),
+        Ok(Duration::from_secs(120))
+    );
+    assert!(Duration::try_from(Days::MAX).is_err());
+}
+
+#[test]
+fn parse_and_display() {
+    use super::ParseUnitError;
+
+    assert_eq!("1.5s".parse(), Ok(Milliseconds::new(1_500)));
+    assert_eq!(" 2 h ".parse(), Ok(Minutes::new(120)));
+    assert_eq!("250µs".parse(), Ok(Nanoseconds::new(250_000)));
+    assert_eq!("42".parse(), Ok(Seconds::new(42)));
+    assert_eq!(".5min".parse(), Ok(Seconds::new(30)));
+
+    assert_eq!("1.5s".parse::<Seconds>(), Err(ParseUnitError::Inexact));
+    assert_eq!("90s".parse::<Minutes>(), Err(ParseUnitError::Inexact));
+    assert_eq!(
+        "3 fortnights".parse::<Days>(),
+        Err(ParseUnitError::UnknownUnit("fortnights".into()))
+    );
+    assert_eq!(
+        "1.2.3s".parse::<Seconds>(),
+        Err(ParseUnitError::InvalidNumber)
+    );
+    assert_eq!("s".parse::<Seconds>(), Err(ParseUnitError::InvalidNumber));
+    assert_eq!(
+        "-1s".parse::<Nanoseconds>(),
+        Err(ParseUnitError::InvalidNumber)
+    );
+    assert_eq!("+1s".parse::<Seconds>(), Err(ParseUnitError::InvalidNumber));
+    assert_eq!(
+        "1KiB".parse::<Seconds>(),
+        Err(ParseUnitError::UnknownUnit("KiB".into()))
+    );
+    assert_eq!(
+        "18446744073709551616ns".parse::<Nanoseconds>(),
+        Err(ParseUnitError::Overflow)
+    );
+    // 목표 단위의 배율과 곱하면 u128 을 넘는 소수 자릿수
+    assert_eq!(
+        "0.000000000000000000000000000001s".parse::<Seconds>(),
+        Err(ParseUnitError::Overflow)
+    );
+    assert_eq!(
+        "1.500000000000000000000000000000s".parse(),
+        Ok(Milliseconds::new(1_500))
+    );
+
+    for value in [0, 1, 59, 1_000, u64::MAX] {
+        let ms = Milliseconds::new(value);
+        assert_eq!(ms.to_string().parse(), Ok(ms));
+    }
+    assert_eq!(Minutes::new(5).to_string(), "5min");
+}